@@ -31,6 +31,9 @@ pub fn codegen(model: &smithy::Model) {
         headers.insert("x-amz-decoded-content-length");
         headers.insert("x-amz-request-id");
         headers.insert("x-amz-id-2");
+        headers.insert("x-amz-trailer");
+        headers.insert("x-amz-region-set");
+        headers.insert("x-amz-security-token");
     }
 
     declare_codegen!();
@@ -0,0 +1,40 @@
+use super::ops::Operations;
+
+use crate::declare_codegen;
+
+use heck::ToSnakeCase;
+use scoped_writer::g;
+
+pub fn codegen(ops: &Operations) {
+    declare_codegen!();
+
+    g([
+        "use super::{S3Hook, S3Layer};",
+        "",
+        "use crate::S3Operation;",
+        "use crate::dto::*;",
+        "use crate::error::S3Result;",
+        "use crate::request::S3Request;",
+        "use crate::response::S3Response;",
+        "use crate::s3_trait::S3;",
+        "",
+        "#[async_trait::async_trait]",
+        "impl<H: S3Hook> S3 for S3Layer<H> {",
+        "",
+    ]);
+
+    for op in ops.values() {
+        let method_name = op.name.to_snake_case();
+        let input = &op.input;
+        let output = &op.output;
+
+        g!("async fn {method_name}(&self, req: S3Request<{input}>) -> S3Result<S3Response<{output}>> {{");
+        g!("let op = S3Operation {{ name: \"{}\" }};", op.name);
+        g!("self.hook.call(&op, req, |req| self.inner.{method_name}(req)).await");
+        g!("}}");
+        g!();
+    }
+
+    g!("}}");
+    g!();
+}
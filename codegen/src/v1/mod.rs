@@ -6,6 +6,7 @@ mod access;
 mod dto;
 mod error;
 mod headers;
+mod layer;
 mod minio;
 mod ops;
 mod s3_trait;
@@ -73,6 +74,11 @@ pub fn run() {
         write_file(path, || access::codegen(&ops));
     }
 
+    {
+        let path = "crates/s3s/src/layer/generated.rs";
+        write_file(path, || layer::codegen(&ops));
+    }
+
     {
         let path = "crates/s3s-aws/src/conv/generated.rs";
         write_file(path, || aws_conv::codegen(&ops, &rust_types));
@@ -144,6 +144,7 @@ pub fn codegen(ops: &Operations, rust_types: &RustTypes) {
         "use crate::header::*;",
         "use crate::http;",
         "use crate::error::*;",
+        "use crate::interceptor;",
         "use crate::path::S3Path;",
         "use crate::ops::CallContext;",
         "",
@@ -560,14 +561,12 @@ fn codegen_op_http_de_multipart(op: &Operation, rust_types: &RustTypes) {
 
     g([
         "let bucket = http::unwrap_bucket(req);",
-        "let key = http::parse_field_value(&m, \"key\")?.ok_or_else(|| invalid_request!(\"missing key\"))?;",
+        "let key = m.object_key().ok_or_else(|| invalid_request!(\"missing key\"))?;",
         "",
-        "let vec_stream = req.s3ext.vec_stream.take().expect(\"missing vec stream\");",
+        "let body: Option<StreamingBlob> = req.s3ext.file_stream.take();",
         "",
-        "let content_length = i64::try_from(vec_stream.exact_remaining_length()).map_err(|e|s3_error!(e, InvalidArgument, \"content-length overflow\"))?;",
-        "let content_length = (content_length != 0).then_some(content_length);",
-        "",
-        "let body: Option<StreamingBlob> = Some(StreamingBlob::new(vec_stream));",
+        "// the length of the file is unknown until the stream ends, unless the whole file has been received",
+        "let content_length: Option<ContentLength> = body.as_ref().and_then(http::file_content_length);",
         "",
     ]);
 
@@ -674,15 +673,20 @@ fn codegen_op_http_call(op: &Operation) {
 
     if op.name == "CompleteMultipartUpload" {
         g!("let s3 = s3.clone();");
+        g!("let interceptors = ccx.interceptors.clone();");
+        g!("let request_id = ccx.request_id.clone();");
         g!("let fut = async move {{");
-        g!("let result = s3.{method}(s3_req).await;");
+        g!(
+            "let result = interceptor::intercept(&interceptors, \"{}\", s3_req, |s3_req| s3.{method}(s3_req)).await;",
+            op.name
+        );
         g!("match result {{");
         g(["Ok(s3_resp) => {
                 let mut resp = Self::serialize_http(s3_resp.output)?;
                 resp.headers.extend(s3_resp.headers);
                 Ok(resp)
             }"]);
-        g!("Err(err) => super::serialize_error(err, true).map_err(Into::into),");
+        g!("Err(err) => super::serialize_error(super::with_request_id(err, &request_id), true).map_err(Into::into),");
         g!("}}");
         g!("}};");
         g!("let mut resp = http::Response::with_status(http::StatusCode::OK);");
@@ -693,14 +697,12 @@ fn codegen_op_http_call(op: &Operation) {
             "http::add_opt_header(&mut resp, \"trailer\", Some([X_AMZ_SERVER_SIDE_ENCRYPTION_BUCKET_KEY_ENABLED.as_str(), X_AMZ_EXPIRATION.as_str(), X_AMZ_REQUEST_CHARGED.as_str(), X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID.as_str(), X_AMZ_SERVER_SIDE_ENCRYPTION.as_str(), X_AMZ_VERSION_ID.as_str()].join(\",\")))?;"
         );
     } else {
-        g!("let result = s3.{method}(s3_req).await;");
-
-        g([
-            "let s3_resp = match result {",
-            "    Ok(val) => val,",
-            "    Err(err) => return super::serialize_error(err, false),",
-            "};",
-        ]);
+        g!(
+            "let result = interceptor::intercept(ccx.interceptors, \"{}\", s3_req, |s3_req| s3.{method}(s3_req)).await;",
+            op.name
+        );
+
+        g!("let s3_resp = result?;");
 
         g!("let mut resp = Self::serialize_http(s3_resp.output)?;");
 
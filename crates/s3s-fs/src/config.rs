@@ -0,0 +1,353 @@
+//! Configuration file
+//!
+//! The configuration file is in TOML format, or JSON format if the file name ends with `.json`.
+//!
+//! ```toml
+//! host = "localhost"
+//! port = 8014
+//! region = "us-east-1"
+//! domains = ["localhost"]
+//!
+//! [[users]]
+//! access_key = "AKEXAMPLE"
+//! secret_key = "SKEXAMPLE"
+//! buckets = { "photos" = ["read", "write", "list"], "logs-*" = ["read"], "*" = ["list"] }
+//! ```
+//!
+//! A bucket pattern is either a bucket name, `*` for all buckets,
+//! or a prefix followed by `*` for the buckets starting with the prefix.
+//!
+//! `ListBuckets` only returns the buckets on which the user has any permission.
+//!
+//! Only `users` are reloaded on SIGHUP.
+//! Changes of `host`, `port`, `region` and `domains` take effect after a restart.
+
+use crate::error::*;
+
+use s3s::S3Request;
+use s3s::S3Result;
+use s3s::access::{S3Access, S3AccessContext};
+use s3s::auth::{S3Auth, SecretKey};
+use s3s::dto::{CopyObjectInput, CopySource, UploadPartCopyInput};
+use s3s::path::S3Path;
+use s3s::s3_error;
+use s3s::sts::SessionStore;
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Not;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use serde::Deserialize;
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Host name to listen on
+    pub host: Option<String>,
+
+    /// Port number to listen on
+    pub port: Option<u16>,
+
+    /// The default region
+    pub region: Option<String>,
+
+    /// Domain names used for virtual-hosted-style requests
+    #[serde(default)]
+    pub domains: Vec<String>,
+
+    #[serde(default)]
+    pub users: Vec<UserConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    pub access_key: String,
+    pub secret_key: String,
+
+    /// Permissions by bucket patterns
+    #[serde(default)]
+    pub buckets: BTreeMap<String, Vec<Permission>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Getting objects and bucket configurations
+    Read,
+    /// Creating, modifying and deleting buckets and objects
+    Write,
+    /// Listing objects and uploads in buckets
+    List,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Ok(serde_json::from_str(&text)?)
+        } else {
+            Ok(toml::from_str(&text)?)
+        }
+    }
+}
+
+struct User {
+    secret_key: SecretKey,
+    buckets: BTreeMap<String, Vec<Permission>>,
+}
+
+impl User {
+    fn is_allowed(&self, bucket: &str, permission: Permission) -> bool {
+        self.buckets
+            .iter()
+            .any(|(pattern, permissions)| matches_bucket(pattern, bucket) && permissions.contains(&permission))
+    }
+}
+
+fn matches_bucket(pattern: &str, bucket: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => bucket.starts_with(prefix),
+        None => pattern == bucket,
+    }
+}
+
+/// The buckets which can be seen by the user of a `ListBuckets` request
+///
+/// It is attached to the request extensions by [`Users`].
+#[derive(Debug, Clone)]
+pub struct VisibleBuckets {
+    patterns: Vec<String>,
+}
+
+impl VisibleBuckets {
+    #[must_use]
+    pub fn contains(&self, bucket: &str) -> bool {
+        self.patterns.iter().any(|pattern| matches_bucket(pattern, bucket))
+    }
+}
+
+/// Returns the permission required by an operation on a bucket or an object.
+fn required_permission(method: &str, op_name: &str) -> Permission {
+    match op_name {
+        "ListObjects" | "ListObjectsV2" | "ListObjectVersions" | "ListMultipartUploads" => Permission::List,
+        "SelectObjectContent" => Permission::Read,
+        _ if matches!(method, "GET" | "HEAD") => Permission::Read,
+        _ => Permission::Write,
+    }
+}
+
+/// Users and their bucket permissions
+///
+/// It is both an authentication provider and an access control provider.
+/// Cloned values share the same users, which can be replaced by [`Users::set`] at runtime.
+#[derive(Clone, Default)]
+pub struct Users {
+    map: Arc<RwLock<Arc<HashMap<String, User>>>>,
+    sessions: Option<SessionStore>,
+}
+
+impl Users {
+    #[must_use]
+    pub fn new(users: &[UserConfig]) -> Self {
+        let this = Self::default();
+        this.set(users);
+        this
+    }
+
+    /// Replaces all users.
+    pub fn set(&self, users: &[UserConfig]) {
+        let map = users
+            .iter()
+            .map(|u| {
+                let user = User {
+                    secret_key: SecretKey::from(u.secret_key.as_str()),
+                    buckets: u.buckets.clone(),
+                };
+                (u.access_key.clone(), user)
+            })
+            .collect();
+        *self.map.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(map);
+    }
+
+    /// Sets the store of temporary credentials.
+    ///
+    /// The temporary credentials have the permissions of the user who requested them.
+    pub fn set_sessions(&mut self, sessions: SessionStore) {
+        self.sessions = Some(sessions);
+    }
+
+    fn get(&self) -> Arc<HashMap<String, User>> {
+        Arc::clone(&self.map.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Returns the user of an access key, which may be a temporary one.
+    fn with_user<R>(&self, access_key: &str, f: impl FnOnce(&User) -> R) -> Option<R> {
+        let map = self.get();
+        if let Some(user) = map.get(access_key) {
+            return Some(f(user));
+        }
+        let source = self.sessions.as_ref()?.source_access_key(access_key)?;
+        map.get(&source).map(f)
+    }
+
+    /// Checks whether the user has the permission on the bucket.
+    #[must_use]
+    pub fn is_allowed(&self, access_key: &str, bucket: &str, permission: Permission) -> bool {
+        self.with_user(access_key, |u| u.is_allowed(bucket, permission))
+            .unwrap_or(false)
+    }
+
+    fn visible_buckets(&self, access_key: &str) -> VisibleBuckets {
+        let patterns = self.with_user(access_key, |u| {
+            u.buckets
+                .iter()
+                .filter(|(_, permissions)| permissions.is_empty().not())
+                .map(|(pattern, _)| pattern.clone())
+                .collect()
+        });
+        VisibleBuckets {
+            patterns: patterns.unwrap_or_default(),
+        }
+    }
+
+    fn check_copy_source<T>(&self, req: &S3Request<T>, source: &CopySource) -> S3Result<()> {
+        let Some(cred) = &req.credentials else { return Err(s3_error!(AccessDenied)) };
+        let CopySource::Bucket { bucket, .. } = source else {
+            return Err(s3_error!(NotImplemented, "Copying from access points is not supported"));
+        };
+        if self.is_allowed(&cred.access_key, bucket, Permission::Read) {
+            Ok(())
+        } else {
+            Err(s3_error!(AccessDenied, "Access Denied to the copy source"))
+        }
+    }
+}
+
+impl std::fmt::Debug for Users {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Users")
+            .field("len", &self.get().len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl S3Auth for Users {
+    async fn get_secret_key(&self, access_key: &str) -> S3Result<SecretKey> {
+        match self.get().get(access_key) {
+            None => Err(s3_error!(NotSignedUp, "Your account is not signed up")),
+            Some(u) => Ok(u.secret_key.clone()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl S3Access for Users {
+    async fn check(&self, cx: &mut S3AccessContext<'_>) -> S3Result<()> {
+        let Some(cred) = cx.credentials() else {
+            return Err(s3_error!(AccessDenied, "Signature is required"));
+        };
+        let bucket = match cx.s3_path() {
+            S3Path::Root => {
+                let visible = self.visible_buckets(&cred.access_key);
+                cx.extensions_mut().insert(visible);
+                return Ok(());
+            }
+            S3Path::Bucket { bucket } | S3Path::Object { bucket, .. } => bucket,
+        };
+        let permission = required_permission(cx.method().as_str(), cx.s3_op().name());
+        if self.is_allowed(&cred.access_key, bucket, permission) {
+            Ok(())
+        } else {
+            Err(s3_error!(AccessDenied))
+        }
+    }
+
+    async fn copy_object(&self, req: &mut S3Request<CopyObjectInput>) -> S3Result<()> {
+        self.check_copy_source(req, &req.input.copy_source)
+    }
+
+    async fn upload_part_copy(&self, req: &mut S3Request<UploadPartCopyInput>) -> S3Result<()> {
+        self.check_copy_source(req, &req.input.copy_source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users() -> Users {
+        let config: Config = toml::from_str(
+            r#"
+            [[users]]
+            access_key = "AKALICE"
+            secret_key = "SKALICE"
+            buckets = { "photos" = ["read", "write", "list"], "logs-*" = ["read"], "archive" = [] }
+
+            [[users]]
+            access_key = "AKBOB"
+            secret_key = "SKBOB"
+            buckets = { "*" = ["list"] }
+            "#,
+        )
+        .unwrap();
+        Users::new(&config.users)
+    }
+
+    #[test]
+    fn bucket_patterns() {
+        assert!(matches_bucket("photos", "photos"));
+        assert!(matches_bucket("photos", "photos-2").not());
+        assert!(matches_bucket("logs-*", "logs-2025"));
+        assert!(matches_bucket("logs-*", "logs-"));
+        assert!(matches_bucket("logs-*", "logs").not());
+        assert!(matches_bucket("*", "anything"));
+    }
+
+    #[test]
+    fn permissions() {
+        assert_eq!(required_permission("GET", "GetObject"), Permission::Read);
+        assert_eq!(required_permission("HEAD", "HeadBucket"), Permission::Read);
+        assert_eq!(required_permission("GET", "ListObjectsV2"), Permission::List);
+        assert_eq!(required_permission("POST", "SelectObjectContent"), Permission::Read);
+        assert_eq!(required_permission("PUT", "PutObject"), Permission::Write);
+        assert_eq!(required_permission("DELETE", "DeleteBucket"), Permission::Write);
+
+        let users = users();
+        assert!(users.is_allowed("AKALICE", "photos", Permission::Write));
+        assert!(users.is_allowed("AKALICE", "logs-2025", Permission::Read));
+        assert!(users.is_allowed("AKALICE", "logs-2025", Permission::Write).not());
+        assert!(users.is_allowed("AKALICE", "archive", Permission::Read).not());
+        assert!(users.is_allowed("AKALICE", "other", Permission::Read).not());
+        assert!(users.is_allowed("AKBOB", "photos", Permission::List));
+        assert!(users.is_allowed("AKBOB", "photos", Permission::Read).not());
+        assert!(users.is_allowed("AKUNKNOWN", "photos", Permission::Read).not());
+    }
+
+    #[test]
+    fn visible_buckets() {
+        let users = users();
+
+        let alice = users.visible_buckets("AKALICE");
+        assert!(alice.contains("photos"));
+        assert!(alice.contains("logs-2025"));
+        assert!(alice.contains("archive").not());
+        assert!(alice.contains("other").not());
+
+        let bob = users.visible_buckets("AKBOB");
+        assert!(bob.contains("photos"));
+        assert!(bob.contains("other"));
+
+        assert!(users.visible_buckets("AKUNKNOWN").contains("photos").not());
+    }
+
+    #[test]
+    fn reload() {
+        let users = users();
+        let cloned = users.clone();
+        users.set(&[]);
+        assert!(cloned.is_allowed("AKALICE", "photos", Permission::Read).not());
+    }
+}
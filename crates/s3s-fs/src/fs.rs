@@ -1,28 +1,43 @@
 use crate::error::*;
+use crate::index::KeyIndex;
 use crate::utils::hex;
 
+use s3s::S3Result;
 use s3s::auth::Credentials;
+use s3s::conditional::Preconditions;
 use s3s::crypto::Checksum;
 use s3s::crypto::Md5;
 use s3s::dto;
 use s3s::dto::PartNumber;
 
 use std::env;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::ops::Not;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use tokio::fs;
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, BufWriter};
+use tokio::sync::{Mutex, MutexGuard};
 
 use path_absolutize::Absolutize;
 use uuid::Uuid;
 
+/// The number of locks which serialize the commits of conditional writes
+const OBJECT_LOCKS: usize = 64;
+
+/// A file system backend.
+///
+/// Uploaded objects and parts are verified by the service,
+/// so [`S3ServiceBuilder::set_verify_checksums`](s3s::service::S3ServiceBuilder::set_verify_checksums) must be enabled.
 #[derive(Debug)]
 pub struct FileSystem {
     pub(crate) root: PathBuf,
     tmp_file_counter: AtomicU64,
+    pub(crate) key_index: Option<Arc<KeyIndex>>,
+    object_locks: Box<[Mutex<()>]>,
 }
 
 pub(crate) type InternalInfo = serde_json::Map<String, serde_json::Value>;
@@ -50,7 +65,12 @@ impl FileSystem {
         let root = env::current_dir()?.join(root).canonicalize()?;
         clean_old_tmp_files(&root)?;
         let tmp_file_counter = AtomicU64::new(0);
-        Ok(Self { root, tmp_file_counter })
+        Ok(Self {
+            root,
+            tmp_file_counter,
+            key_index: None,
+            object_locks: (0..OBJECT_LOCKS).map(|_| Mutex::new(())).collect(),
+        })
     }
 
     pub(crate) fn resolve_abs_path(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
@@ -82,6 +102,30 @@ impl FileSystem {
         self.resolve_abs_path(file_path)
     }
 
+    fn get_location_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        self.resolve_abs_path(format!(".bucket-{}.location.json", encode(bucket)))
+    }
+
+    /// Loads the location constraint of a bucket, which is empty for `us-east-1`.
+    ///
+    /// Returns `None` if the bucket was created without recording it.
+    pub(crate) async fn load_location_constraint(&self, bucket: &str) -> Result<Option<String>> {
+        let path = self.get_location_path(bucket)?;
+        if path.exists().not() {
+            return Ok(None);
+        }
+        let content = fs::read(&path).await?;
+        Ok(Some(serde_json::from_slice(&content)?))
+    }
+
+    pub(crate) async fn save_location_constraint(&self, bucket: &str, constraint: &str) -> Result<()> {
+        let path = self.get_location_path(bucket)?;
+        let content = serde_json::to_vec(constraint)?;
+        fs::write(&path, &content).await?;
+        Ok(())
+    }
+
     pub(crate) fn get_internal_info_path(&self, bucket: &str, key: &str) -> Result<PathBuf> {
         let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
         let file_path = format!(".bucket-{}.object-{}.internal.json", encode(bucket), encode(key));
@@ -140,7 +184,12 @@ impl FileSystem {
     /// get md5 sum
     pub(crate) async fn get_md5_sum(&self, bucket: &str, key: &str) -> Result<String> {
         let object_path = self.get_object_path(bucket, key)?;
-        let mut file = File::open(&object_path).await?;
+        self.get_file_md5_sum(&object_path).await
+    }
+
+    /// get md5 sum of a file
+    pub(crate) async fn get_file_md5_sum(&self, path: &Path) -> Result<String> {
+        let mut file = File::open(path).await?;
         let mut buf = vec![0; 65536];
         let mut md5_hash = Md5::new();
         loop {
@@ -153,6 +202,33 @@ impl FileSystem {
         Ok(hex(md5_hash.finalize()))
     }
 
+    /// Locks an object key, so that checking the preconditions of a write and committing it are atomic.
+    ///
+    /// Every write or delete of the current object and its versions must hold the lock.
+    ///
+    /// The keys are hashed into a fixed number of locks.
+    pub(crate) async fn lock_object(&self, bucket: &str, key: &str) -> MutexGuard<'_, ()> {
+        let mut hasher = DefaultHasher::new();
+        (bucket, key).hash(&mut hasher);
+        let idx = hasher.finish() % (self.object_locks.len() as u64);
+        let idx = usize::try_from(idx).unwrap_or_default();
+        self.object_locks[idx].lock().await
+    }
+
+    /// Checks the preconditions of a conditional write against the current version of an object.
+    pub(crate) async fn check_write_preconditions(&self, bucket: &str, key: &str, preconditions: Preconditions<'_>) -> S3Result {
+        if preconditions.if_match.is_none() && preconditions.if_none_match.is_none() {
+            return Ok(());
+        }
+        let object_path = self.get_object_path(bucket, key)?;
+        let e_tag = if object_path.is_file() {
+            Some(format!("\"{}\"", self.get_file_md5_sum(&object_path).await?))
+        } else {
+            None
+        };
+        preconditions.check_write(e_tag.as_deref())
+    }
+
     fn get_upload_info_path(&self, upload_id: &Uuid) -> Result<PathBuf> {
         self.resolve_abs_path(format!(".upload-{upload_id}.json"))
     }
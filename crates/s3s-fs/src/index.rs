@@ -0,0 +1,361 @@
+//! Persistent sorted key index
+//!
+//! The keys of each bucket are stored in a table of an embedded database,
+//! so that a listing can seek to a prefix or a marker and stream keys in order,
+//! without walking and sorting the whole directory tree.
+//!
+//! The index of a bucket is built from disk on first use,
+//! and is maintained when objects are written or deleted.
+//! Building and updating the index of a bucket are serialized by a per-bucket lock,
+//! so that an update made during a build is not overwritten.
+//! Files changed outside the server are not noticed until the index is rebuilt.
+//!
+//! Write transactions wait for the disk, so they run on blocking threads.
+
+use crate::error::*;
+use crate::fs::FileSystem;
+use crate::list::KeyWalker;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Bound;
+use std::ops::Not;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use redb::{Database, ReadOnlyTable, TableDefinition};
+use tokio::fs;
+use tracing::{debug, error};
+
+/// The buckets whose index has been built
+const BUILT_BUCKETS: TableDefinition<'static, &str, ()> = TableDefinition::new("built_buckets");
+
+/// The keys of a bucket are stored in the table named after the bucket.
+/// Bucket names never contain `_`, so they do not conflict with [`BUILT_BUCKETS`].
+fn objects_table(bucket: &str) -> TableDefinition<'_, &'static str, ()> {
+    TableDefinition::new(bucket)
+}
+
+pub(crate) struct KeyIndex {
+    db: Database,
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl fmt::Debug for KeyIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyIndex").finish_non_exhaustive()
+    }
+}
+
+impl KeyIndex {
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let db = Database::create(path)?;
+        let txn = db.begin_write()?;
+        txn.open_table(BUILT_BUCKETS)?;
+        txn.commit()?;
+        Ok(Self {
+            db,
+            locks: Mutex::default(),
+        })
+    }
+
+    /// Returns the lock which serializes building the index of a bucket against updates.
+    fn bucket_lock(&self, bucket: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(locks.entry(bucket.to_owned()).or_default())
+    }
+
+    fn is_built(&self, bucket: &str) -> Result<bool> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(BUILT_BUCKETS)?;
+        Ok(table.get(bucket)?.is_some())
+    }
+
+    /// Replaces the index of a bucket with the keys, which are in lexicographical order.
+    fn replace(&self, bucket: &str, keys: &[String]) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        txn.delete_table(objects_table(bucket))?;
+        {
+            let mut table = txn.open_table(objects_table(bucket))?;
+            for key in keys {
+                table.insert(key.as_str(), ())?;
+            }
+            txn.open_table(BUILT_BUCKETS)?.insert(bucket, ())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn update(&self, bucket: &str, key: &str, exists: bool) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(objects_table(bucket))?;
+            if exists {
+                table.insert(key, ())?;
+            } else {
+                table.remove(key)?;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Marks the index of a bucket as stale, so that it is rebuilt on next use.
+    fn invalidate(&self, bucket: &str) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        txn.open_table(BUILT_BUCKETS)?.remove(bucket)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn delete_bucket(&self, bucket: &str) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        txn.delete_table(objects_table(bucket))?;
+        txn.open_table(BUILT_BUCKETS)?.remove(bucket)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Opens a cursor over the keys which start with `prefix` and are after `start_after`.
+    pub(crate) fn cursor<'a>(&self, bucket: &str, prefix: &'a str, start_after: Option<&str>) -> Result<KeyCursor<'a>> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(objects_table(bucket))?;
+        let lower = match start_after {
+            Some(s) if s >= prefix => Bound::Excluded(s.to_owned()),
+            _ => Bound::Included(prefix.to_owned()),
+        };
+        Ok(KeyCursor {
+            range: Some(table.range::<&str>((lower.as_ref().map(String::as_str), Bound::Unbounded))?),
+            table,
+            prefix,
+        })
+    }
+}
+
+/// Streams the keys of a bucket in lexicographical order.
+pub(crate) struct KeyCursor<'a> {
+    table: ReadOnlyTable<&'static str, ()>,
+    range: Option<redb::Range<'static, &'static str, ()>>,
+    prefix: &'a str,
+}
+
+impl KeyCursor<'_> {
+    pub(crate) fn next_key(&mut self) -> Result<Option<String>> {
+        let Some(item) = self.range.as_mut().and_then(Iterator::next) else { return Ok(None) };
+        let (key, _) = item?;
+        let key = key.value();
+        if key.starts_with(self.prefix).not() {
+            self.range = None;
+            return Ok(None);
+        }
+        Ok(Some(key.to_owned()))
+    }
+
+    /// Seeks to the first key which does not start with `prefix`.
+    pub(crate) fn skip(&mut self, prefix: &str) -> Result<()> {
+        self.range = match prefix_successor(prefix) {
+            Some(s) => Some(self.table.range::<&str>(s.as_str()..)?),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+/// Runs a write transaction on a blocking thread.
+async fn write_blocking<T, F>(index: &Arc<KeyIndex>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&KeyIndex) -> Result<T> + Send + 'static,
+{
+    let index = Arc::clone(index);
+    tokio::task::spawn_blocking(move || f(&index)).await?
+}
+
+/// Returns the smallest string which is greater than all strings starting with `prefix`.
+fn prefix_successor(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(c) = chars.pop() {
+        let next = (u32::from(c) + 1..=u32::from(char::MAX)).find_map(char::from_u32);
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+impl FileSystem {
+    /// Enables the persistent key index, which is stored in the root directory.
+    ///
+    /// Listings seek in the index instead of walking the directory tree.
+    pub fn enable_key_index(&mut self) -> Result<()> {
+        let path = self.resolve_abs_path(".key-index.redb")?;
+        self.key_index = Some(Arc::new(KeyIndex::open(&path)?));
+        Ok(())
+    }
+
+    /// Rebuilds the key index of a bucket from disk.
+    pub async fn rebuild_key_index(&self, bucket: &str) -> Result<()> {
+        let Some(ref index) = self.key_index else { return Ok(()) };
+        let lock = index.bucket_lock(bucket);
+        let _guard = lock.lock().await;
+        self.build_key_index(index, bucket).await
+    }
+
+    /// Builds the key index of a bucket, with the bucket lock held.
+    async fn build_key_index(&self, index: &Arc<KeyIndex>, bucket: &str) -> Result<()> {
+        let mut walker = KeyWalker::new(self.get_bucket_path(bucket)?, "", None).await?;
+        let mut keys = Vec::new();
+        while let Some(key) = walker.next_key().await? {
+            keys.push(key);
+        }
+        let count = keys.len();
+        let owned_bucket = bucket.to_owned();
+        write_blocking(index, move |index| index.replace(&owned_bucket, &keys)).await?;
+
+        debug!(bucket, count, "rebuild key index");
+        Ok(())
+    }
+
+    /// Rebuilds the key indexes of all buckets from disk.
+    pub async fn rebuild_key_indexes(&self) -> Result<()> {
+        let mut iter = fs::read_dir(&self.root).await?;
+        while let Some(entry) = iter.next_entry().await? {
+            if entry.file_type().await?.is_dir().not() {
+                continue;
+            }
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str() else { continue };
+            if s3s::path::check_bucket_name(name).not() {
+                continue;
+            }
+            self.rebuild_key_index(name).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the key index of a bucket, which is built on first use.
+    pub(crate) async fn get_key_index(&self, bucket: &str) -> Result<Option<&KeyIndex>> {
+        let Some(ref index) = self.key_index else { return Ok(None) };
+        if index.is_built(bucket)?.not() {
+            let lock = index.bucket_lock(bucket);
+            let _guard = lock.lock().await;
+            // Another listing may have built it while waiting for the lock.
+            if index.is_built(bucket)?.not() {
+                self.build_key_index(index, bucket).await?;
+            }
+        }
+        Ok(Some(index))
+    }
+
+    /// Updates the key index after an object is written or deleted.
+    ///
+    /// The object has been changed, so a failure does not fail the request.
+    /// Instead, the index of the bucket is rebuilt on next use.
+    pub(crate) async fn update_key_index(&self, bucket: &str, key: &str) {
+        let Some(ref index) = self.key_index else { return };
+        let lock = index.bucket_lock(bucket);
+        let _guard = lock.lock().await;
+
+        let exists = match self.get_object_path(bucket, key) {
+            Ok(path) => fs::metadata(path).await.is_ok_and(|m| m.is_file()),
+            Err(_) => false,
+        };
+
+        let (owned_bucket, owned_key) = (bucket.to_owned(), key.to_owned());
+        let result = write_blocking(index, move |index| index.update(&owned_bucket, &owned_key, exists)).await;
+        if let Err(err) = result {
+            error!(bucket, key, ?err, "failed to update key index");
+            let owned_bucket = bucket.to_owned();
+            if let Err(err) = write_blocking(index, move |index| index.invalidate(&owned_bucket)).await {
+                error!(bucket, ?err, "failed to invalidate key index");
+            }
+        }
+    }
+
+    /// Deletes the key index after a bucket is deleted.
+    pub(crate) async fn delete_key_index(&self, bucket: &str) {
+        let Some(ref index) = self.key_index else { return };
+        let lock = index.bucket_lock(bucket);
+        let _guard = lock.lock().await;
+
+        let owned_bucket = bucket.to_owned();
+        if let Err(err) = write_blocking(index, move |index| index.delete_bucket(&owned_bucket)).await {
+            error!(bucket, ?err, "failed to delete key index");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_index(keys: &[&str]) -> (tempfile::TempDir, KeyIndex) {
+        let dir = tempfile::tempdir().unwrap();
+        let index = KeyIndex::open(&dir.path().join("index.redb")).unwrap();
+        let keys: Vec<String> = keys.iter().map(|&k| k.to_owned()).collect();
+        index.replace("bucket", &keys).unwrap();
+        (dir, index)
+    }
+
+    fn collect(mut cursor: KeyCursor<'_>) -> Vec<String> {
+        let mut keys = Vec::new();
+        while let Some(key) = cursor.next_key().unwrap() {
+            keys.push(key);
+        }
+        keys
+    }
+
+    #[test]
+    fn successor() {
+        assert_eq!(prefix_successor("a").as_deref(), Some("b"));
+        assert_eq!(prefix_successor("a/").as_deref(), Some("a0"));
+        assert_eq!(prefix_successor("a\u{10FFFF}").as_deref(), Some("b"));
+        assert_eq!(prefix_successor("\u{D7FF}").as_deref(), Some("\u{E000}"));
+        assert_eq!(prefix_successor("\u{10FFFF}\u{10FFFF}"), None);
+        assert_eq!(prefix_successor(""), None);
+    }
+
+    #[test]
+    fn cursor_bounds() {
+        let (_dir, index) = open_index(&["a", "b/1", "b/2", "b\u{10FFFF}", "b\u{10FFFF}/1", "c"]);
+
+        assert_eq!(collect(index.cursor("bucket", "b/", None).unwrap()), ["b/1", "b/2"]);
+        assert_eq!(collect(index.cursor("bucket", "b/", Some("a")).unwrap()), ["b/1", "b/2"]);
+        assert_eq!(collect(index.cursor("bucket", "b/", Some("b/1")).unwrap()), ["b/2"]);
+        assert!(collect(index.cursor("bucket", "b/", Some("b/2")).unwrap()).is_empty());
+        assert!(collect(index.cursor("bucket", "b/", Some("c")).unwrap()).is_empty());
+
+        let prefix = "b\u{10FFFF}";
+        assert_eq!(collect(index.cursor("bucket", prefix, None).unwrap()), [prefix, "b\u{10FFFF}/1"]);
+        assert_eq!(collect(index.cursor("bucket", prefix, Some(prefix)).unwrap()), ["b\u{10FFFF}/1"]);
+
+        let (_dir, index) = open_index(&[]);
+        assert!(collect(index.cursor("bucket", "", None).unwrap()).is_empty());
+        assert!(collect(index.cursor("bucket", "a", Some("b")).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn cursor_skip() {
+        let (_dir, index) = open_index(&["a/1", "a/2", "a0", "b\u{10FFFF}/1", "b\u{10FFFF}/2"]);
+
+        let mut cursor = index.cursor("bucket", "", None).unwrap();
+        assert_eq!(cursor.next_key().unwrap().as_deref(), Some("a/1"));
+        cursor.skip("a/").unwrap();
+        assert_eq!(cursor.next_key().unwrap().as_deref(), Some("a0"));
+        assert_eq!(cursor.next_key().unwrap().as_deref(), Some("b\u{10FFFF}/1"));
+        cursor.skip("b\u{10FFFF}").unwrap();
+        assert_eq!(cursor.next_key().unwrap(), None);
+
+        // Skipping a prefix without successor ends the cursor.
+        let mut cursor = index.cursor("bucket", "", None).unwrap();
+        cursor.skip("\u{10FFFF}").unwrap();
+        assert_eq!(cursor.next_key().unwrap(), None);
+
+        // The cursor does not leave its prefix after skipping.
+        let mut cursor = index.cursor("bucket", "a", None).unwrap();
+        cursor.skip("a/").unwrap();
+        assert_eq!(cursor.next_key().unwrap().as_deref(), Some("a0"));
+        assert_eq!(cursor.next_key().unwrap(), None);
+    }
+}
@@ -10,9 +10,13 @@
 mod error;
 
 mod checksum;
+pub mod config;
 mod fs;
+mod index;
+mod list;
 mod s3;
 mod utils;
+mod version;
 
 pub use self::error::*;
 pub use self::fs::FileSystem;
@@ -0,0 +1,285 @@
+use crate::error::*;
+use crate::fs::FileSystem;
+use crate::index::KeyCursor;
+
+use s3s::S3Result;
+use s3s::dto;
+use s3s::s3_error;
+
+use std::io;
+use std::ops::Not;
+use std::path::PathBuf;
+
+use tokio::fs;
+
+/// The default and maximum number of keys returned in a response
+pub(crate) const MAX_KEYS: usize = 1000;
+
+/// The parameters of a listing
+#[derive(Debug)]
+pub(crate) struct ListParams<'a> {
+    pub prefix: &'a str,
+    pub delimiter: Option<&'a str>,
+    /// Lists the keys after the marker.
+    /// If the marker is a common prefix, the keys under it are skipped.
+    pub marker: Option<&'a str>,
+    pub max_keys: usize,
+}
+
+/// A page of a listing
+#[derive(Debug, Default)]
+pub(crate) struct ListPage {
+    pub objects: Vec<dto::Object>,
+    pub common_prefixes: Vec<String>,
+    pub is_truncated: bool,
+    /// The last key or common prefix of the page, if the page is truncated
+    pub next_marker: Option<String>,
+}
+
+/// An entry of a directory
+struct Entry {
+    /// The key of a file, or the key prefix of a directory which ends with `/`
+    key: String,
+    path: PathBuf,
+    is_dir: bool,
+}
+
+/// Walks the object keys of a bucket in lexicographical order.
+///
+/// The entries of each directory are sorted by their keys,
+/// so that the keys are visited in order without collecting the whole tree.
+/// Directories which can not contain a wanted key are not read.
+pub(crate) struct KeyWalker<'a> {
+    stack: Vec<std::vec::IntoIter<Entry>>,
+    prefix: &'a str,
+    start_after: Option<&'a str>,
+    skip_prefix: Option<String>,
+}
+
+impl<'a> KeyWalker<'a> {
+    pub(crate) async fn new(root: PathBuf, prefix: &'a str, start_after: Option<&'a str>) -> Result<Self> {
+        let mut this = Self {
+            stack: Vec::new(),
+            prefix,
+            start_after,
+            skip_prefix: None,
+        };
+        this.push_dir(root, String::new()).await?;
+        Ok(this)
+    }
+
+    async fn push_dir(&mut self, dir: PathBuf, key_prefix: String) -> Result<()> {
+        let mut entries = Vec::new();
+        let mut iter = fs::read_dir(&dir).await?;
+        while let Some(entry) = iter.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str() else { continue };
+            let is_dir = entry.file_type().await?.is_dir();
+            let key = if is_dir {
+                format!("{key_prefix}{name}/")
+            } else {
+                format!("{key_prefix}{name}")
+            };
+            entries.push(Entry {
+                key,
+                path: entry.path(),
+                is_dir,
+            });
+        }
+        entries.sort_unstable_by(|lhs, rhs| lhs.key.cmp(&rhs.key));
+        self.stack.push(entries.into_iter());
+        Ok(())
+    }
+
+    /// Skips the keys which start with `prefix`.
+    fn skip(&mut self, prefix: String) {
+        self.skip_prefix = Some(prefix);
+    }
+
+    /// Checks whether a directory may contain a wanted key.
+    fn may_contain(&self, dir_key: &str) -> bool {
+        if dir_key.starts_with(self.prefix).not() && self.prefix.starts_with(dir_key).not() {
+            return false;
+        }
+        if let Some(start_after) = self.start_after {
+            // all keys in the directory are before the start
+            if dir_key < start_after && start_after.starts_with(dir_key).not() {
+                return false;
+            }
+        }
+        if let Some(ref skip_prefix) = self.skip_prefix
+            && dir_key.starts_with(skip_prefix.as_str())
+        {
+            return false;
+        }
+        true
+    }
+
+    fn is_wanted(&self, key: &str) -> bool {
+        key.starts_with(self.prefix)
+            && self.start_after.is_none_or(|s| key > s)
+            && self.skip_prefix.as_deref().is_none_or(|p| key.starts_with(p).not())
+    }
+
+    pub(crate) async fn next_key(&mut self) -> Result<Option<String>> {
+        loop {
+            let Some(iter) = self.stack.last_mut() else { return Ok(None) };
+            let Some(entry) = iter.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            // the remaining keys are all after the prefix
+            if entry.key.as_str() > self.prefix
+                && entry.key.starts_with(self.prefix).not()
+                && self.prefix.starts_with(&entry.key).not()
+            {
+                self.stack.clear();
+                return Ok(None);
+            }
+
+            if entry.is_dir {
+                if self.may_contain(&entry.key) {
+                    self.push_dir(entry.path, entry.key).await?;
+                }
+                continue;
+            }
+
+            if self.is_wanted(&entry.key) {
+                return Ok(Some(entry.key));
+            }
+        }
+    }
+}
+
+/// The source of keys of a listing
+enum KeySource<'a> {
+    Walker(KeyWalker<'a>),
+    Index(Box<KeyCursor<'a>>),
+}
+
+impl KeySource<'_> {
+    async fn next_key(&mut self) -> Result<Option<String>> {
+        match self {
+            Self::Walker(walker) => walker.next_key().await,
+            Self::Index(cursor) => cursor.next_key(),
+        }
+    }
+
+    fn skip(&mut self, prefix: &str) -> Result<()> {
+        match self {
+            Self::Walker(walker) => {
+                walker.skip(prefix.to_owned());
+                Ok(())
+            }
+            Self::Index(cursor) => cursor.skip(prefix),
+        }
+    }
+}
+
+impl FileSystem {
+    /// Lists a page of the objects and the common prefixes in a bucket.
+    pub(crate) async fn list_page(&self, bucket: &str, params: &ListParams<'_>) -> S3Result<ListPage> {
+        let path = self.get_bucket_path(bucket)?;
+        if path.exists().not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let mut page = ListPage::default();
+        if params.max_keys == 0 {
+            return Ok(page);
+        }
+
+        let mut source = match self.get_key_index(bucket).await? {
+            Some(index) => KeySource::Index(Box::new(index.cursor(bucket, params.prefix, params.marker)?)),
+            None => KeySource::Walker(KeyWalker::new(path, params.prefix, params.marker).await?),
+        };
+        let mut count = 0;
+
+        while let Some(key) = source.next_key().await? {
+            let common_prefix = params.delimiter.filter(|d| d.is_empty().not()).and_then(|delimiter| {
+                let rest = &key[params.prefix.len()..];
+                let pos = rest.find(delimiter)?;
+                Some(&key[..params.prefix.len() + pos + delimiter.len()])
+            });
+
+            // The marker is a common prefix which has been returned.
+            if let Some(common_prefix) = common_prefix.filter(|&p| Some(p) == params.marker) {
+                source.skip(common_prefix)?;
+                continue;
+            }
+
+            // The index may be stale if files are changed outside the server.
+            let metadata = match fs::metadata(self.get_object_path(bucket, &key)?).await {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(s3_error!(e, InternalError)),
+            };
+
+            if count == params.max_keys {
+                page.is_truncated = true;
+                break;
+            }
+            count += 1;
+
+            if let Some(common_prefix) = common_prefix {
+                let common_prefix = common_prefix.to_owned();
+                source.skip(&common_prefix)?;
+                page.next_marker = Some(common_prefix.clone());
+                page.common_prefixes.push(common_prefix);
+                continue;
+            }
+
+            let last_modified = dto::Timestamp::from(try_!(metadata.modified()));
+            page.objects.push(dto::Object {
+                key: Some(key.clone()),
+                last_modified: Some(last_modified),
+                size: Some(try_!(i64::try_from(metadata.len()))),
+                ..Default::default()
+            });
+            page.next_marker = Some(key);
+        }
+
+        if page.is_truncated.not() {
+            page.next_marker = None;
+        }
+        Ok(page)
+    }
+}
+
+/// Encodes a continuation token from the last key or common prefix of a page.
+pub(crate) fn encode_continuation_token(marker: &str) -> String {
+    base64_simd::URL_SAFE_NO_PAD.encode_to_string(marker)
+}
+
+/// Decodes a continuation token into the last key or common prefix of the previous page.
+pub(crate) fn decode_continuation_token(token: &str) -> S3Result<String> {
+    let invalid = || s3_error!(InvalidArgument, "The continuation token provided is incorrect");
+    let bytes = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(token).map_err(|_| invalid())?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Parses the `max-keys` parameter.
+pub(crate) fn parse_max_keys(max_keys: Option<dto::MaxKeys>) -> S3Result<usize> {
+    match max_keys {
+        None => Ok(MAX_KEYS),
+        Some(n) => {
+            let n = usize::try_from(n).map_err(|_| s3_error!(InvalidArgument, "max-keys must not be negative"))?;
+            Ok(n.min(MAX_KEYS))
+        }
+    }
+}
+
+/// Returns whether the keys in the response should be url-encoded.
+pub(crate) fn parse_encoding_type(encoding_type: Option<&dto::EncodingType>) -> S3Result<bool> {
+    match encoding_type.map(dto::EncodingType::as_str) {
+        None => Ok(false),
+        Some(dto::EncodingType::URL) => Ok(true),
+        Some(_) => Err(s3_error!(InvalidArgument, "Invalid Encoding Method specified in Request")),
+    }
+}
+
+/// Encodes a key or a prefix for `encoding-type=url`.
+pub(crate) fn url_encode(s: &str) -> String {
+    urlencoding::encode(s).into_owned()
+}
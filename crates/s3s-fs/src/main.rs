@@ -1,9 +1,15 @@
 use s3s_fs::FileSystem;
 use s3s_fs::Result;
+use s3s_fs::config::{Config, Users};
 
+use s3s::access_log::{RollingFileSink, Rotation};
 use s3s::auth::SimpleAuth;
+use s3s::connection::IpCidr;
 use s3s::host::MultiDomain;
+use s3s::region::Region;
 use s3s::service::S3ServiceBuilder;
+use s3s::sts::{AssumeRoleRoute, SessionStore, StsAuth};
+use s3s::tls::{TlsAcceptor, TlsListener};
 
 use std::io::IsTerminal;
 use std::ops::Not;
@@ -20,13 +26,13 @@ use hyper_util::server::conn::auto::Builder as ConnBuilder;
 #[derive(Debug, Parser)]
 #[command(version)]
 struct Opt {
-    /// Host name to listen on.
-    #[arg(long, default_value = "localhost")]
-    host: String,
+    /// Host name to listen on. [default: localhost]
+    #[arg(long)]
+    host: Option<String>,
 
-    /// Port number to listen on.
-    #[arg(long, default_value = "8014")] // The original design was finished on 2020-08-14.
-    port: u16,
+    /// Port number to listen on. [default: 8014]
+    #[arg(long)]
+    port: Option<u16>,
 
     /// Access key used for authentication.
     #[arg(long)]
@@ -36,10 +42,53 @@ struct Opt {
     #[arg(long)]
     secret_key: Option<String>,
 
+    /// Configuration file of users, bucket permissions and server settings.
+    /// Only users are reloaded on SIGHUP. Other settings take effect after a restart.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Enable the STS `AssumeRole` endpoint which issues temporary credentials.
+    #[arg(long)]
+    sts: bool,
+
+    /// ARNs of the roles which can be assumed via the STS endpoint.
+    #[arg(long)]
+    sts_role: Vec<String>,
+
     /// Domain names used for virtual-hosted-style requests.
     #[arg(long)]
     domain: Vec<String>,
 
+    /// Regions served by the server. The first one is the primary region.
+    #[arg(long)]
+    region: Vec<Region>,
+
+    /// Networks of trusted proxies, such as `10.0.0.0/8`.
+    /// The client IP of a request from a trusted proxy is taken from the forwarded headers.
+    #[arg(long)]
+    trusted_proxy: Vec<IpCidr>,
+
+    /// Directory of server access logs, rotated hourly.
+    #[arg(long)]
+    access_log: Option<PathBuf>,
+
+    /// Certificate chain file (PEM) for serving HTTPS. Reloaded on SIGHUP.
+    #[arg(long)]
+    tls_cert: Option<PathBuf>,
+
+    /// Private key file (PEM) for serving HTTPS. Reloaded on SIGHUP.
+    #[arg(long)]
+    tls_key: Option<PathBuf>,
+
+    /// Keep a persistent sorted index of object keys for listing.
+    #[arg(long)]
+    key_index: bool,
+
+    /// Rebuild the key indexes of all buckets from disk before serving.
+    /// This implies `--key-index`.
+    #[arg(long)]
+    rebuild_key_index: bool,
+
     /// Root directory of stored data.
     root: PathBuf,
 }
@@ -57,6 +106,14 @@ fn setup_tracing() {
         .init();
 }
 
+fn assume_role_route(store: SessionStore, roles: &[String]) -> AssumeRoleRoute {
+    let mut route = AssumeRoleRoute::new(store);
+    for role in roles {
+        route.add_role(role.as_str());
+    }
+    route
+}
+
 fn check_cli_args(opt: &Opt) {
     use clap::error::ErrorKind;
 
@@ -68,6 +125,31 @@ fn check_cli_args(opt: &Opt) {
         cmd.error(ErrorKind::MissingRequiredArgument, msg).exit();
     }
 
+    if let (Some(_), None) | (None, Some(_)) = (&opt.tls_cert, &opt.tls_key) {
+        let msg = "TLS certificate and key must be specified together";
+        cmd.error(ErrorKind::MissingRequiredArgument, msg).exit();
+    }
+
+    if opt.config.is_some() && opt.access_key.is_some() {
+        let msg = "access key and configuration file can not be specified together";
+        cmd.error(ErrorKind::ArgumentConflict, msg).exit();
+    }
+
+    if opt.sts && opt.access_key.is_none() && opt.config.is_none() {
+        let msg = "STS requires authentication to be enabled";
+        cmd.error(ErrorKind::MissingRequiredArgument, msg).exit();
+    }
+
+    if opt.sts && opt.sts_role.is_empty() {
+        let msg = "STS requires at least one role";
+        cmd.error(ErrorKind::MissingRequiredArgument, msg).exit();
+    }
+
+    if opt.sts.not() && opt.sts_role.is_empty().not() {
+        let msg = "STS roles require STS to be enabled";
+        cmd.error(ErrorKind::MissingRequiredArgument, msg).exit();
+    }
+
     for s in &opt.domain {
         if s.contains('/') {
             let msg = format!("expected domain name, found URL-like string: {s:?}");
@@ -76,6 +158,25 @@ fn check_cli_args(opt: &Opt) {
     }
 }
 
+/// Calls `f` every time the process receives SIGHUP.
+#[cfg(unix)]
+fn on_sighup(f: impl Fn() + Send + 'static) -> Result {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            f();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn on_sighup(_: impl Fn() + Send + 'static) -> Result {
+    Ok(())
+}
+
 fn main() -> Result {
     let opt = Opt::parse();
     check_cli_args(&opt);
@@ -87,8 +188,22 @@ fn main() -> Result {
 
 #[tokio::main]
 async fn run(opt: Opt) -> Result {
+    // Load configuration file
+    let config = match &opt.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+
     // Setup S3 provider
-    let fs = FileSystem::new(opt.root)?;
+    let mut fs = FileSystem::new(opt.root)?;
+    if opt.key_index || opt.rebuild_key_index {
+        fs.enable_key_index()?;
+        info!("key index is enabled");
+    }
+    if opt.rebuild_key_index {
+        fs.rebuild_key_indexes().await?;
+        info!("key indexes are rebuilt");
+    }
 
     // Setup S3 service
     let service = {
@@ -96,22 +211,90 @@ async fn run(opt: Opt) -> Result {
 
         // Enable authentication
         if let (Some(ak), Some(sk)) = (opt.access_key, opt.secret_key) {
-            b.set_auth(SimpleAuth::from_single(ak, sk));
+            let auth = SimpleAuth::from_single(ak, sk);
+            if opt.sts {
+                let store = SessionStore::new();
+                b.set_auth(StsAuth::new(auth, store.clone()));
+                b.set_route(assume_role_route(store, &opt.sts_role));
+                info!("STS AssumeRole endpoint is enabled");
+            } else {
+                b.set_auth(auth);
+            }
             info!("authentication is enabled");
         }
 
+        // Enable authentication and per-user bucket permissions
+        if let Some(path) = opt.config {
+            let mut users = Users::new(&config.users);
+            if opt.sts {
+                let store = SessionStore::new();
+                users.set_sessions(store.clone());
+                b.set_auth(StsAuth::new(users.clone(), store.clone()));
+                b.set_route(assume_role_route(store, &opt.sts_role));
+                info!("STS AssumeRole endpoint is enabled");
+            } else {
+                b.set_auth(users.clone());
+            }
+            b.set_access(users.clone());
+            on_sighup(move || match Config::load(&path) {
+                Ok(config) => {
+                    users.set(&config.users);
+                    info!("users are reloaded");
+                }
+                Err(err) => tracing::error!("error reloading configuration file: {err:?}"),
+            })?;
+            info!("authentication is enabled with {} users", config.users.len());
+        }
+
         // Enable parsing virtual-hosted-style requests
-        if opt.domain.is_empty().not() {
-            b.set_host(MultiDomain::new(&opt.domain)?);
+        let mut domains = opt.domain;
+        domains.extend(config.domains);
+        if domains.is_empty().not() {
+            b.set_host(MultiDomain::new(&domains)?);
             info!("virtual-hosted-style requests are enabled");
         }
 
+        // Reject requests scoped to other regions
+        for region in opt.region {
+            b.add_region(region);
+        }
+        if let Some(region) = config.region {
+            b.add_region(Region::new(region)?);
+        }
+
+        // Resolve client IPs behind proxies
+        for proxy in opt.trusted_proxy {
+            b.add_trusted_proxy(proxy);
+        }
+
+        // Verify the checksums of uploaded objects and parts
+        b.set_verify_checksums(true);
+
+        // Enable server access logging
+        if let Some(dir) = opt.access_log {
+            b.set_access_log(RollingFileSink::new(dir, "access", Rotation::Hourly)?);
+            info!("access logging is enabled");
+        }
+
         b.build()
     };
 
+    // Setup TLS
+    let tls_acceptor = match (opt.tls_cert, opt.tls_key) {
+        (Some(cert), Some(key)) => {
+            let acceptor = TlsAcceptor::from_pem_files(cert, key)?;
+            acceptor.reload_on_sighup()?;
+            info!("TLS is enabled");
+            Some(acceptor)
+        }
+        _ => None,
+    };
+
     // Run server
-    let listener = TcpListener::bind((opt.host.as_str(), opt.port)).await?;
-    let local_addr = listener.local_addr()?;
+    let host = opt.host.or(config.host).unwrap_or_else(|| "localhost".to_owned());
+    let port = opt.port.or(config.port).unwrap_or(8014); // The original design was finished on 2020-08-14.
+    let listener = TcpListener::bind((host.as_str(), port)).await?;
+    let mut listener = TlsListener::new(listener, tls_acceptor)?;
 
     let hyper_service = service.into_shared();
 
@@ -120,11 +303,12 @@ async fn run(opt: Opt) -> Result {
 
     let mut ctrl_c = std::pin::pin!(tokio::signal::ctrl_c());
 
-    info!("server is running at http://{local_addr}");
+    let scheme = if listener.is_tls() { "https" } else { "http" };
+    info!("server is running at {scheme}://{}", listener.local_addr());
 
     loop {
-        let (socket, _) = tokio::select! {
-            res =  listener.accept() => {
+        let (stream, connection) = tokio::select! {
+            res = listener.accept() => {
                 match res {
                     Ok(conn) => conn,
                     Err(err) => {
@@ -138,13 +322,16 @@ async fn run(opt: Opt) -> Result {
             }
         };
 
-        let conn = http_server.serve_connection(TokioIo::new(socket), hyper_service.clone());
+        let service = hyper_service.with_connection(connection);
+        let conn = http_server.serve_connection(TokioIo::new(stream), service);
         let conn = graceful.watch(conn.into_owned());
         tokio::spawn(async move {
             let _ = conn.await;
         });
     }
 
+    drop(listener);
+
     tokio::select! {
         () = graceful.shutdown() => {
              tracing::debug!("Gracefully shutdown!");
@@ -1,12 +1,18 @@
+use crate::config::VisibleBuckets;
 use crate::fs::FileSystem;
 use crate::fs::InternalInfo;
+use crate::list::*;
 use crate::utils::*;
+use crate::version::{Versioning, set_version_id};
 
 use s3s::S3;
 use s3s::S3Result;
+use s3s::checksum::BodyChecksums;
+use s3s::conditional::Preconditions;
 use s3s::crypto::Checksum;
 use s3s::crypto::Md5;
 use s3s::dto::*;
+use s3s::region::Region;
 use s3s::s3_error;
 use s3s::{S3Request, S3Response};
 
@@ -48,6 +54,84 @@ fn normalize_path(path: &Path, delimiter: &str) -> Option<String> {
     Some(normalized)
 }
 
+/// Returns the region of the request, or `us-east-1` if the service does not declare its regions.
+fn request_region<T>(req: &S3Request<T>) -> &str {
+    req.region.as_ref().map_or(Region::US_EAST_1, Region::as_str)
+}
+
+/// Returns the region of a location constraint.
+fn constraint_region(constraint: &str) -> &str {
+    match constraint {
+        "" => Region::US_EAST_1,
+        BucketLocationConstraint::EU => "eu-west-1",
+        _ => constraint,
+    }
+}
+
+impl FileSystem {
+    /// Returns the location constraint of a bucket.
+    ///
+    /// The buckets created without recording it are considered to be in the region of the request.
+    async fn location_constraint<T>(&self, req: &S3Request<T>, bucket: &str) -> S3Result<String> {
+        match self.load_location_constraint(bucket).await? {
+            Some(constraint) => Ok(constraint),
+            None => match request_region(req) {
+                Region::US_EAST_1 => Ok(String::new()),
+                region => Ok(region.to_owned()),
+            },
+        }
+    }
+
+    async fn bucket_region<T>(&self, req: &S3Request<T>, bucket: &str) -> S3Result<String> {
+        let constraint = self.location_constraint(req, bucket).await?;
+        Ok(constraint_region(&constraint).to_owned())
+    }
+}
+
+/// Returns the prefix of a listing.
+///
+/// Keys never start with `/`, so a leading `/` is ignored.
+fn list_prefix(prefix: Option<&str>) -> &str {
+    let prefix = prefix.unwrap_or_default();
+    prefix.strip_prefix('/').unwrap_or(prefix)
+}
+
+/// Returns the owner of listed objects.
+///
+/// Owners are not recorded, so the requester is reported as the owner.
+fn list_owner<T>(req: &S3Request<T>) -> Option<Owner> {
+    let cred = req.credentials.as_ref()?;
+    Some(Owner {
+        display_name: Some(cred.access_key.clone()),
+        id: Some(cred.access_key.clone()),
+    })
+}
+
+fn list_contents(objects: Vec<Object>, owner: Option<&Owner>, encode: impl Fn(String) -> String) -> Vec<Object> {
+    objects
+        .into_iter()
+        .map(|object| Object {
+            key: object.key.map(&encode),
+            owner: owner.cloned(),
+            ..object
+        })
+        .collect()
+}
+
+fn list_common_prefixes(prefixes: Vec<String>, encode: impl Fn(String) -> String) -> Vec<CommonPrefix> {
+    prefixes
+        .into_iter()
+        .map(|prefix| CommonPrefix {
+            prefix: Some(encode(prefix)),
+        })
+        .collect()
+}
+
+/// The request body is verified by the service, see [`S3ServiceBuilder::set_verify_checksums`](s3s::service::S3ServiceBuilder::set_verify_checksums).
+fn require_body_checksums(body_checksums: Option<BodyChecksums>) -> S3Result<BodyChecksums> {
+    body_checksums.ok_or_else(|| s3_error!(InternalError, "Checksum verification is not enabled."))
+}
+
 /// <https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Range>
 fn fmt_content_range(start: u64, end_inclusive: u64, size: u64) -> String {
     format!("bytes {start}-{end_inclusive}/{size}")
@@ -57,14 +141,33 @@ fn fmt_content_range(start: u64, end_inclusive: u64, size: u64) -> String {
 impl S3 for FileSystem {
     #[tracing::instrument]
     async fn create_bucket(&self, req: S3Request<CreateBucketInput>) -> S3Result<S3Response<CreateBucketOutput>> {
-        let input = req.input;
+        let region = request_region(&req);
+        let input = &req.input;
         let path = self.get_bucket_path(&input.bucket)?;
 
+        let constraint = input
+            .create_bucket_configuration
+            .as_ref()
+            .and_then(|c| c.location_constraint.as_ref())
+            .map(BucketLocationConstraint::as_str);
+        let constraint = match constraint {
+            Some(constraint) if req.region.is_some() && constraint_region(constraint) != region => {
+                return Err(s3_error!(
+                    IllegalLocationConstraintException,
+                    "The {constraint} location constraint is incompatible for the region specific endpoint this request was sent to."
+                ));
+            }
+            Some(constraint) => constraint,
+            None if region == Region::US_EAST_1 => "",
+            None => region,
+        };
+
         if path.exists() {
             return Err(s3_error!(BucketAlreadyExists));
         }
 
         try_!(fs::create_dir(&path).await);
+        self.save_location_constraint(&input.bucket, constraint).await?;
 
         let output = CreateBucketOutput::default(); // TODO: handle other fields
         Ok(S3Response::new(output))
@@ -72,41 +175,59 @@ impl S3 for FileSystem {
 
     #[tracing::instrument]
     async fn copy_object(&self, req: S3Request<CopyObjectInput>) -> S3Result<S3Response<CopyObjectOutput>> {
-        let input = req.input;
-        let (bucket, key) = match input.copy_source {
+        // The conditional write headers of the destination are not modeled in `CopyObjectInput`.
+        let header = |name: &str| req.headers.get(name).and_then(|v| v.to_str().ok());
+        let dst_preconditions = Preconditions {
+            if_match: header("if-match"),
+            if_none_match: header("if-none-match"),
+            ..Default::default()
+        };
+        let input = &req.input;
+        let (bucket, key, version_id) = match input.copy_source {
             CopySource::AccessPoint { .. } => return Err(s3_error!(NotImplemented)),
-            CopySource::Bucket { ref bucket, ref key, .. } => (bucket, key),
+            CopySource::Bucket {
+                ref bucket,
+                ref key,
+                ref version_id,
+            } => (bucket, key, version_id.as_deref()),
         };
 
-        let src_path = self.get_object_path(bucket, key)?;
+        let src = self.get_version_files(bucket, key, version_id).await?;
         let dst_path = self.get_object_path(&input.bucket, &input.key)?;
 
-        if src_path.exists().not() {
-            return Err(s3_error!(NoSuchKey));
-        }
-
         if self.get_bucket_path(&input.bucket)?.exists().not() {
             return Err(s3_error!(NoSuchBucket));
         }
 
-        if let Some(dir_path) = dst_path.parent() {
-            try_!(fs::create_dir_all(&dir_path).await);
-        }
-
-        let file_metadata = try_!(fs::metadata(&src_path).await);
+        let file_metadata = try_!(fs::metadata(&src.data_path).await);
         let last_modified = Timestamp::from(try_!(file_metadata.modified()));
 
-        let _ = try_!(fs::copy(&src_path, &dst_path).await);
+        let src_md5_sum = self.get_file_md5_sum(&src.data_path).await?;
+        Preconditions::from(input).check_copy_source(&format!("\"{src_md5_sum}\""), &last_modified)?;
 
-        debug!(from = %src_path.display(), to = %dst_path.display(), "copy file");
+        let mut src_file = try_!(fs::File::open(&src.data_path).await);
+        let mut file_writer = self.prepare_file_write(&dst_path).await?;
+        let _ = try_!(tokio::io::copy(&mut src_file, file_writer.writer()).await);
 
-        let src_metadata_path = self.get_metadata_path(bucket, key, None)?;
-        if src_metadata_path.exists() {
-            let dst_metadata_path = self.get_metadata_path(&input.bucket, &input.key, None)?;
-            let _ = try_!(fs::copy(src_metadata_path, dst_metadata_path).await);
+        let _guard = self.lock_object(&input.bucket, &input.key).await;
+        self.check_write_preconditions(&input.bucket, &input.key, dst_preconditions)
+            .await?;
+
+        let dst_version_id = self.prepare_new_version(&input.bucket, &input.key).await?;
+        file_writer.done().await?;
+
+        debug!(from = %src.data_path.display(), to = %dst_path.display(), "copy file");
+
+        if let Some(ref metadata) = src.metadata {
+            self.save_metadata(&input.bucket, &input.key, metadata, None).await?;
         }
 
-        let md5_sum = self.get_md5_sum(bucket, key).await?;
+        let mut info = src.info.unwrap_or_default();
+        set_version_id(&mut info, dst_version_id.as_deref());
+        self.save_internal_info(&input.bucket, &input.key, &info).await?;
+        self.update_key_index(&input.bucket, &input.key).await;
+
+        let md5_sum = self.get_file_md5_sum(&dst_path).await?;
 
         let copy_object_result = CopyObjectResult {
             e_tag: Some(format!("\"{md5_sum}\"")),
@@ -116,6 +237,8 @@ impl S3 for FileSystem {
 
         let output = CopyObjectOutput {
             copy_object_result: Some(copy_object_result),
+            copy_source_version_id: src.version_id,
+            version_id: dst_version_id,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -127,6 +250,8 @@ impl S3 for FileSystem {
         let path = self.get_bucket_path(&input.bucket)?;
         if path.exists() {
             try_!(fs::remove_dir_all(path).await);
+            self.delete_bucket_state(&input.bucket).await?;
+            self.delete_key_index(&input.bucket).await;
         } else {
             return Err(s3_error!(NoSuchBucket));
         }
@@ -136,48 +261,66 @@ impl S3 for FileSystem {
     #[tracing::instrument]
     async fn delete_object(&self, req: S3Request<DeleteObjectInput>) -> S3Result<S3Response<DeleteObjectOutput>> {
         let input = req.input;
-        let path = self.get_object_path(&input.bucket, &input.key)?;
-        if path.exists().not() {
-            return Err(s3_error!(NoSuchKey));
-        }
-        if input.key.ends_with('/') {
-            let mut dir = try_!(fs::read_dir(&path).await);
-            let is_empty = try_!(dir.next_entry().await).is_none();
-            if is_empty {
-                try_!(fs::remove_dir(&path).await);
-            }
-        } else {
-            try_!(fs::remove_file(&path).await);
-        }
-        let output = DeleteObjectOutput::default(); // TODO: handle other fields
+        let _guard = self.lock_object(&input.bucket, &input.key).await;
+        let preconditions = Preconditions {
+            if_match: input.if_match.as_deref(),
+            ..Default::default()
+        };
+        self.check_write_preconditions(&input.bucket, &input.key, preconditions)
+            .await?;
+        let deleted = self
+            .delete_object_version(&input.bucket, &input.key, input.version_id)
+            .await?;
+        let output = DeleteObjectOutput {
+            delete_marker: deleted.delete_marker,
+            version_id: deleted.version_id.or(deleted.delete_marker_version_id),
+            ..Default::default()
+        };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
     async fn delete_objects(&self, req: S3Request<DeleteObjectsInput>) -> S3Result<S3Response<DeleteObjectsOutput>> {
         let input = req.input;
-        let mut objects: Vec<(PathBuf, String)> = Vec::new();
-        for object in input.delete.objects {
-            let path = self.get_object_path(&input.bucket, &object.key)?;
-            if path.exists() {
-                objects.push((path, object.key));
-            }
-        }
+        let versioning = self.load_versioning(&input.bucket).await?;
 
         let mut deleted_objects: Vec<DeletedObject> = Vec::new();
-        for (path, key) in objects {
-            try_!(fs::remove_file(path).await);
+        let mut errors: Vec<Error> = Vec::new();
+        for object in input.delete.objects {
+            let _guard = self.lock_object(&input.bucket, &object.key).await;
 
-            let deleted_object = DeletedObject {
-                key: Some(key),
+            if versioning == Versioning::Unversioned && object.version_id.is_none() {
+                let path = self.get_object_path(&input.bucket, &object.key)?;
+                if path.exists().not() {
+                    continue;
+                }
+            }
+
+            let preconditions = Preconditions {
+                if_match: object.e_tag.as_deref(),
                 ..Default::default()
             };
-
-            deleted_objects.push(deleted_object);
+            let result = async {
+                self.check_write_preconditions(&input.bucket, &object.key, preconditions)
+                    .await?;
+                self.delete_object_version(&input.bucket, &object.key, object.version_id.clone())
+                    .await
+            }
+            .await;
+            match result {
+                Ok(deleted_object) => deleted_objects.push(deleted_object),
+                Err(err) => errors.push(Error {
+                    code: Some(err.code().as_str().to_owned()),
+                    key: Some(object.key),
+                    message: err.message().map(ToOwned::to_owned),
+                    version_id: object.version_id,
+                }),
+            }
         }
 
         let output = DeleteObjectsOutput {
             deleted: Some(deleted_objects),
+            errors: errors.is_empty().not().then_some(errors),
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -185,28 +328,63 @@ impl S3 for FileSystem {
 
     #[tracing::instrument]
     async fn get_bucket_location(&self, req: S3Request<GetBucketLocationInput>) -> S3Result<S3Response<GetBucketLocationOutput>> {
-        let input = req.input;
-        let path = self.get_bucket_path(&input.bucket)?;
+        let path = self.get_bucket_path(&req.input.bucket)?;
 
         if !path.exists() {
             return Err(s3_error!(NoSuchBucket));
         }
 
-        let output = GetBucketLocationOutput::default();
+        // the location constraint of `us-east-1` is empty
+        let constraint = self.location_constraint(&req, &req.input.bucket).await?;
+        let output = GetBucketLocationOutput {
+            location_constraint: constraint.is_empty().not().then(|| constraint.into()),
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_versioning(
+        &self,
+        req: S3Request<GetBucketVersioningInput>,
+    ) -> S3Result<S3Response<GetBucketVersioningOutput>> {
+        let input = req.input;
+        let path = self.get_bucket_path(&input.bucket)?;
+        if path.exists().not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let status = match self.load_versioning(&input.bucket).await? {
+            Versioning::Unversioned => None,
+            Versioning::Enabled => Some(BucketVersioningStatus::from_static(BucketVersioningStatus::ENABLED)),
+            Versioning::Suspended => Some(BucketVersioningStatus::from_static(BucketVersioningStatus::SUSPENDED)),
+        };
+
+        let output = GetBucketVersioningOutput {
+            status,
+            ..Default::default()
+        };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
     async fn get_object(&self, req: S3Request<GetObjectInput>) -> S3Result<S3Response<GetObjectOutput>> {
         let input = req.input;
-        let object_path = self.get_object_path(&input.bucket, &input.key)?;
+        let version = self
+            .get_version_files(&input.bucket, &input.key, input.version_id.as_deref())
+            .await?;
 
-        let mut file = fs::File::open(&object_path).await.map_err(|e| s3_error!(e, NoSuchKey))?;
+        let mut file = fs::File::open(&version.data_path)
+            .await
+            .map_err(|e| s3_error!(e, NoSuchKey))?;
 
         let file_metadata = try_!(file.metadata().await);
         let last_modified = Timestamp::from(try_!(file_metadata.modified()));
         let file_len = file_metadata.len();
 
+        let md5_sum = self.get_file_md5_sum(&version.data_path).await?;
+        let e_tag = format!("\"{md5_sum}\"");
+        Preconditions::from(&input).check_read(&e_tag, &last_modified)?;
+
         let (content_length, content_range) = match input.range {
             None => (file_len, None),
             Some(range) => {
@@ -232,13 +410,7 @@ impl S3 for FileSystem {
 
         let body = bytes_stream(ReaderStream::with_capacity(file, 4096), content_length_usize);
 
-        let object_metadata = self.load_metadata(&input.bucket, &input.key, None).await?;
-
-        let md5_sum = self.get_md5_sum(&input.bucket, &input.key).await?;
-        let e_tag = format!("\"{md5_sum}\"");
-
-        let info = self.load_internal_info(&input.bucket, &input.key).await?;
-        let checksum = match &info {
+        let checksum = match &version.info {
             Some(info) => crate::checksum::from_internal_info(info),
             None => default(),
         };
@@ -248,12 +420,13 @@ impl S3 for FileSystem {
             content_length: Some(content_length_i64),
             content_range,
             last_modified: Some(last_modified),
-            metadata: object_metadata,
+            metadata: version.metadata,
             e_tag: Some(e_tag),
             checksum_crc32: checksum.checksum_crc32,
             checksum_crc32c: checksum.checksum_crc32c,
             checksum_sha1: checksum.checksum_sha1,
             checksum_sha256: checksum.checksum_sha256,
+            version_id: version.version_id,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -261,30 +434,33 @@ impl S3 for FileSystem {
 
     #[tracing::instrument]
     async fn head_bucket(&self, req: S3Request<HeadBucketInput>) -> S3Result<S3Response<HeadBucketOutput>> {
-        let input = req.input;
-        let path = self.get_bucket_path(&input.bucket)?;
+        let path = self.get_bucket_path(&req.input.bucket)?;
 
         if !path.exists() {
             return Err(s3_error!(NoSuchBucket));
         }
 
-        Ok(S3Response::new(HeadBucketOutput::default()))
+        let output = HeadBucketOutput {
+            bucket_region: Some(self.bucket_region(&req, &req.input.bucket).await?),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
     async fn head_object(&self, req: S3Request<HeadObjectInput>) -> S3Result<S3Response<HeadObjectOutput>> {
         let input = req.input;
-        let path = self.get_object_path(&input.bucket, &input.key)?;
+        let version = self
+            .get_version_files(&input.bucket, &input.key, input.version_id.as_deref())
+            .await?;
 
-        if !path.exists() {
-            return Err(s3_error!(NoSuchBucket));
-        }
-
-        let file_metadata = try_!(fs::metadata(path).await);
+        let file_metadata = try_!(fs::metadata(&version.data_path).await);
         let last_modified = Timestamp::from(try_!(file_metadata.modified()));
         let file_len = file_metadata.len();
 
-        let object_metadata = self.load_metadata(&input.bucket, &input.key, None).await?;
+        let md5_sum = self.get_file_md5_sum(&version.data_path).await?;
+        let e_tag = format!("\"{md5_sum}\"");
+        Preconditions::from(&input).check_read(&e_tag, &last_modified)?;
 
         // TODO: detect content type
         let content_type = mime::APPLICATION_OCTET_STREAM;
@@ -293,14 +469,18 @@ impl S3 for FileSystem {
             content_length: Some(try_!(i64::try_from(file_len))),
             content_type: Some(content_type),
             last_modified: Some(last_modified),
-            metadata: object_metadata,
+            metadata: version.metadata,
+            e_tag: Some(e_tag),
+            version_id: version.version_id,
             ..Default::default()
         };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn list_buckets(&self, _: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
+    async fn list_buckets(&self, req: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
+        let visible = req.extensions.get::<VisibleBuckets>();
+
         let mut buckets: Vec<Bucket> = Vec::new();
         let mut iter = try_!(fs::read_dir(&self.root).await);
         while let Some(entry) = try_!(iter.next_entry().await) {
@@ -314,6 +494,9 @@ impl S3 for FileSystem {
             if s3s::path::check_bucket_name(name).not() {
                 continue;
             }
+            if visible.is_some_and(|v| v.contains(name).not()) {
+                continue;
+            }
 
             let file_meta = try_!(entry.metadata().await);
             // Not all filesystems/mounts provide all file attributes like created timestamp,
@@ -324,7 +507,7 @@ impl S3 for FileSystem {
             let bucket = Bucket {
                 creation_date: Some(created_or_modified_date),
                 name: Some(name.to_owned()),
-                bucket_region: None,
+                bucket_region: Some(self.bucket_region(&req, name).await?),
             };
             buckets.push(bucket);
         }
@@ -339,21 +522,90 @@ impl S3 for FileSystem {
 
     #[tracing::instrument]
     async fn list_objects(&self, req: S3Request<ListObjectsInput>) -> S3Result<S3Response<ListObjectsOutput>> {
-        let v2_resp = self.list_objects_v2(req.map_input(Into::into)).await?;
-
-        Ok(v2_resp.map_output(|v2| ListObjectsOutput {
-            contents: v2.contents,
-            delimiter: v2.delimiter,
-            encoding_type: v2.encoding_type,
-            name: v2.name,
-            prefix: v2.prefix,
-            max_keys: v2.max_keys,
+        let input = &req.input;
+        let url_encoded = parse_encoding_type(input.encoding_type.as_ref())?;
+        let max_keys = parse_max_keys(input.max_keys)?;
+
+        let params = ListParams {
+            prefix: list_prefix(input.prefix.as_deref()),
+            delimiter: input.delimiter.as_deref(),
+            marker: input.marker.as_deref(),
+            max_keys,
+        };
+        let page = self.list_page(&input.bucket, &params).await?;
+
+        let encode = |s: String| if url_encoded { url_encode(&s) } else { s };
+        let owner = list_owner(&req);
+
+        let input = req.input;
+        let output = ListObjectsOutput {
+            contents: Some(list_contents(page.objects, owner.as_ref(), encode)),
+            common_prefixes: Some(list_common_prefixes(page.common_prefixes, encode)),
+            delimiter: input.delimiter.map(encode),
+            encoding_type: input.encoding_type,
+            is_truncated: Some(page.is_truncated),
+            marker: input.marker.map(encode),
+            max_keys: Some(max_keys.numeric_cast()),
+            name: Some(input.bucket),
+            next_marker: page.next_marker.map(encode),
+            prefix: input.prefix.map(encode),
             ..Default::default()
-        }))
+        };
+        Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
     async fn list_objects_v2(&self, req: S3Request<ListObjectsV2Input>) -> S3Result<S3Response<ListObjectsV2Output>> {
+        let input = &req.input;
+        let url_encoded = parse_encoding_type(input.encoding_type.as_ref())?;
+        let max_keys = parse_max_keys(input.max_keys)?;
+
+        // The continuation token takes precedence over `start-after`.
+        let marker = match input.continuation_token {
+            Some(ref token) => Some(decode_continuation_token(token)?),
+            None => input.start_after.clone(),
+        };
+
+        let params = ListParams {
+            prefix: list_prefix(input.prefix.as_deref()),
+            delimiter: input.delimiter.as_deref(),
+            marker: marker.as_deref(),
+            max_keys,
+        };
+        let page = self.list_page(&input.bucket, &params).await?;
+
+        let encode = |s: String| if url_encoded { url_encode(&s) } else { s };
+        let owner = if input.fetch_owner == Some(true) {
+            list_owner(&req)
+        } else {
+            None
+        };
+        let key_count = page.objects.len() + page.common_prefixes.len();
+
+        let input = req.input;
+        let output = ListObjectsV2Output {
+            key_count: Some(key_count.numeric_cast()),
+            max_keys: Some(max_keys.numeric_cast()),
+            contents: Some(list_contents(page.objects, owner.as_ref(), encode)),
+            common_prefixes: Some(list_common_prefixes(page.common_prefixes, encode)),
+            continuation_token: input.continuation_token,
+            delimiter: input.delimiter.map(encode),
+            encoding_type: input.encoding_type,
+            is_truncated: Some(page.is_truncated),
+            name: Some(input.bucket),
+            next_continuation_token: page.next_marker.as_deref().map(encode_continuation_token),
+            prefix: input.prefix.map(encode),
+            start_after: input.start_after.map(encode),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn list_object_versions(
+        &self,
+        req: S3Request<ListObjectVersionsInput>,
+    ) -> S3Result<S3Response<ListObjectVersionsOutput>> {
         let input = req.input;
         let path = self.get_bucket_path(&input.bucket)?;
 
@@ -361,10 +613,9 @@ impl S3 for FileSystem {
             return Err(s3_error!(NoSuchBucket));
         }
 
-        let mut objects: Vec<Object> = default();
+        let mut keys = self.list_versioned_keys(&input.bucket).await?;
         let mut dir_queue: VecDeque<PathBuf> = default();
         dir_queue.push_back(path.clone());
-
         while let Some(dir) = dir_queue.pop_front() {
             let mut iter = try_!(fs::read_dir(dir).await);
             while let Some(entry) = try_!(iter.next_entry().await) {
@@ -374,70 +625,137 @@ impl S3 for FileSystem {
                 } else {
                     let file_path = entry.path();
                     let key = try_!(file_path.strip_prefix(&path));
-                    let delimiter = input.delimiter.as_ref().map_or("/", |d| d.as_str());
-                    let Some(key_str) = normalize_path(key, delimiter) else {
-                        continue;
-                    };
+                    if let Some(key) = normalize_path(key, "/") {
+                        keys.push(key);
+                    }
+                }
+            }
+        }
+
+        if let Some(ref prefix) = input.prefix {
+            keys.retain(|k| k.starts_with(prefix.as_str()));
+        }
+        if let Some(ref key_marker) = input.key_marker {
+            keys.retain(|k| k.as_str() >= key_marker.as_str());
+        }
+        keys.sort();
+        keys.dedup();
+
+        let max_keys = input.max_keys.unwrap_or(1000).max(0).numeric_cast::<usize>();
+
+        let mut versions: Vec<ObjectVersion> = default();
+        let mut delete_markers: Vec<DeleteMarkerEntry> = default();
+        let mut count = 0;
+        let mut is_truncated = false;
+        let mut next_marker: Option<(String, String)> = None;
+
+        'keys: for key in keys {
+            // Skips the versions before the marker, or the whole key without a version id marker.
+            let mut skipping = input.key_marker.as_deref() == Some(key.as_str());
+            let version_id_marker = input.version_id_marker.as_deref().filter(|_| skipping);
+            if skipping && version_id_marker.is_none() {
+                continue;
+            }
 
-                    if let Some(ref prefix) = input.prefix {
-                        let prefix_path: PathBuf = prefix.split(delimiter).collect();
+            let current_version_id = self.current_version_id(&input.bucket, &key).await?;
+            let index = self.load_version_index(&input.bucket, &key).await?;
 
-                        let key_s = format!("{}", key.display());
-                        let prefix_path_s = format!("{}", prefix_path.display());
+            let current = current_version_id.iter().map(|v| (v.as_str(), None));
+            let noncurrent = index.iter().map(|e| (e.version_id.as_str(), Some(e)));
 
-                        if !key_s.starts_with(&prefix_path_s) {
-                            continue;
-                        }
-                    }
+            for (i, (version_id, entry)) in current.chain(noncurrent).enumerate() {
+                if skipping {
+                    skipping = Some(version_id) != version_id_marker;
+                    continue;
+                }
 
-                    let metadata = try_!(entry.metadata().await);
-                    let last_modified = Timestamp::from(try_!(metadata.modified()));
-                    let size = metadata.len();
+                if count == max_keys {
+                    is_truncated = true;
+                    break 'keys;
+                }
+                count += 1;
+                next_marker = Some((key.clone(), version_id.to_owned()));
 
-                    let object = Object {
-                        key: Some(key_str),
-                        last_modified: Some(last_modified),
-                        size: Some(try_!(i64::try_from(size))),
+                let is_latest = i == 0;
+
+                if let Some(entry) = entry.filter(|e| e.delete_marker) {
+                    delete_markers.push(DeleteMarkerEntry {
+                        is_latest: Some(is_latest),
+                        key: Some(key.clone()),
+                        last_modified: Some(entry.last_modified()),
+                        version_id: Some(entry.version_id.clone()),
                         ..Default::default()
-                    };
-                    objects.push(object);
+                    });
+                    continue;
                 }
+
+                let data_path = match entry {
+                    None => self.get_object_path(&input.bucket, &key)?,
+                    Some(_) => self.get_noncurrent_paths(&input.bucket, &key, version_id)?[0].clone(),
+                };
+                let file_metadata = try_!(fs::metadata(&data_path).await);
+                let last_modified = Timestamp::from(try_!(file_metadata.modified()));
+                let md5_sum = self.get_file_md5_sum(&data_path).await?;
+
+                versions.push(ObjectVersion {
+                    e_tag: Some(format!("\"{md5_sum}\"")),
+                    is_latest: Some(is_latest),
+                    key: Some(key.clone()),
+                    last_modified: Some(last_modified),
+                    size: Some(try_!(i64::try_from(file_metadata.len()))),
+                    version_id: Some(version_id.to_owned()),
+                    ..Default::default()
+                });
             }
         }
 
-        objects.sort_by(|lhs, rhs| {
-            let lhs_key = lhs.key.as_deref().unwrap_or("");
-            let rhs_key = rhs.key.as_deref().unwrap_or("");
-            lhs_key.cmp(rhs_key)
-        });
-
-        let objects = if let Some(marker) = &input.start_after {
-            objects
-                .into_iter()
-                .skip_while(|n| n.key.as_deref().unwrap_or("") <= marker.as_str())
-                .collect()
-        } else {
-            objects
+        let (next_key_marker, next_version_id_marker) = match next_marker.filter(|_| is_truncated) {
+            Some((key, version_id)) => (Some(key), Some(version_id)),
+            None => (None, None),
         };
 
-        let key_count = try_!(i32::try_from(objects.len()));
-
-        let output = ListObjectsV2Output {
-            key_count: Some(key_count),
-            max_keys: Some(key_count),
-            contents: Some(objects),
-            delimiter: input.delimiter,
-            encoding_type: input.encoding_type,
+        let output = ListObjectVersionsOutput {
+            delete_markers: Some(delete_markers),
+            is_truncated: Some(is_truncated),
+            key_marker: input.key_marker,
+            max_keys: Some(max_keys.numeric_cast()),
             name: Some(input.bucket),
+            next_key_marker,
+            next_version_id_marker,
             prefix: input.prefix,
+            version_id_marker: input.version_id_marker,
+            versions: Some(versions),
             ..Default::default()
         };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+    async fn put_bucket_versioning(
+        &self,
+        req: S3Request<PutBucketVersioningInput>,
+    ) -> S3Result<S3Response<PutBucketVersioningOutput>> {
         let input = req.input;
+        let path = self.get_bucket_path(&input.bucket)?;
+        if path.exists().not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(status) = input.versioning_configuration.status else {
+            return Err(s3_error!(MalformedXML, "The versioning status is required"));
+        };
+        match status.as_str() {
+            BucketVersioningStatus::ENABLED | BucketVersioningStatus::SUSPENDED => {}
+            _ => return Err(s3_error!(MalformedXML, "Invalid versioning status")),
+        }
+        self.save_versioning(&input.bucket, &status).await?;
+
+        Ok(S3Response::new(PutBucketVersioningOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+        let mut input = req.input;
         if let Some(ref storage_class) = input.storage_class {
             let is_valid = ["STANDARD", "REDUCED_REDUNDANCY"].contains(&storage_class.as_str());
             if !is_valid {
@@ -445,30 +763,15 @@ impl S3 for FileSystem {
             }
         }
 
-        let PutObjectInput {
-            body,
-            bucket,
-            key,
-            metadata,
-            content_length,
-            ..
-        } = input;
+        // Fails fast before receiving the body. The preconditions are checked again when committing.
+        self.check_write_preconditions(&input.bucket, &input.key, Preconditions::from(&input))
+            .await?;
 
-        let Some(body) = body else { return Err(s3_error!(IncompleteBody)) };
-
-        let mut checksum: s3s::checksum::ChecksumHasher = default();
-        if input.checksum_crc32.is_some() {
-            checksum.crc32 = Some(default());
-        }
-        if input.checksum_crc32c.is_some() {
-            checksum.crc32c = Some(default());
-        }
-        if input.checksum_sha1.is_some() {
-            checksum.sha1 = Some(default());
-        }
-        if input.checksum_sha256.is_some() {
-            checksum.sha256 = Some(default());
-        }
+        let Some(body) = input.body.take() else { return Err(s3_error!(IncompleteBody)) };
+        let bucket = input.bucket.clone();
+        let key = input.key.clone();
+        let metadata = input.metadata.take();
+        let content_length = input.content_length;
 
         if key.ends_with('/') {
             if let Some(len) = content_length {
@@ -482,33 +785,22 @@ impl S3 for FileSystem {
             return Ok(S3Response::new(output));
         }
 
+        let body_checksums = require_body_checksums(req.body_checksums)?;
+
         let object_path = self.get_object_path(&bucket, &key)?;
         let mut file_writer = self.prepare_file_write(&object_path).await?;
 
-        let mut md5_hash = Md5::new();
-        let stream = body.inspect_ok(|bytes| {
-            md5_hash.update(bytes.as_ref());
-            checksum.update(bytes.as_ref());
-        });
+        let size = copy_bytes(body, file_writer.writer()).await?;
 
-        let size = copy_bytes(stream, file_writer.writer()).await?;
-        file_writer.done().await?;
+        let computed = body_checksums.get().ok_or_else(|| s3_error!(IncompleteBody))?;
+        let (md5_sum, checksum) = (hex(computed.md5), computed.checksum);
 
-        let md5_sum = hex(md5_hash.finalize());
+        let _guard = self.lock_object(&bucket, &key).await;
+        self.check_write_preconditions(&bucket, &key, Preconditions::from(&input))
+            .await?;
 
-        let checksum = checksum.finalize();
-        if checksum.checksum_crc32 != input.checksum_crc32 {
-            return Err(s3_error!(BadDigest, "checksum_crc32 mismatch"));
-        }
-        if checksum.checksum_crc32c != input.checksum_crc32c {
-            return Err(s3_error!(BadDigest, "checksum_crc32c mismatch"));
-        }
-        if checksum.checksum_sha1 != input.checksum_sha1 {
-            return Err(s3_error!(BadDigest, "checksum_sha1 mismatch"));
-        }
-        if checksum.checksum_sha256 != input.checksum_sha256 {
-            return Err(s3_error!(BadDigest, "checksum_sha256 mismatch"));
-        }
+        let version_id = self.prepare_new_version(&bucket, &key).await?;
+        file_writer.done().await?;
 
         debug!(path = %object_path.display(), ?size, %md5_sum, ?checksum, "write file");
 
@@ -518,7 +810,9 @@ impl S3 for FileSystem {
 
         let mut info: InternalInfo = default();
         crate::checksum::modify_internal_info(&mut info, &checksum);
+        set_version_id(&mut info, version_id.as_deref());
         self.save_internal_info(&bucket, &key, &info).await?;
+        self.update_key_index(&bucket, &key).await;
 
         let e_tag = format!("\"{md5_sum}\"");
 
@@ -528,6 +822,7 @@ impl S3 for FileSystem {
             checksum_crc32c: checksum.checksum_crc32c,
             checksum_sha1: checksum.checksum_sha1,
             checksum_sha256: checksum.checksum_sha256,
+            version_id,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -573,20 +868,24 @@ impl S3 for FileSystem {
         }
 
         let file_path = self.resolve_upload_part_path(upload_id, part_number)?;
-
-        let mut md5_hash = Md5::new();
-        let stream = body.inspect_ok(|bytes| md5_hash.update(bytes.as_ref()));
+        let body_checksums = require_body_checksums(req.body_checksums)?;
 
         let mut file_writer = self.prepare_file_write(&file_path).await?;
-        let size = copy_bytes(stream, file_writer.writer()).await?;
-        file_writer.done().await?;
+        let size = copy_bytes(body, file_writer.writer()).await?;
 
-        let md5_sum = hex(md5_hash.finalize());
+        let computed = body_checksums.get().ok_or_else(|| s3_error!(IncompleteBody))?;
+        let (md5_sum, checksum) = (hex(computed.md5), computed.checksum);
+
+        file_writer.done().await?;
 
-        debug!(path = %file_path.display(), ?size, %md5_sum, "write file");
+        debug!(path = %file_path.display(), ?size, %md5_sum, ?checksum, "write file");
 
         let output = UploadPartOutput {
             e_tag: Some(format!("\"{md5_sum}\"")),
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -602,15 +901,24 @@ impl S3 for FileSystem {
             return Err(s3_error!(AccessDenied));
         }
 
-        let (src_bucket, src_key) = match input.copy_source {
+        let (src_bucket, src_key, src_version_id) = match input.copy_source {
             CopySource::AccessPoint { .. } => return Err(s3_error!(NotImplemented)),
-            CopySource::Bucket { ref bucket, ref key, .. } => (bucket, key),
+            CopySource::Bucket {
+                ref bucket,
+                ref key,
+                ref version_id,
+            } => (bucket, key, version_id.as_deref()),
         };
-        let src_path = self.get_object_path(src_bucket, src_key)?;
+        let src = self.get_version_files(src_bucket, src_key, src_version_id).await?;
         let dst_path = self.resolve_upload_part_path(upload_id, part_number)?;
 
-        let mut src_file = fs::File::open(&src_path).await.map_err(|e| s3_error!(e, NoSuchKey))?;
-        let file_len = try_!(src_file.metadata().await).len();
+        let mut src_file = fs::File::open(&src.data_path).await.map_err(|e| s3_error!(e, NoSuchKey))?;
+        let src_metadata = try_!(src_file.metadata().await);
+        let file_len = src_metadata.len();
+
+        let src_last_modified = Timestamp::from(try_!(src_metadata.modified()));
+        let src_md5_sum = self.get_file_md5_sum(&src.data_path).await?;
+        Preconditions::from(&input).check_copy_source(&format!("\"{src_md5_sum}\""), &src_last_modified)?;
 
         let (start, end) = if let Some(copy_range) = &input.copy_source_range {
             if !copy_range.starts_with("bytes=") {
@@ -654,6 +962,7 @@ impl S3 for FileSystem {
                 e_tag: Some(format!("\"{md5_sum}\"")),
                 ..Default::default()
             }),
+            copy_source_version_id: src.version_id,
             ..Default::default()
         };
 
@@ -712,27 +1021,23 @@ impl S3 for FileSystem {
         &self,
         req: S3Request<CompleteMultipartUploadInput>,
     ) -> S3Result<S3Response<CompleteMultipartUploadOutput>> {
-        let CompleteMultipartUploadInput {
-            multipart_upload,
-            bucket,
-            key,
-            upload_id,
-            ..
-        } = req.input;
-
-        let Some(multipart_upload) = multipart_upload else { return Err(s3_error!(InvalidPart)) };
+        let mut input = req.input;
+        let Some(multipart_upload) = input.multipart_upload.take() else {
+            return Err(s3_error!(InvalidPart));
+        };
+        let bucket = input.bucket.clone();
+        let key = input.key.clone();
 
-        let upload_id = Uuid::parse_str(&upload_id).map_err(|_| s3_error!(InvalidRequest))?;
+        let upload_id = Uuid::parse_str(&input.upload_id).map_err(|_| s3_error!(InvalidRequest))?;
         if self.verify_upload_id(req.credentials.as_ref(), &upload_id).await?.not() {
             return Err(s3_error!(AccessDenied));
         }
 
-        self.delete_upload_id(&upload_id).await?;
+        // Fails fast before concatenating the parts. The preconditions are checked again when committing.
+        self.check_write_preconditions(&bucket, &key, Preconditions::from(&input))
+            .await?;
 
-        if let Ok(Some(metadata)) = self.load_metadata(&bucket, &key, Some(upload_id)).await {
-            self.save_metadata(&bucket, &key, &metadata, None).await?;
-            let _ = self.delete_metadata(&bucket, &key, Some(upload_id));
-        }
+        self.delete_upload_id(&upload_id).await?;
 
         let object_path = self.get_object_path(&bucket, &key)?;
         let mut file_writer = self.prepare_file_write(&object_path).await?;
@@ -755,8 +1060,24 @@ impl S3 for FileSystem {
             debug!(from = %part_path.display(), tmp = %file_writer.tmp_path().display(), to = %file_writer.dest_path().display(), ?size, "write file");
             try_!(fs::remove_file(&part_path).await);
         }
+
+        let _guard = self.lock_object(&bucket, &key).await;
+        self.check_write_preconditions(&bucket, &key, Preconditions::from(&input))
+            .await?;
+
+        let version_id = self.prepare_new_version(&bucket, &key).await?;
         file_writer.done().await?;
 
+        if let Ok(Some(metadata)) = self.load_metadata(&bucket, &key, Some(upload_id)).await {
+            self.save_metadata(&bucket, &key, &metadata, None).await?;
+            let _ = self.delete_metadata(&bucket, &key, Some(upload_id));
+        }
+
+        let mut info: InternalInfo = default();
+        set_version_id(&mut info, version_id.as_deref());
+        self.save_internal_info(&bucket, &key, &info).await?;
+        self.update_key_index(&bucket, &key).await;
+
         let file_size = try_!(fs::metadata(&object_path).await).len();
         let md5_sum = self.get_md5_sum(&bucket, &key).await?;
 
@@ -766,6 +1087,7 @@ impl S3 for FileSystem {
             bucket: Some(bucket),
             key: Some(key),
             e_tag: Some(format!("\"{md5_sum}\"")),
+            version_id,
             ..Default::default()
         };
         Ok(S3Response::new(output))
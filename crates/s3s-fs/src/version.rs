@@ -0,0 +1,520 @@
+//! Object versioning
+//!
+//! The current version of an object is stored at the object path as usual,
+//! and its version id is kept in the internal info (no version id means the null version).
+//!
+//! When a bucket is versioned, noncurrent versions and delete markers are moved into
+//! a per-object directory, together with an index of them sorted from newest to oldest.
+//! If the object path does not exist, the newest entry of the index (if any) is a delete marker.
+
+use crate::error::*;
+use crate::fs::{FileSystem, InternalInfo};
+
+use s3s::S3Result;
+use s3s::dto;
+use s3s::header::{X_AMZ_DELETE_MARKER, X_AMZ_VERSION_ID};
+use s3s::{S3Error, S3ErrorCode, s3_error};
+
+use std::ops::Not;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use tokio::fs;
+
+use hyper::HeaderMap;
+use hyper::header::HeaderValue;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub(crate) const NULL_VERSION_ID: &str = "null";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Versioning {
+    Unversioned,
+    Enabled,
+    Suspended,
+}
+
+/// A noncurrent version or a delete marker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct VersionEntry {
+    pub version_id: String,
+    #[serde(default)]
+    pub delete_marker: bool,
+    /// Milliseconds since the unix epoch
+    pub last_modified: u64,
+}
+
+impl VersionEntry {
+    pub fn last_modified(&self) -> dto::Timestamp {
+        dto::Timestamp::from(SystemTime::UNIX_EPOCH + Duration::from_millis(self.last_modified))
+    }
+}
+
+/// The files of a version which can be read
+#[derive(Debug)]
+pub(crate) struct VersionFiles {
+    pub data_path: PathBuf,
+    pub metadata: Option<dto::Metadata>,
+    pub info: Option<InternalInfo>,
+    /// `None` means the object is not versioned
+    pub version_id: Option<String>,
+}
+
+/// Saves the version id of the current version in the internal info.
+pub(crate) fn set_version_id(info: &mut InternalInfo, version_id: Option<&str>) {
+    match version_id {
+        Some(v) if v != NULL_VERSION_ID => {
+            info.insert("version_id".to_owned(), serde_json::Value::String(v.to_owned()));
+        }
+        _ => {
+            info.remove("version_id");
+        }
+    }
+}
+
+fn millis(time: SystemTime) -> u64 {
+    let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX)
+}
+
+fn new_version_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+fn is_valid_version_id(version_id: &str) -> bool {
+    version_id == NULL_VERSION_ID || (version_id.len() == 32 && version_id.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+fn check_version_id(version_id: &str) -> S3Result<()> {
+    if is_valid_version_id(version_id) {
+        Ok(())
+    } else {
+        Err(s3_error!(InvalidArgument, "Invalid version id specified"))
+    }
+}
+
+fn delete_marker_error(code: S3ErrorCode, version_id: Option<&str>) -> S3Error {
+    let mut err = S3Error::new(code);
+    let mut headers = HeaderMap::new();
+    headers.insert(X_AMZ_DELETE_MARKER, HeaderValue::from_static("true"));
+    if let Some(val) = version_id.and_then(|v| HeaderValue::try_from(v).ok()) {
+        headers.insert(X_AMZ_VERSION_ID, val);
+    }
+    err.set_headers(headers);
+    err
+}
+
+async fn load_json_if_exists<T: DeserializeOwned>(path: &PathBuf) -> Result<Option<T>> {
+    if path.exists().not() {
+        return Ok(None);
+    }
+    let content = fs::read(path).await?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+async fn remove_file_if_exists(path: &PathBuf) -> Result<()> {
+    match fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn rename_if_exists(from: &PathBuf, to: &PathBuf) -> Result<()> {
+    match fs::rename(from, to).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl FileSystem {
+    fn get_versioning_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        self.resolve_abs_path(format!(".bucket-{}.versioning.json", encode(bucket)))
+    }
+
+    /// resolve the directory of noncurrent versions and delete markers
+    pub(crate) fn get_versions_dir(&self, bucket: &str, key: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        self.resolve_abs_path(format!(".bucket-{}.object-{}.versions", encode(bucket), encode(key)))
+    }
+
+    /// Returns the keys which have noncurrent versions or delete markers.
+    pub(crate) async fn list_versioned_keys(&self, bucket: &str) -> Result<Vec<String>> {
+        let prefix = format!(".bucket-{}.object-", base64_simd::URL_SAFE_NO_PAD.encode_to_string(bucket));
+        let mut keys = Vec::new();
+        let mut iter = fs::read_dir(&self.root).await?;
+        while let Some(entry) = iter.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str() else { continue };
+            let Some(encoded) = name.strip_prefix(&prefix).and_then(|s| s.strip_suffix(".versions")) else {
+                continue;
+            };
+            let Ok(key) = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(encoded) else { continue };
+            let Ok(key) = String::from_utf8(key) else { continue };
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+
+    pub(crate) async fn load_versioning(&self, bucket: &str) -> Result<Versioning> {
+        let path = self.get_versioning_path(bucket)?;
+        if path.exists().not() {
+            return Ok(Versioning::Unversioned);
+        }
+        let content = fs::read(&path).await?;
+        let status: String = serde_json::from_slice(&content)?;
+        Ok(match status.as_str() {
+            dto::BucketVersioningStatus::ENABLED => Versioning::Enabled,
+            _ => Versioning::Suspended,
+        })
+    }
+
+    pub(crate) async fn save_versioning(&self, bucket: &str, status: &dto::BucketVersioningStatus) -> Result<()> {
+        let path = self.get_versioning_path(bucket)?;
+        let content = serde_json::to_vec(status.as_str())?;
+        fs::write(&path, &content).await?;
+        Ok(())
+    }
+
+    /// Removes the versioning state, the metadata and the versions of all objects in a bucket.
+    pub(crate) async fn delete_bucket_state(&self, bucket: &str) -> Result<()> {
+        let prefix = format!(".bucket-{}.", base64_simd::URL_SAFE_NO_PAD.encode_to_string(bucket));
+        let mut iter = fs::read_dir(&self.root).await?;
+        while let Some(entry) = iter.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str() else { continue };
+            if name.starts_with(&prefix).not() {
+                continue;
+            }
+            if entry.file_type().await?.is_dir() {
+                fs::remove_dir_all(entry.path()).await?;
+            } else {
+                fs::remove_file(entry.path()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// load the index of noncurrent versions and delete markers, from newest to oldest
+    pub(crate) async fn load_version_index(&self, bucket: &str, key: &str) -> Result<Vec<VersionEntry>> {
+        let path = self.get_versions_dir(bucket, key)?.join("index.json");
+        if path.exists().not() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read(&path).await?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    async fn save_version_index(&self, bucket: &str, key: &str, index: &[VersionEntry]) -> Result<()> {
+        let dir = self.get_versions_dir(bucket, key)?;
+        if index.is_empty() {
+            if dir.exists() {
+                fs::remove_dir_all(&dir).await?;
+            }
+            return Ok(());
+        }
+        fs::create_dir_all(&dir).await?;
+        let content = serde_json::to_vec(index)?;
+        fs::write(dir.join("index.json"), &content).await?;
+        Ok(())
+    }
+
+    /// Returns the paths of the data, the metadata and the internal info of a noncurrent version.
+    pub(crate) fn get_noncurrent_paths(&self, bucket: &str, key: &str, version_id: &str) -> Result<[PathBuf; 3]> {
+        let dir = self.get_versions_dir(bucket, key)?;
+        Ok([
+            dir.join(version_id),
+            dir.join(format!("{version_id}.metadata.json")),
+            dir.join(format!("{version_id}.internal.json")),
+        ])
+    }
+
+    fn get_current_paths(&self, bucket: &str, key: &str) -> Result<[PathBuf; 3]> {
+        Ok([
+            self.get_object_path(bucket, key)?,
+            self.get_metadata_path(bucket, key, None)?,
+            self.get_internal_info_path(bucket, key)?,
+        ])
+    }
+
+    /// Returns the version id of the current version, or `None` if there is no current version.
+    pub(crate) async fn current_version_id(&self, bucket: &str, key: &str) -> Result<Option<String>> {
+        let path = self.get_object_path(bucket, key)?;
+        if path.is_file().not() {
+            return Ok(None);
+        }
+        let info = self.load_internal_info(bucket, key).await?;
+        let version_id = info.as_ref().and_then(|info| info.get("version_id")).and_then(|v| v.as_str());
+        Ok(Some(version_id.unwrap_or(NULL_VERSION_ID).to_owned()))
+    }
+
+    /// Moves the current version into the noncurrent versions.
+    async fn archive_current(&self, bucket: &str, key: &str, index: &mut Vec<VersionEntry>) -> Result<()> {
+        let Some(version_id) = self.current_version_id(bucket, key).await? else { return Ok(()) };
+
+        let [data, metadata, info] = self.get_current_paths(bucket, key)?;
+        let last_modified = fs::metadata(&data).await?.modified()?;
+
+        self.remove_noncurrent(bucket, key, index, &version_id).await?;
+
+        let [dst_data, dst_metadata, dst_info] = self.get_noncurrent_paths(bucket, key, &version_id)?;
+        fs::create_dir_all(self.get_versions_dir(bucket, key)?).await?;
+        fs::rename(&data, &dst_data).await?;
+        rename_if_exists(&metadata, &dst_metadata).await?;
+        rename_if_exists(&info, &dst_info).await?;
+
+        index.insert(
+            0,
+            VersionEntry {
+                version_id,
+                delete_marker: false,
+                last_modified: millis(last_modified),
+            },
+        );
+        Ok(())
+    }
+
+    /// Removes the metadata and the internal info of the current version.
+    /// The data is removed only if `remove_data` is true.
+    async fn discard_current(&self, bucket: &str, key: &str, remove_data: bool) -> Result<()> {
+        let [data, metadata, info] = self.get_current_paths(bucket, key)?;
+        if remove_data {
+            remove_file_if_exists(&data).await?;
+        }
+        remove_file_if_exists(&metadata).await?;
+        remove_file_if_exists(&info).await?;
+        Ok(())
+    }
+
+    async fn remove_noncurrent(&self, bucket: &str, key: &str, index: &mut Vec<VersionEntry>, version_id: &str) -> Result<()> {
+        let Some(pos) = index.iter().position(|e| e.version_id == version_id) else { return Ok(()) };
+        let entry = index.remove(pos);
+        for path in self.get_noncurrent_paths(bucket, key, &entry.version_id)? {
+            remove_file_if_exists(&path).await?;
+        }
+        Ok(())
+    }
+
+    /// Makes the newest noncurrent version current, if there is no current version
+    /// and the newest one is not a delete marker.
+    async fn promote_newest(&self, bucket: &str, key: &str, index: &mut Vec<VersionEntry>) -> Result<()> {
+        if self.get_object_path(bucket, key)?.exists() {
+            return Ok(());
+        }
+        let Some(entry) = index.first() else { return Ok(()) };
+        if entry.delete_marker {
+            return Ok(());
+        }
+
+        let entry = index.remove(0);
+        let [data, metadata, info] = self.get_noncurrent_paths(bucket, key, &entry.version_id)?;
+        let [dst_data, dst_metadata, dst_info] = self.get_current_paths(bucket, key)?;
+        if let Some(dir) = dst_data.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::rename(&data, &dst_data).await?;
+        rename_if_exists(&metadata, &dst_metadata).await?;
+        rename_if_exists(&info, &dst_info).await?;
+        Ok(())
+    }
+
+    /// Prepares for writing a new current version,
+    /// and returns the version id of the new version.
+    ///
+    /// This method should be called right before replacing the object file.
+    /// The version id should be saved in the internal info.
+    pub(crate) async fn prepare_new_version(&self, bucket: &str, key: &str) -> Result<Option<String>> {
+        match self.load_versioning(bucket).await? {
+            Versioning::Unversioned => {
+                self.discard_current(bucket, key, false).await?;
+                Ok(None)
+            }
+            Versioning::Enabled => {
+                let mut index = self.load_version_index(bucket, key).await?;
+                self.archive_current(bucket, key, &mut index).await?;
+                self.save_version_index(bucket, key, &index).await?;
+                Ok(Some(new_version_id()))
+            }
+            Versioning::Suspended => {
+                let mut index = self.load_version_index(bucket, key).await?;
+                match self.current_version_id(bucket, key).await?.as_deref() {
+                    Some(NULL_VERSION_ID) => self.discard_current(bucket, key, false).await?,
+                    _ => self.archive_current(bucket, key, &mut index).await?,
+                }
+                self.remove_noncurrent(bucket, key, &mut index, NULL_VERSION_ID).await?;
+                self.save_version_index(bucket, key, &index).await?;
+                Ok(Some(NULL_VERSION_ID.to_owned()))
+            }
+        }
+    }
+
+    /// Deletes an object without a version id, which inserts a delete marker if the bucket is versioned.
+    ///
+    /// Returns the version id of the delete marker.
+    pub(crate) async fn insert_delete_marker(&self, bucket: &str, key: &str, versioning: Versioning) -> Result<String> {
+        let mut index = self.load_version_index(bucket, key).await?;
+        let version_id = if versioning == Versioning::Suspended {
+            match self.current_version_id(bucket, key).await?.as_deref() {
+                Some(NULL_VERSION_ID) => self.discard_current(bucket, key, true).await?,
+                _ => self.archive_current(bucket, key, &mut index).await?,
+            }
+            self.remove_noncurrent(bucket, key, &mut index, NULL_VERSION_ID).await?;
+            NULL_VERSION_ID.to_owned()
+        } else {
+            self.archive_current(bucket, key, &mut index).await?;
+            new_version_id()
+        };
+        index.insert(
+            0,
+            VersionEntry {
+                version_id: version_id.clone(),
+                delete_marker: true,
+                last_modified: millis(SystemTime::now()),
+            },
+        );
+        self.save_version_index(bucket, key, &index).await?;
+        Ok(version_id)
+    }
+
+    /// Permanently deletes a version.
+    ///
+    /// Returns whether the version is a delete marker, or `None` if the version does not exist.
+    pub(crate) async fn delete_version(&self, bucket: &str, key: &str, version_id: &str) -> S3Result<Option<bool>> {
+        check_version_id(version_id)?;
+
+        let mut index = self.load_version_index(bucket, key).await?;
+
+        if self.current_version_id(bucket, key).await?.as_deref() == Some(version_id) {
+            self.discard_current(bucket, key, true).await?;
+            self.promote_newest(bucket, key, &mut index).await?;
+            self.save_version_index(bucket, key, &index).await?;
+            return Ok(Some(false));
+        }
+
+        let Some(entry) = index.iter().find(|e| e.version_id == version_id) else { return Ok(None) };
+        let delete_marker = entry.delete_marker;
+        self.remove_noncurrent(bucket, key, &mut index, version_id).await?;
+        self.promote_newest(bucket, key, &mut index).await?;
+        self.save_version_index(bucket, key, &index).await?;
+        Ok(Some(delete_marker))
+    }
+
+    /// Deletes an object or a version of an object.
+    ///
+    /// Without a version id, a delete marker is inserted if the bucket is versioned.
+    pub(crate) async fn delete_object_version(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<String>,
+    ) -> S3Result<dto::DeletedObject> {
+        let result = self.delete_object_version_files(bucket, key, version_id).await;
+        self.update_key_index(bucket, key).await;
+        result
+    }
+
+    async fn delete_object_version_files(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<String>,
+    ) -> S3Result<dto::DeletedObject> {
+        if let Some(version_id) = version_id {
+            return match self.delete_version(bucket, key, &version_id).await? {
+                Some(delete_marker) => Ok(dto::DeletedObject {
+                    key: Some(key.to_owned()),
+                    delete_marker: delete_marker.then_some(true),
+                    version_id: Some(version_id),
+                    ..Default::default()
+                }),
+                None => Err(s3_error!(NoSuchVersion)),
+            };
+        }
+
+        let versioning = self.load_versioning(bucket).await?;
+        if versioning != Versioning::Unversioned && key.ends_with('/').not() {
+            let version_id = self.insert_delete_marker(bucket, key, versioning).await?;
+            return Ok(dto::DeletedObject {
+                key: Some(key.to_owned()),
+                delete_marker: Some(true),
+                delete_marker_version_id: Some(version_id),
+                ..Default::default()
+            });
+        }
+
+        let path = self.get_object_path(bucket, key)?;
+        if path.exists().not() {
+            return Err(s3_error!(NoSuchKey));
+        }
+        if key.ends_with('/') {
+            let mut dir = try_!(fs::read_dir(&path).await);
+            let is_empty = try_!(dir.next_entry().await).is_none();
+            if is_empty {
+                try_!(fs::remove_dir(&path).await);
+            }
+        } else {
+            self.discard_current(bucket, key, true).await?;
+        }
+        Ok(dto::DeletedObject {
+            key: Some(key.to_owned()),
+            ..Default::default()
+        })
+    }
+
+    /// Finds the files of a version to read.
+    ///
+    /// Returns `NoSuchKey` if the current version is a delete marker or does not exist,
+    /// and `MethodNotAllowed` if the specified version is a delete marker.
+    pub(crate) async fn get_version_files(&self, bucket: &str, key: &str, version_id: Option<&str>) -> S3Result<VersionFiles> {
+        if let Some(version_id) = version_id {
+            check_version_id(version_id)?;
+        }
+
+        let current_version_id = self.current_version_id(bucket, key).await?;
+
+        let is_current = match (version_id, &current_version_id) {
+            (None, Some(_)) => true,
+            (Some(v), Some(c)) => v == c,
+            (_, None) => false,
+        };
+
+        if is_current {
+            let versioning = self.load_versioning(bucket).await?;
+            let version_id = current_version_id.filter(|v| v != NULL_VERSION_ID || versioning != Versioning::Unversioned);
+            return Ok(VersionFiles {
+                data_path: self.get_object_path(bucket, key)?,
+                metadata: self.load_metadata(bucket, key, None).await?,
+                info: self.load_internal_info(bucket, key).await?,
+                version_id,
+            });
+        }
+
+        let index = self.load_version_index(bucket, key).await?;
+
+        let Some(version_id) = version_id else {
+            return match index.first() {
+                Some(entry) if entry.delete_marker => Err(delete_marker_error(S3ErrorCode::NoSuchKey, Some(&entry.version_id))),
+                _ => Err(s3_error!(NoSuchKey)),
+            };
+        };
+
+        let Some(entry) = index.iter().find(|e| e.version_id == version_id) else {
+            return Err(s3_error!(NoSuchVersion));
+        };
+        if entry.delete_marker {
+            return Err(delete_marker_error(S3ErrorCode::MethodNotAllowed, Some(version_id)));
+        }
+
+        let [data_path, metadata_path, info_path] = self.get_noncurrent_paths(bucket, key, version_id)?;
+        Ok(VersionFiles {
+            data_path,
+            metadata: load_json_if_exists(&metadata_path).await?,
+            info: load_json_if_exists(&info_path).await?,
+            version_id: Some(version_id.to_owned()),
+        })
+    }
+}
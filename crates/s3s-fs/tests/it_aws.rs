@@ -1,6 +1,7 @@
 use s3s::auth::SimpleAuth;
+use s3s::crypto::{Checksum as _, Crc32, Md5};
 use s3s::host::SingleDomain;
-use s3s::service::S3ServiceBuilder;
+use s3s::service::{S3ServiceBuilder, SharedS3Service};
 use s3s_fs::FileSystem;
 
 use std::env;
@@ -11,13 +12,18 @@ use aws_credential_types::provider::SharedCredentialsProvider;
 use aws_sdk_s3::Client;
 use aws_sdk_s3::config::Credentials;
 use aws_sdk_s3::config::Region;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
 use aws_sdk_s3::primitives::ByteStream;
 
 use aws_sdk_s3::types::BucketLocationConstraint;
+use aws_sdk_s3::types::BucketVersioningStatus;
+use aws_sdk_s3::types::ChecksumAlgorithm;
 use aws_sdk_s3::types::ChecksumMode;
 use aws_sdk_s3::types::CompletedMultipartUpload;
 use aws_sdk_s3::types::CompletedPart;
 use aws_sdk_s3::types::CreateBucketConfiguration;
+use aws_sdk_s3::types::VersioningConfiguration;
 
 use anyhow::Result;
 use tokio::sync::Mutex;
@@ -61,6 +67,8 @@ fn config() -> &'static SdkConfig {
             let mut b = S3ServiceBuilder::new(fs);
             b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
             b.set_host(SingleDomain::new(DOMAIN_NAME).unwrap());
+            b.add_region(s3s::region::Region::new(REGION).unwrap());
+            b.set_verify_checksums(true);
             b.build()
         };
 
@@ -108,6 +116,17 @@ async fn delete_bucket(c: &Client, bucket: &str) -> Result<()> {
     Ok(())
 }
 
+/// Returns a path-style client which sends requests to `service` directly.
+fn client_for(service: &SharedS3Service, cred: Credentials, region: &str) -> Client {
+    let conf = SdkConfig::builder()
+        .credentials_provider(SharedCredentialsProvider::new(cred))
+        .http_client(s3s_aws::Client::from(service.clone()))
+        .region(Region::new(region.to_owned()))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .build();
+    Client::from_conf(aws_sdk_s3::config::Builder::from(&conf).force_path_style(true).build())
+}
+
 macro_rules! log_and_unwrap {
     ($result:expr) => {
         match $result {
@@ -146,6 +165,206 @@ async fn test_list_buckets() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_location() -> Result<()> {
+    let c = Client::new(config());
+    let bucket = format!("test-bucket-location-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let ans = log_and_unwrap!(c.get_bucket_location().bucket(bucket).send().await);
+        assert_eq!(ans.location_constraint(), Some(&BucketLocationConstraint::from(REGION)));
+    }
+
+    {
+        let ans = log_and_unwrap!(c.head_bucket().bucket(bucket).send().await);
+        assert_eq!(ans.bucket_region(), Some(REGION));
+    }
+
+    {
+        let other = Client::from_conf(
+            aws_sdk_s3::config::Builder::from(config())
+                .region(Region::new("eu-west-1"))
+                .build(),
+        );
+        let result = other.list_buckets().send().await;
+        assert_error_code(result, "AuthorizationHeaderMalformed");
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_location_multi_region() -> Result<()> {
+    let _ = config(); // setup tracing
+
+    let service = {
+        let mut b = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap());
+        let cred = Credentials::for_tests();
+        b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+        b.add_region(s3s::region::Region::new(REGION).unwrap());
+        b.add_region(s3s::region::Region::new("eu-west-1").unwrap());
+        b.set_verify_checksums(true);
+        b.build()
+    };
+    let service = service.into_shared();
+
+    let primary = client_for(&service, Credentials::for_tests(), REGION);
+    let europe = client_for(&service, Credentials::for_tests(), "eu-west-1");
+
+    let bucket = format!("test-location-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    {
+        let cfg = CreateBucketConfiguration::builder()
+            .location_constraint(BucketLocationConstraint::from("eu-west-1"))
+            .build();
+        let result = primary
+            .create_bucket()
+            .create_bucket_configuration(cfg)
+            .bucket(bucket)
+            .send()
+            .await;
+        assert_error_code(result, "IllegalLocationConstraintException");
+    }
+
+    {
+        let cfg = CreateBucketConfiguration::builder()
+            .location_constraint(BucketLocationConstraint::from("eu-west-1"))
+            .build();
+        europe
+            .create_bucket()
+            .create_bucket_configuration(cfg)
+            .bucket(bucket)
+            .send()
+            .await?;
+    }
+
+    // The bucket stays in its own region, whichever region the request is signed for.
+    {
+        let ans = log_and_unwrap!(primary.get_bucket_location().bucket(bucket).send().await);
+        assert_eq!(ans.location_constraint(), Some(&BucketLocationConstraint::from("eu-west-1")));
+    }
+
+    {
+        let ans = log_and_unwrap!(primary.head_bucket().bucket(bucket).send().await);
+        assert_eq!(ans.bucket_region(), Some("eu-west-1"));
+    }
+
+    {
+        let ans = log_and_unwrap!(primary.list_buckets().send().await);
+        let found = ans.buckets().iter().find(|b| b.name() == Some(bucket)).unwrap();
+        assert_eq!(found.bucket_region(), Some("eu-west-1"));
+    }
+
+    delete_bucket(&primary, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_config_permissions() -> Result<()> {
+    use s3s_fs::config::{Config, Users};
+
+    let _ = config(); // setup tracing
+
+    let path = concat!(env!("CARGO_TARGET_TMPDIR"), "/s3s-fs-tests-config.toml");
+    let write_config = |bob_buckets: &str| {
+        let text = format!(
+            r#"
+            region = "{REGION}"
+
+            [[users]]
+            access_key = "alice"
+            secret_key = "alice-secret"
+            buckets = {{ "*" = ["read", "write", "list"] }}
+
+            [[users]]
+            access_key = "bob"
+            secret_key = "bob-secret"
+            buckets = {bob_buckets}
+            "#
+        );
+        fs::write(path, text).unwrap();
+        Config::load(path.as_ref()).unwrap()
+    };
+
+    let users = Users::new(&write_config(r#"{ "test-config-*" = ["read"] }"#).users);
+
+    let service = {
+        let mut b = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap());
+        b.set_auth(users.clone());
+        b.set_access(users.clone());
+        b.set_verify_checksums(true);
+        b.build()
+    };
+    let service = service.into_shared();
+
+    let client = |ak: &str, sk: &str| client_for(&service, Credentials::new(ak, sk, None, None, "test"), REGION);
+    let alice = client("alice", "alice-secret");
+    let bob = client("bob", "bob-secret");
+
+    let bucket = format!("test-config-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "hello.txt";
+
+    create_bucket(&alice, bucket).await?;
+    alice
+        .put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(ByteStream::from_static(b"hello"))
+        .send()
+        .await?;
+
+    {
+        let ans = bob.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"hello");
+    }
+
+    {
+        let result = bob.put_object().bucket(bucket).key(key).send().await;
+        assert_error_code(result, "AccessDenied");
+    }
+
+    {
+        let result = bob.list_objects_v2().bucket(bucket).send().await;
+        assert_error_code(result, "AccessDenied");
+    }
+
+    {
+        let ans = bob.list_buckets().send().await?;
+        let names: Vec<_> = ans.buckets().iter().filter_map(|b| b.name()).collect();
+        assert!(names.contains(&bucket));
+        assert!(names.iter().all(|name| name.starts_with("test-config-")));
+    }
+
+    users.set(&write_config(r#"{ "test-config-*" = ["read", "list"] }"#).users);
+
+    {
+        let ans = bob.list_objects_v2().bucket(bucket).send().await?;
+        assert_eq!(ans.contents().len(), 1);
+    }
+
+    {
+        let result = client("carol", "carol-secret").list_buckets().send().await;
+        assert_error_code(result, "NotSignedUp");
+    }
+
+    delete_object(&alice, bucket, key).await?;
+    delete_bucket(&alice, bucket).await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 #[tracing::instrument]
 async fn test_list_objects_v2() -> Result<()> {
@@ -396,3 +615,655 @@ async fn test_upload_part_copy() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_versioning() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-versioning-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "versioned.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let put = |content: &'static str| {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .send()
+    };
+    let get_body = |version_id: Option<&str>| {
+        let req = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_version_id(version_id.map(ToOwned::to_owned));
+        async move {
+            let ans = req.send().await?;
+            Ok::<_, anyhow::Error>(ans.body.collect().await?.into_bytes())
+        }
+    };
+
+    {
+        let ans = c.get_bucket_versioning().bucket(bucket).send().await?;
+        assert!(ans.status().is_none());
+
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Enabled)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+
+        let ans = c.get_bucket_versioning().bucket(bucket).send().await?;
+        assert_eq!(ans.status(), Some(&BucketVersioningStatus::Enabled));
+    }
+
+    let v1 = put("first").await?.version_id.unwrap();
+    let v2 = put("second").await?.version_id.unwrap();
+    assert_ne!(v1, v2);
+
+    {
+        assert_eq!(get_body(None).await?.as_ref(), b"second");
+        assert_eq!(get_body(Some(&v1)).await?.as_ref(), b"first");
+        assert_eq!(get_body(Some(&v2)).await?.as_ref(), b"second");
+    }
+
+    let marker = {
+        let ans = c.delete_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.delete_marker(), Some(true));
+        let marker = ans.version_id.unwrap();
+
+        assert_error_code(c.get_object().bucket(bucket).key(key).send().await, "NoSuchKey");
+
+        assert_eq!(get_body(Some(&v1)).await?.as_ref(), b"first");
+        marker
+    };
+
+    {
+        let ans = c.list_object_versions().bucket(bucket).send().await?;
+        let versions: Vec<_> = ans
+            .versions()
+            .iter()
+            .map(|v| (v.version_id().unwrap(), v.is_latest()))
+            .collect();
+        assert_eq!(versions, [(v2.as_str(), Some(false)), (v1.as_str(), Some(false))]);
+        let markers: Vec<_> = ans
+            .delete_markers()
+            .iter()
+            .map(|m| (m.version_id().unwrap(), m.is_latest()))
+            .collect();
+        assert_eq!(markers, [(marker.as_str(), Some(true))]);
+
+        let ans = c.list_object_versions().bucket(bucket).max_keys(2).send().await?;
+        assert_eq!(ans.is_truncated(), Some(true));
+        assert_eq!(ans.next_version_id_marker(), Some(v2.as_str()));
+
+        let ans = c
+            .list_object_versions()
+            .bucket(bucket)
+            .key_marker(key)
+            .version_id_marker(&v2)
+            .send()
+            .await?;
+        assert_eq!(ans.is_truncated(), Some(false));
+        assert_eq!(ans.versions().len(), 1);
+        assert_eq!(ans.versions()[0].version_id(), Some(v1.as_str()));
+    }
+
+    {
+        let ans = c.delete_object().bucket(bucket).key(key).version_id(&marker).send().await?;
+        assert_eq!(ans.delete_marker(), Some(true));
+        assert_eq!(get_body(None).await?.as_ref(), b"second");
+
+        c.delete_object().bucket(bucket).key(key).version_id(&v2).send().await?;
+        assert_eq!(get_body(None).await?.as_ref(), b"first");
+    }
+
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Suspended)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+
+        assert_eq!(put("third").await?.version_id(), Some("null"));
+        assert_eq!(put("fourth").await?.version_id(), Some("null"));
+        assert_eq!(get_body(Some("null")).await?.as_ref(), b"fourth");
+        assert_eq!(get_body(Some(&v1)).await?.as_ref(), b"first");
+
+        let ans = c.list_object_versions().bucket(bucket).send().await?;
+        let versions: Vec<_> = ans.versions().iter().filter_map(|v| v.version_id()).collect();
+        assert_eq!(versions, ["null", v1.as_str()]);
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_checksum_verification() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-checksum-verification-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "checked.txt";
+    let content = "hello world\n";
+    let wrong_md5 = base64_simd::STANDARD.encode_to_string(Md5::checksum(b"hello\n"));
+    let wrong_crc32 = base64_simd::STANDARD.encode_to_string(Crc32::checksum(b"hello\n"));
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .content_md5(wrong_md5)
+            .send()
+            .await;
+        assert_error_code(result, "BadDigest");
+
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .checksum_crc32(wrong_crc32)
+            .send()
+            .await;
+        assert_error_code(result, "BadDigest");
+
+        let result = c.head_object().bucket(bucket).key(key).send().await;
+        assert!(result.is_err());
+    }
+
+    {
+        let crc32 = base64_simd::STANDARD.encode_to_string(Crc32::checksum(content.as_bytes()));
+        let md5 = hex_simd::encode_to_string(Md5::checksum(content.as_bytes()), hex_simd::AsciiCase::Lower);
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .checksum_crc32(crc32.as_str())
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_crc32(), Some(crc32.as_str()));
+        assert_eq!(ans.e_tag(), Some(format!("\"{md5}\"").as_str()));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_upload_part_checksum() -> Result<()> {
+    let _ = config(); // setup tracing
+
+    let service = {
+        let mut b = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap());
+        let cred = Credentials::for_tests();
+        b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+        b.add_region(s3s::region::Region::new(REGION).unwrap());
+        b.set_verify_checksums(true);
+        b.build()
+    };
+    let c = client_for(&service.into_shared(), Credentials::for_tests(), REGION);
+
+    let bucket = format!("test-upload-part-checksum-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "part.txt";
+    let content = "hello world\n";
+    let crc32 = base64_simd::STANDARD.encode_to_string(Crc32::checksum(content.as_bytes()));
+    let wrong_crc32 = base64_simd::STANDARD.encode_to_string(Crc32::checksum(b"hello\n"));
+
+    create_bucket(&c, bucket).await?;
+
+    let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+    let upload_id = ans.upload_id().unwrap();
+
+    {
+        let result = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .checksum_crc32(wrong_crc32)
+            .send()
+            .await;
+        assert_error_code(result, "BadDigest");
+    }
+
+    // A streaming body is sent with the checksum in the trailing headers.
+    {
+        let path = concat!(env!("CARGO_TARGET_TMPDIR"), "/s3s-fs-tests-upload-part-checksum.txt");
+        fs::write(path, content)?;
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_path(path).await?)
+            .checksum_algorithm(ChecksumAlgorithm::Crc32)
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_crc32(), Some(crc32.as_str()));
+    }
+
+    c.abort_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .send()
+        .await?;
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+/// Asserts that a request failed with the given S3 error code.
+#[track_caller]
+fn assert_error_code<T, E: ProvideErrorMetadata>(result: Result<T, SdkError<E, HttpResponse>>, code: &str) {
+    let Err(err) = result else { panic!("expected an error") };
+    assert_eq!(err.as_service_error().and_then(ProvideErrorMetadata::code), Some(code));
+}
+
+/// Returns the HTTP status of a failed request.
+fn error_status<T, E>(result: Result<T, SdkError<E, HttpResponse>>) -> u16 {
+    let Err(err) = result else { panic!("expected an error") };
+    err.raw_response().unwrap().status().as_u16()
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_conditional_requests() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-conditional-requests-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "conditional.txt";
+    let copy_key = "conditional-copy.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let e_tag = {
+        let put = |content: &'static str| {
+            c.put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from_static(content.as_bytes()))
+                .if_none_match("*")
+        };
+
+        let ans = put("hello\n").send().await?;
+        let e_tag = ans.e_tag().unwrap().to_owned();
+
+        assert_eq!(error_status(put("world\n").send().await), 412);
+
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"world\n"))
+            .if_match("\"00000000000000000000000000000000\"")
+            .send()
+            .await;
+        assert_eq!(error_status(result), 412);
+
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"world\n"))
+            .if_match(&e_tag)
+            .send()
+            .await?;
+        ans.e_tag().unwrap().to_owned()
+    };
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).if_match(&e_tag).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"world\n");
+
+        let result = c.get_object().bucket(bucket).key(key).if_none_match(&e_tag).send().await;
+        assert_eq!(error_status(result), 304);
+
+        let result = c.get_object().bucket(bucket).key(key).if_match("\"other\"").send().await;
+        assert_eq!(error_status(result), 412);
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+        let last_modified = ans.last_modified().unwrap().to_owned();
+
+        let result = c
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .if_modified_since(last_modified)
+            .send()
+            .await;
+        assert_eq!(error_status(result), 304);
+    }
+
+    {
+        let copy = || {
+            c.copy_object()
+                .bucket(bucket)
+                .key(copy_key)
+                .copy_source(format!("{bucket}/{key}"))
+        };
+
+        let result = copy().copy_source_if_none_match(&e_tag).send().await;
+        assert_eq!(error_status(result), 412);
+
+        copy().copy_source_if_match(&e_tag).send().await?;
+
+        // The destination is not overwritten if it exists.
+        let result = copy()
+            .customize()
+            .mutate_request(|req| {
+                req.headers_mut().insert("if-none-match", "*");
+            })
+            .send()
+            .await;
+        assert_eq!(error_status(result), 412);
+    }
+
+    {
+        let result = c.delete_object().bucket(bucket).key(key).if_match("\"other\"").send().await;
+        assert_eq!(error_status(result), 412);
+
+        c.delete_object().bucket(bucket).key(key).if_match(&e_tag).send().await?;
+        delete_object(&c, bucket, copy_key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[tracing::instrument]
+async fn test_concurrent_conditional_puts() -> Result<()> {
+    let c = Client::new(config());
+    let bucket = format!("test-concurrent-puts-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "object";
+
+    create_bucket(&c, bucket).await?;
+
+    // Only one of the concurrent creations succeeds.
+    let tasks: Vec<_> = (0..16)
+        .map(|i| {
+            let put = c
+                .put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from(vec![b'x'; 4096 * (i + 1)]))
+                .if_none_match("*");
+            tokio::spawn(put.send())
+        })
+        .collect();
+
+    let mut succeeded = 0;
+    for task in tasks {
+        match task.await? {
+            Ok(_) => succeeded += 1,
+            result => assert_eq!(error_status(result), 412),
+        }
+    }
+    assert_eq!(succeeded, 1);
+
+    delete_object(&c, bucket, key).await?;
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_objects_pagination() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-list-pagination-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let keys = ["a-b", "a/1", "a/2", "b", "c/d/e", "c/f", "space key+1"];
+
+    create_bucket(&c, bucket).await?;
+    for key in keys {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"x"))
+            .send()
+            .await?;
+    }
+
+    {
+        let mut listed = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let ans = c
+                .list_objects_v2()
+                .bucket(bucket)
+                .max_keys(2)
+                .set_continuation_token(token)
+                .send()
+                .await?;
+            assert!(ans.key_count().unwrap() <= 2);
+            listed.extend(ans.contents().iter().filter_map(|o| o.key()).map(str::to_owned));
+            token = ans.next_continuation_token().map(str::to_owned);
+            if ans.is_truncated() != Some(true) {
+                assert!(token.is_none());
+                break;
+            }
+        }
+        assert_eq!(listed, keys);
+    }
+
+    {
+        let mut items = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let ans = c
+                .list_objects_v2()
+                .bucket(bucket)
+                .delimiter("/")
+                .max_keys(1)
+                .set_continuation_token(token)
+                .send()
+                .await?;
+            items.extend(ans.contents().iter().filter_map(|o| o.key()).map(str::to_owned));
+            items.extend(ans.common_prefixes().iter().filter_map(|p| p.prefix()).map(str::to_owned));
+            token = ans.next_continuation_token().map(str::to_owned);
+            if token.is_none() {
+                break;
+            }
+        }
+        assert_eq!(items, ["a-b", "a/", "b", "c/", "space key+1"]);
+
+        let ans = c.list_objects_v2().bucket(bucket).prefix("c/").delimiter("/").send().await?;
+        let contents: Vec<_> = ans.contents().iter().filter_map(|o| o.key()).collect();
+        let prefixes: Vec<_> = ans.common_prefixes().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(contents, ["c/f"]);
+        assert_eq!(prefixes, ["c/d/"]);
+        assert_eq!(ans.key_count(), Some(2));
+    }
+
+    {
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix("space")
+            .encoding_type(aws_sdk_s3::types::EncodingType::Url)
+            .fetch_owner(true)
+            .send()
+            .await?;
+        let object = &ans.contents()[0];
+        assert_eq!(object.key(), Some("space%20key%2B1"));
+        assert!(object.owner().is_some());
+
+        let ans = c.list_objects_v2().bucket(bucket).start_after("b").send().await?;
+        let contents: Vec<_> = ans.contents().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(contents, ["c/d/e", "c/f", "space key+1"]);
+        assert!(ans.contents().iter().all(|o| o.owner().is_none()));
+    }
+
+    {
+        let mut items = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let ans = c
+                .list_objects()
+                .bucket(bucket)
+                .delimiter("/")
+                .max_keys(2)
+                .set_marker(marker)
+                .send()
+                .await?;
+            items.extend(ans.contents().iter().filter_map(|o| o.key()).map(str::to_owned));
+            items.extend(ans.common_prefixes().iter().filter_map(|p| p.prefix()).map(str::to_owned));
+            if ans.is_truncated() != Some(true) {
+                break;
+            }
+            marker = ans.next_marker().map(str::to_owned);
+        }
+        items.sort();
+        assert_eq!(items, ["a-b", "a/", "b", "c/", "space key+1"]);
+
+        let result = c.list_objects().bucket(bucket).max_keys(-1).send().await;
+        assert!(result.is_err());
+    }
+
+    {
+        for key in keys {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_key_index() -> Result<()> {
+    let _ = config(); // setup tracing
+
+    let root = concat!(env!("CARGO_TARGET_TMPDIR"), "/s3s-fs-tests-key-index");
+    fs::create_dir_all(root)?;
+
+    let client = |fs: FileSystem| {
+        let cred = Credentials::for_tests();
+        let service = {
+            let mut b = S3ServiceBuilder::new(fs);
+            b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+            b.set_verify_checksums(true);
+            b.build()
+        };
+        client_for(&service.into_shared(), cred, REGION)
+    };
+    let list = |c: &Client, bucket: &str, delimiter: Option<&str>| {
+        let req = c.list_objects_v2().bucket(bucket).set_delimiter(delimiter.map(str::to_owned));
+        async move {
+            let mut items = Vec::new();
+            let mut token: Option<String> = None;
+            loop {
+                let ans = req.clone().max_keys(2).set_continuation_token(token).send().await?;
+                items.extend(ans.contents().iter().filter_map(|o| o.key()).map(str::to_owned));
+                items.extend(ans.common_prefixes().iter().filter_map(|p| p.prefix()).map(str::to_owned));
+                token = ans.next_continuation_token().map(str::to_owned);
+                if token.is_none() {
+                    break;
+                }
+            }
+            anyhow::Ok(items)
+        }
+    };
+
+    let bucket = format!("test-key-index-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let keys = ["a-b", "a/1", "a/2", "b", "c/d/e", "c/f"];
+
+    {
+        let mut fs = FileSystem::new(root).unwrap();
+        fs.enable_key_index().unwrap();
+        let c = client(fs);
+
+        create_bucket(&c, bucket).await?;
+        for key in keys {
+            c.put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from_static(b"x"))
+                .send()
+                .await?;
+        }
+
+        assert_eq!(list(&c, bucket, None).await?, keys);
+        assert_eq!(list(&c, bucket, Some("/")).await?, ["a-b", "a/", "b", "c/"]);
+
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix("c/")
+            .start_after("c/d")
+            .send()
+            .await?;
+        let contents: Vec<_> = ans.contents().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(contents, ["c/d/e", "c/f"]);
+
+        delete_object(&c, bucket, "b").await?;
+        assert_eq!(list(&c, bucket, None).await?, ["a-b", "a/1", "a/2", "c/d/e", "c/f"]);
+    }
+
+    // A file written outside the server is listed after rebuilding the index.
+    fs::write(format!("{root}/{bucket}/b"), "x")?;
+
+    {
+        let mut fs = FileSystem::new(root).unwrap();
+        fs.enable_key_index().unwrap();
+        let c = client(fs);
+        assert_eq!(list(&c, bucket, None).await?, ["a-b", "a/1", "a/2", "c/d/e", "c/f"]);
+    }
+
+    {
+        let mut fs = FileSystem::new(root).unwrap();
+        fs.enable_key_index().unwrap();
+        fs.rebuild_key_indexes().await.unwrap();
+        let c = client(fs);
+        assert_eq!(list(&c, bucket, None).await?, keys);
+
+        for key in keys {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
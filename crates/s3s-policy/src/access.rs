@@ -0,0 +1,501 @@
+//! An [`S3Access`] implementation backed by bucket policies.
+
+use crate::condition::ConditionContext;
+use crate::eval::{Decision, EvalRequest, PolicyEvaluator};
+use crate::model::Policy;
+use crate::pattern::PatternError;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use hyper::HeaderMap;
+use hyper::Uri;
+use s3s::access::{S3Access, S3AccessContext};
+use s3s::dto::{CopyObjectInput, CopySource, DeleteObjectsInput, UploadPartCopyInput};
+use s3s::path::S3Path;
+use s3s::s3_error;
+use s3s::{S3Request, S3Result};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// An access control provider which evaluates policies for every request.
+///
+/// A request is allowed only if the global policy or the policy of the requested bucket allows it,
+/// and none of them denies it explicitly.
+///
+/// The source object of a copy request is checked with `s3:GetObject` (or `s3:GetObjectVersion`),
+/// and every key of a `DeleteObjects` request is checked with `s3:DeleteObject`.
+///
+/// The `aws:SourceIp` and `aws:SecureTransport` condition keys are taken from the connection of the request,
+/// which is provided by the server. See [`s3s::connection`].
+#[derive(Default)]
+pub struct PolicyAccess {
+    global: Option<PolicyEvaluator>,
+    buckets: HashMap<String, PolicyEvaluator>,
+    tags: Option<Box<dyn ObjectTagProvider>>,
+}
+
+impl PolicyAccess {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the policy which applies to all buckets.
+    ///
+    /// # Errors
+    /// Returns an error if the policy contains invalid patterns.
+    pub fn set_global_policy(&mut self, policy: &Policy) -> Result<(), PatternError> {
+        self.global = Some(PolicyEvaluator::new(policy)?);
+        Ok(())
+    }
+
+    /// Sets the policy of a bucket.
+    ///
+    /// # Errors
+    /// Returns an error if the policy contains invalid patterns.
+    pub fn set_bucket_policy(&mut self, bucket: impl Into<String>, policy: &Policy) -> Result<(), PatternError> {
+        self.buckets.insert(bucket.into(), PolicyEvaluator::new(policy)?);
+        Ok(())
+    }
+
+    /// Removes the policy of a bucket.
+    pub fn remove_bucket_policy(&mut self, bucket: &str) -> bool {
+        self.buckets.remove(bucket).is_some()
+    }
+
+    /// Sets the provider of existing object tags.
+    ///
+    /// If it is set, `s3:ExistingObjectTag/<key>` condition keys are available for object requests.
+    pub fn set_object_tag_provider(&mut self, provider: impl ObjectTagProvider) {
+        self.tags = Some(Box::new(provider));
+    }
+
+    /// Collects the condition keys of the request, including the tags of `object` if it is given.
+    async fn condition_context(
+        &self,
+        uri: &Uri,
+        headers: &HeaderMap,
+        principal: Option<&str>,
+        transport: Transport,
+        object: Option<(&str, &str)>,
+    ) -> S3Result<ConditionContext> {
+        let mut context = request_context(uri, headers, principal, transport);
+
+        if let (Some(tags), Some((bucket, key))) = (&self.tags, object) {
+            for (k, v) in tags.get_object_tags(bucket, key).await? {
+                context.insert(&format!("s3:ExistingObjectTag/{k}"), v);
+            }
+        }
+
+        Ok(context)
+    }
+
+    /// Evaluates the global policy and the policy of `bucket`.
+    fn authorize(&self, req: &EvalRequest<'_>, bucket: Option<&str>) -> S3Result<()> {
+        let bucket_policy = bucket.and_then(|b| self.buckets.get(b));
+
+        let mut decision = Decision::ImplicitDeny;
+        for policy in self.global.iter().chain(bucket_policy) {
+            let ans = policy.evaluate(req).map_err(|e| s3_error!(e, AccessDenied))?;
+            decision = decision.combine(ans);
+        }
+
+        match decision {
+            Decision::Allow => Ok(()),
+            Decision::ExplicitDeny | Decision::ImplicitDeny => Err(s3_error!(AccessDenied)),
+        }
+    }
+
+    /// Checks `action` on an object other than the one in the request path.
+    async fn check_object<T>(&self, req: &S3Request<T>, action: &str, bucket: &str, key: &str) -> S3Result<()> {
+        let transport = Transport {
+            source_ip: req.client_ip,
+            secure: req.connection.as_ref().is_some_and(|c| c.tls.is_some()),
+        };
+        let principal = req.credentials.as_ref().map(|c| c.access_key.as_str());
+        let context = self
+            .condition_context(&req.uri, &req.headers, principal, transport, Some((bucket, key)))
+            .await?;
+
+        let resource = s3_resource_arn(&S3Path::object(bucket, key));
+        let eval = EvalRequest {
+            principal,
+            action,
+            resource: &resource,
+            context: &context,
+        };
+        self.authorize(&eval, Some(bucket))
+    }
+
+    /// Checks the read access to the source object of a copy request.
+    async fn check_copy_source<T>(&self, req: &S3Request<T>, source: &CopySource) -> S3Result<()> {
+        let CopySource::Bucket { bucket, key, version_id } = source else {
+            return Err(s3_error!(NotImplemented, "Copying from access points is not supported"));
+        };
+        let action = if version_id.is_some() {
+            "s3:GetObjectVersion"
+        } else {
+            "s3:GetObject"
+        };
+        self.check_object(req, action, bucket, key).await
+    }
+}
+
+/// A provider of the tags of existing objects.
+#[async_trait::async_trait]
+pub trait ObjectTagProvider: Send + Sync + 'static {
+    /// Returns the tags of an object, or an empty list if the object does not exist.
+    async fn get_object_tags(&self, bucket: &str, key: &str) -> S3Result<Vec<(String, String)>>;
+}
+
+#[async_trait::async_trait]
+impl S3Access for PolicyAccess {
+    async fn check(&self, cx: &mut S3AccessContext<'_>) -> S3Result<()> {
+        // The keys of a DeleteObjects request are checked one by one in `delete_objects`.
+        if cx.s3_op().name() == "DeleteObjects" {
+            return Ok(());
+        }
+
+        let action = s3_action(cx.s3_op().name());
+        let resource = s3_resource_arn(cx.s3_path());
+
+        let transport = Transport {
+            source_ip: cx.client_ip(),
+            secure: cx.tls_info().is_some(),
+        };
+        let principal = cx.credentials().map(|c| c.access_key.as_str());
+        let object = cx.s3_path().as_object();
+        let context = self
+            .condition_context(cx.uri(), cx.headers(), principal, transport, object)
+            .await?;
+
+        let req = EvalRequest {
+            principal,
+            action: &action,
+            resource: &resource,
+            context: &context,
+        };
+        self.authorize(&req, cx.s3_path().get_bucket_name())
+    }
+
+    async fn copy_object(&self, req: &mut S3Request<CopyObjectInput>) -> S3Result<()> {
+        self.check_copy_source(req, &req.input.copy_source).await
+    }
+
+    async fn upload_part_copy(&self, req: &mut S3Request<UploadPartCopyInput>) -> S3Result<()> {
+        self.check_copy_source(req, &req.input.copy_source).await
+    }
+
+    async fn delete_objects(&self, req: &mut S3Request<DeleteObjectsInput>) -> S3Result<()> {
+        let bucket = req.input.bucket.as_str();
+        for object in &req.input.delete.objects {
+            self.check_object(req, "s3:DeleteObject", bucket, &object.key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The connection properties of a request
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Transport {
+    /// The IP address of the client
+    pub source_ip: Option<IpAddr>,
+    /// Whether the request is sent over TLS
+    pub secure: bool,
+}
+
+/// Collects the condition keys which can be derived from the request itself.
+///
+/// <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_condition-keys.html>
+/// <https://docs.aws.amazon.com/service-authorization/latest/reference/list_amazons3.html#amazons3-policy-keys>
+#[must_use]
+pub fn request_context(uri: &Uri, headers: &HeaderMap, principal: Option<&str>, transport: Transport) -> ConditionContext {
+    let mut cx = ConditionContext::new();
+
+    let now = OffsetDateTime::now_utc();
+    if let Ok(s) = now.format(&Rfc3339) {
+        cx.insert("aws:CurrentTime", s);
+    }
+    cx.insert("aws:EpochTime", now.unix_timestamp().to_string());
+
+    if let Some(ip) = transport.source_ip {
+        cx.insert("aws:SourceIp", ip.to_string());
+    }
+
+    cx.insert("aws:SecureTransport", if transport.secure { "true" } else { "false" });
+
+    match principal {
+        Some(p) => {
+            cx.insert("aws:PrincipalType", "User");
+            cx.insert("aws:username", p);
+            cx.insert("aws:userid", p);
+        }
+        None => cx.insert("aws:PrincipalType", "Anonymous"),
+    }
+
+    let header_keys = [
+        ("aws:Referer", "referer"),
+        ("aws:UserAgent", "user-agent"),
+        ("s3:x-amz-acl", "x-amz-acl"),
+        ("s3:x-amz-content-sha256", "x-amz-content-sha256"),
+        ("s3:x-amz-copy-source", "x-amz-copy-source"),
+        ("s3:x-amz-metadata-directive", "x-amz-metadata-directive"),
+        ("s3:x-amz-server-side-encryption", "x-amz-server-side-encryption"),
+        (
+            "s3:x-amz-server-side-encryption-aws-kms-key-id",
+            "x-amz-server-side-encryption-aws-kms-key-id",
+        ),
+        ("s3:x-amz-storage-class", "x-amz-storage-class"),
+        ("s3:x-amz-website-redirect-location", "x-amz-website-redirect-location"),
+        ("s3:object-lock-mode", "x-amz-object-lock-mode"),
+        ("s3:object-lock-retain-until-date", "x-amz-object-lock-retain-until-date"),
+        ("s3:object-lock-legal-hold", "x-amz-object-lock-legal-hold"),
+    ];
+    for (key, name) in header_keys {
+        if let Some(val) = headers.get(name).and_then(|v| v.to_str().ok()) {
+            cx.insert(key, val);
+        }
+    }
+
+    let tags = headers.get("x-amz-tagging").and_then(|v| parse_query(v.to_str().ok()?));
+    if let Some(tags) = tags {
+        cx.insert_multi("s3:RequestObjectTagKeys", tags.iter().map(|(k, _)| k.clone()));
+        for (k, v) in tags {
+            cx.insert(&format!("s3:RequestObjectTag/{k}"), v);
+        }
+    }
+
+    let query_keys = [
+        ("s3:prefix", "prefix"),
+        ("s3:delimiter", "delimiter"),
+        ("s3:max-keys", "max-keys"),
+        ("s3:versionid", "versionId"),
+    ];
+    if let Some(qs) = uri.query().and_then(parse_query) {
+        for (key, name) in query_keys {
+            if let Some((_, val)) = qs.iter().find(|(n, _)| n == name) {
+                cx.insert(key, val.as_str());
+            }
+        }
+    }
+
+    cx
+}
+
+fn parse_query(s: &str) -> Option<Vec<(String, String)>> {
+    serde_urlencoded::from_str(s).ok()
+}
+
+/// Returns the policy action name of an S3 operation.
+///
+/// <https://docs.aws.amazon.com/AmazonS3/latest/userguide/using-with-s3-policy-actions.html>
+#[must_use]
+pub fn s3_action(op: &str) -> Cow<'static, str> {
+    let action = match op {
+        "HeadBucket" | "ListObjects" | "ListObjectsV2" => "s3:ListBucket",
+        "ListBuckets" => "s3:ListAllMyBuckets",
+        "ListObjectVersions" => "s3:ListBucketVersions",
+        "ListMultipartUploads" => "s3:ListBucketMultipartUploads",
+        "ListParts" => "s3:ListMultipartUploadParts",
+        "HeadObject" => "s3:GetObject",
+        "CopyObject" | "CreateMultipartUpload" | "UploadPart" | "UploadPartCopy" | "CompleteMultipartUpload" => "s3:PutObject",
+        "DeleteObjects" => "s3:DeleteObject",
+        _ => return format!("s3:{op}").into(),
+    };
+    action.into()
+}
+
+/// Returns the resource ARN of an S3 path.
+///
+/// The root path is represented by `arn:aws:s3:::*`.
+#[must_use]
+pub fn s3_resource_arn(path: &S3Path) -> String {
+    match path {
+        S3Path::Root => "arn:aws:s3:::*".to_owned(),
+        S3Path::Bucket { bucket } => format!("arn:aws:s3:::{bucket}"),
+        S3Path::Object { bucket, key } => format!("arn:aws:s3:::{bucket}/{key}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_names() {
+        let cases = [
+            ("GetObject", "s3:GetObject"),
+            ("HeadObject", "s3:GetObject"),
+            ("ListObjectsV2", "s3:ListBucket"),
+            ("UploadPart", "s3:PutObject"),
+            ("GetBucketPolicy", "s3:GetBucketPolicy"),
+        ];
+        for (op, expected) in cases {
+            assert_eq!(s3_action(op), expected);
+        }
+    }
+
+    #[test]
+    fn request_keys() {
+        let uri: Uri = "/bucket?list-type=2&prefix=home%2Falice%2F&max-keys=10".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-acl", "public-read".parse().unwrap());
+        headers.insert("x-amz-tagging", "env=prod&team=s3".parse().unwrap());
+
+        let transport = Transport {
+            source_ip: Some("203.0.113.7".parse().unwrap()),
+            secure: true,
+        };
+        let cx = request_context(&uri, &headers, Some("AKID"), transport);
+
+        let get = |key| cx.get(key).map(|vs| vs.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(get("s3:prefix"), Some(vec!["home/alice/"]));
+        assert_eq!(get("s3:max-keys"), Some(vec!["10"]));
+        assert_eq!(get("s3:delimiter"), None);
+        assert_eq!(get("s3:x-amz-acl"), Some(vec!["public-read"]));
+        assert_eq!(get("s3:RequestObjectTagKeys"), Some(vec!["env", "team"]));
+        assert_eq!(get("s3:RequestObjectTag/env"), Some(vec!["prod"]));
+        assert_eq!(get("aws:SourceIp"), Some(vec!["203.0.113.7"]));
+        assert_eq!(get("aws:SecureTransport"), Some(vec!["true"]));
+        assert_eq!(get("aws:username"), Some(vec!["AKID"]));
+        assert!(get("aws:CurrentTime").is_some());
+    }
+
+    #[tokio::test]
+    async fn connection_keys() {
+        use s3s::S3;
+        use s3s::auth::SimpleAuth;
+        use s3s::connection::ConnectionInfo;
+        use s3s::service::S3ServiceBuilder;
+        use s3s::tls::TlsInfo;
+
+        struct Dummy;
+        impl S3 for Dummy {}
+
+        let policy: Policy = serde_json::from_str(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Allow",
+                    "Action": "s3:ListAllMyBuckets",
+                    "Resource": "*",
+                    "Condition": {
+                        "IpAddress": {"aws:SourceIp": "203.0.113.0/24"},
+                        "Bool": {"aws:SecureTransport": "true"}
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut access = PolicyAccess::new();
+        access.set_global_policy(&policy).unwrap();
+
+        let mut builder = S3ServiceBuilder::new(Dummy);
+        builder.set_auth(SimpleAuth::from_single("AKID", "SECRET"));
+        builder.set_access(access);
+        let service = builder.build();
+
+        let call = |peer: &str, tls: bool| {
+            let connection = ConnectionInfo {
+                peer_addr: Some(peer.parse().unwrap()),
+                local_addr: None,
+                tls: tls.then(TlsInfo::default),
+            };
+            let mut req = hyper::Request::new(s3s::Body::empty());
+            req.extensions_mut().insert(connection);
+            service.call(req)
+        };
+
+        // allowed by the policy, then rejected by the dummy implementation
+        let res = call("203.0.113.7:4711", true).await.unwrap();
+        assert_eq!(res.status(), hyper::StatusCode::NOT_IMPLEMENTED);
+
+        for (peer, tls) in [("198.51.100.1:4711", true), ("203.0.113.7:4711", false)] {
+            let res = call(peer, tls).await.unwrap();
+            assert_eq!(res.status(), hyper::StatusCode::FORBIDDEN, "{peer} {tls}");
+        }
+    }
+
+    fn dummy_service(policy: &str) -> s3s::service::S3Service {
+        use s3s::S3;
+        use s3s::service::S3ServiceBuilder;
+
+        struct Dummy;
+        impl S3 for Dummy {}
+
+        let policy: Policy = serde_json::from_str(policy).unwrap();
+        let mut access = PolicyAccess::new();
+        access.set_global_policy(&policy).unwrap();
+
+        let mut builder = S3ServiceBuilder::new(Dummy);
+        builder.set_access(access);
+        builder.build()
+    }
+
+    #[tokio::test]
+    async fn copy_source() {
+        let service = dummy_service(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {"Effect": "Allow", "Action": "s3:PutObject", "Resource": "arn:aws:s3:::dst/*"},
+                    {"Effect": "Allow", "Action": "s3:GetObject", "Resource": "arn:aws:s3:::src/public/*"}
+                ]
+            }"#,
+        );
+
+        let call = |source: &str| {
+            let mut req = hyper::Request::new(s3s::Body::empty());
+            *req.method_mut() = hyper::Method::PUT;
+            *req.uri_mut() = "/dst/copy".parse().unwrap();
+            req.headers_mut().insert("x-amz-copy-source", source.parse().unwrap());
+            service.call(req)
+        };
+
+        // allowed by the policy, then rejected by the dummy implementation
+        let res = call("src/public/a").await.unwrap();
+        assert_eq!(res.status(), hyper::StatusCode::NOT_IMPLEMENTED);
+
+        for source in ["src/secret/a", "src/public/a?versionId=1"] {
+            let res = call(source).await.unwrap();
+            assert_eq!(res.status(), hyper::StatusCode::FORBIDDEN, "{source}");
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_objects_keys() {
+        let service = dummy_service(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {"Effect": "Allow", "Action": "s3:DeleteObject", "Resource": "arn:aws:s3:::bucket/*"},
+                    {"Effect": "Deny", "Action": "s3:DeleteObject", "Resource": "arn:aws:s3:::bucket/secret/*"}
+                ]
+            }"#,
+        );
+
+        let call = |keys: &[&str]| {
+            let objects = keys.iter().map(|k| format!("<Object><Key>{k}</Key></Object>"));
+            let body = format!("<Delete>{}</Delete>", objects.collect::<Vec<_>>().concat());
+            let mut req = hyper::Request::new(s3s::Body::from(body));
+            *req.method_mut() = hyper::Method::POST;
+            *req.uri_mut() = "/bucket?delete".parse().unwrap();
+            service.call(req)
+        };
+
+        let res = call(&["a", "c/d"]).await.unwrap();
+        assert_eq!(res.status(), hyper::StatusCode::NOT_IMPLEMENTED);
+
+        let res = call(&["a", "secret/b"]).await.unwrap();
+        assert_eq!(res.status(), hyper::StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn resource_arns() {
+        assert_eq!(s3_resource_arn(&S3Path::root()), "arn:aws:s3:::*");
+        assert_eq!(s3_resource_arn(&S3Path::bucket("b")), "arn:aws:s3:::b");
+        assert_eq!(s3_resource_arn(&S3Path::object("b", "a/b")), "arn:aws:s3:::b/a/b");
+    }
+}
@@ -0,0 +1,541 @@
+//! <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_elements_condition.html>
+
+use crate::eval::EvalError;
+use crate::model::{ConditionKeyValues, ConditionRule};
+use crate::pattern::{PatternSet, Variables};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// The values of condition keys of a request.
+///
+/// Condition key names are case-insensitive.
+#[derive(Debug, Default, Clone)]
+pub struct ConditionContext {
+    values: HashMap<String, Vec<String>>,
+}
+
+impl ConditionContext {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a single-valued condition key.
+    pub fn insert(&mut self, key: &str, value: impl Into<String>) {
+        self.values.insert(key.to_ascii_lowercase(), vec![value.into()]);
+    }
+
+    /// Sets a multivalued condition key.
+    pub fn insert_multi(&mut self, key: &str, values: impl IntoIterator<Item = String>) {
+        self.values.insert(key.to_ascii_lowercase(), values.into_iter().collect());
+    }
+
+    /// Returns the values of a condition key.
+    ///
+    /// `None` means the key is not present in the request.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&[String]> {
+        self.values.get(&key.to_ascii_lowercase()).map(Vec::as_slice)
+    }
+}
+
+/// Single-valued condition keys can be used as policy variables.
+impl Variables for ConditionContext {
+    fn get(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            [value] => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Checks whether the request satisfies all conditions of a statement.
+///
+/// Conditions with different operators or different keys are combined with a logical AND.
+/// Multiple values of a single key are combined with a logical OR.
+///
+/// # Errors
+/// Returns an error if the rule contains an unsupported condition operator or an invalid value.
+pub fn is_match(rule: &ConditionRule, cx: &ConditionContext) -> Result<bool, EvalError> {
+    for (op, kvs) in &rule.0 {
+        let op = Operator::parse(op).ok_or_else(|| EvalError::UnsupportedConditionOperator(op.clone()))?;
+        if !op.is_match(kvs, cx)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_condition-single-vs-multi-valued-context-keys.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetQualifier {
+    None,
+    ForAllValues,
+    ForAnyValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base {
+    StringEquals,
+    StringEqualsIgnoreCase,
+    StringLike,
+    NumericEquals,
+    NumericLessThan,
+    NumericLessThanEquals,
+    NumericGreaterThan,
+    NumericGreaterThanEquals,
+    DateEquals,
+    DateLessThan,
+    DateLessThanEquals,
+    DateGreaterThan,
+    DateGreaterThanEquals,
+    Bool,
+    BinaryEquals,
+    IpAddress,
+    ArnLike,
+    Null,
+}
+
+/// <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_elements_condition_operators.html>
+#[derive(Debug, Clone, Copy)]
+struct Operator {
+    qualifier: SetQualifier,
+    base: Base,
+    negated: bool,
+    if_exists: bool,
+}
+
+impl Operator {
+    fn parse(s: &str) -> Option<Self> {
+        let (qualifier, s) = if let Some(s) = s.strip_prefix("ForAllValues:") {
+            (SetQualifier::ForAllValues, s)
+        } else if let Some(s) = s.strip_prefix("ForAnyValue:") {
+            (SetQualifier::ForAnyValue, s)
+        } else {
+            (SetQualifier::None, s)
+        };
+
+        let (s, if_exists) = match s.strip_suffix("IfExists") {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+
+        let (base, negated) = match s {
+            "StringEquals" => (Base::StringEquals, false),
+            "StringNotEquals" => (Base::StringEquals, true),
+            "StringEqualsIgnoreCase" => (Base::StringEqualsIgnoreCase, false),
+            "StringNotEqualsIgnoreCase" => (Base::StringEqualsIgnoreCase, true),
+            "StringLike" => (Base::StringLike, false),
+            "StringNotLike" => (Base::StringLike, true),
+            "NumericEquals" => (Base::NumericEquals, false),
+            "NumericNotEquals" => (Base::NumericEquals, true),
+            "NumericLessThan" => (Base::NumericLessThan, false),
+            "NumericLessThanEquals" => (Base::NumericLessThanEquals, false),
+            "NumericGreaterThan" => (Base::NumericGreaterThan, false),
+            "NumericGreaterThanEquals" => (Base::NumericGreaterThanEquals, false),
+            "DateEquals" => (Base::DateEquals, false),
+            "DateNotEquals" => (Base::DateEquals, true),
+            "DateLessThan" => (Base::DateLessThan, false),
+            "DateLessThanEquals" => (Base::DateLessThanEquals, false),
+            "DateGreaterThan" => (Base::DateGreaterThan, false),
+            "DateGreaterThanEquals" => (Base::DateGreaterThanEquals, false),
+            "Bool" => (Base::Bool, false),
+            "BinaryEquals" => (Base::BinaryEquals, false),
+            "IpAddress" => (Base::IpAddress, false),
+            "NotIpAddress" => (Base::IpAddress, true),
+            "ArnEquals" | "ArnLike" => (Base::ArnLike, false),
+            "ArnNotEquals" | "ArnNotLike" => (Base::ArnLike, true),
+            "Null" => (Base::Null, false),
+            _ => return None,
+        };
+
+        if base == Base::Null && (if_exists || qualifier != SetQualifier::None) {
+            return None;
+        }
+
+        Some(Self {
+            qualifier,
+            base,
+            negated,
+            if_exists,
+        })
+    }
+
+    fn is_match(self, kvs: &ConditionKeyValues, cx: &ConditionContext) -> Result<bool, EvalError> {
+        for (key, policy_values) in &kvs.0 {
+            let policy_values = policy_values.as_slice();
+            if !self.is_match_key(cx.get(key), policy_values, cx)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn is_match_key(
+        self,
+        request_values: Option<&[String]>,
+        policy_values: &[String],
+        vars: &dyn Variables,
+    ) -> Result<bool, EvalError> {
+        if self.base == Base::Null {
+            let is_null = request_values.is_none_or(<[String]>::is_empty);
+            for pv in policy_values {
+                let expected = parse_bool(pv).ok_or_else(|| invalid_value(pv))?;
+                if expected == is_null {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+
+        let request_values = match request_values {
+            Some(vs) if !vs.is_empty() => vs,
+            _ => {
+                return Ok(match self.qualifier {
+                    SetQualifier::ForAllValues => true,
+                    SetQualifier::ForAnyValue => false,
+                    SetQualifier::None => self.if_exists || self.negated,
+                });
+            }
+        };
+
+        // The negation applies to each request value.
+        let test = |rv: &str| -> Result<bool, EvalError> {
+            let mut matched = false;
+            for pv in policy_values {
+                if self.base.is_match(rv, pv, vars)? {
+                    matched = true;
+                    break;
+                }
+            }
+            Ok(matched != self.negated)
+        };
+
+        // Without a set qualifier, a negated operator requires that no request value matches.
+        let require_all = match self.qualifier {
+            SetQualifier::ForAllValues => true,
+            SetQualifier::ForAnyValue => false,
+            SetQualifier::None => self.negated,
+        };
+
+        for rv in request_values {
+            if test(rv)? != require_all {
+                return Ok(!require_all);
+            }
+        }
+        Ok(require_all)
+    }
+}
+
+impl Base {
+    /// Compares a request value with a policy value.
+    ///
+    /// An invalid request value never matches. An invalid policy value is an error.
+    /// Policy variables are substituted in `StringLike` and `ArnLike` patterns.
+    fn is_match(self, rv: &str, pv: &str, vars: &dyn Variables) -> Result<bool, EvalError> {
+        Ok(match self {
+            Base::StringEquals | Base::BinaryEquals => rv == pv,
+            Base::StringEqualsIgnoreCase => rv.to_lowercase() == pv.to_lowercase(),
+            Base::StringLike => PatternSet::is_match_one(pv, rv, vars),
+            Base::NumericEquals
+            | Base::NumericLessThan
+            | Base::NumericLessThanEquals
+            | Base::NumericGreaterThan
+            | Base::NumericGreaterThanEquals => {
+                let pv = parse_number(pv).ok_or_else(|| invalid_value(pv))?;
+                let Some(rv) = parse_number(rv) else { return Ok(false) };
+                rv.partial_cmp(&pv).is_some_and(|ord| self.is_ordering_match(ord))
+            }
+            Base::DateEquals
+            | Base::DateLessThan
+            | Base::DateLessThanEquals
+            | Base::DateGreaterThan
+            | Base::DateGreaterThanEquals => {
+                let pv = parse_date(pv).ok_or_else(|| invalid_value(pv))?;
+                let Some(rv) = parse_date(rv) else { return Ok(false) };
+                self.is_ordering_match(rv.cmp(&pv))
+            }
+            Base::Bool => {
+                let pv = parse_bool(pv).ok_or_else(|| invalid_value(pv))?;
+                parse_bool(rv) == Some(pv)
+            }
+            Base::IpAddress => {
+                let cidr = Cidr::parse(pv).ok_or_else(|| invalid_value(pv))?;
+                rv.parse::<IpAddr>().is_ok_and(|ip| cidr.contains(ip))
+            }
+            Base::ArnLike => is_arn_match(pv, rv, vars),
+            Base::Null => unreachable!(),
+        })
+    }
+
+    fn is_ordering_match(self, ord: Ordering) -> bool {
+        match self {
+            Base::NumericEquals | Base::DateEquals => ord.is_eq(),
+            Base::NumericLessThan | Base::DateLessThan => ord.is_lt(),
+            Base::NumericLessThanEquals | Base::DateLessThanEquals => ord.is_le(),
+            Base::NumericGreaterThan | Base::DateGreaterThan => ord.is_gt(),
+            Base::NumericGreaterThanEquals | Base::DateGreaterThanEquals => ord.is_ge(),
+            _ => false,
+        }
+    }
+}
+
+fn invalid_value(value: &str) -> EvalError {
+    EvalError::InvalidConditionValue(value.to_owned())
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    if s.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if s.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn parse_number(s: &str) -> Option<f64> {
+    s.parse::<f64>().ok().filter(|x| x.is_finite())
+}
+
+/// Parses an ISO 8601 date or a UNIX epoch time.
+fn parse_date(s: &str) -> Option<OffsetDateTime> {
+    if let Ok(t) = OffsetDateTime::parse(s, &Rfc3339) {
+        return Some(t);
+    }
+    if let Ok(secs) = s.parse::<i64>() {
+        return OffsetDateTime::from_unix_timestamp(secs).ok();
+    }
+    let date = time::Date::parse(s, time::macros::format_description!("[year]-[month]-[day]")).ok()?;
+    Some(date.midnight().assume_utc())
+}
+
+/// An IP address range in CIDR notation.
+///
+/// A single address is treated as a range with full prefix length.
+struct Cidr {
+    addr: IpAddr,
+    prefix_len: u32,
+}
+
+impl Cidr {
+    fn parse(s: &str) -> Option<Self> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, len)) => (addr.parse::<IpAddr>().ok()?, Some(len.parse::<u32>().ok()?)),
+            None => (s.parse::<IpAddr>().ok()?, None),
+        };
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = prefix_len.unwrap_or(max_len);
+        if prefix_len > max_len {
+            return None;
+        }
+        Some(Self { addr, prefix_len })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match (self.addr, ip) {
+            (IpAddr::V4(_), IpAddr::V6(v6)) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            _ => ip,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len).unwrap_or(0);
+                (u32::from(net) & mask) == (u32::from(ip) & mask)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len).unwrap_or(0);
+                (u128::from(net) & mask) == (u128::from(ip) & mask)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Compares ARNs component by component. Each component may contain wildcards.
+fn is_arn_match(pattern: &str, arn: &str, vars: &dyn Variables) -> bool {
+    let mut ps = pattern.splitn(6, ':');
+    let mut xs = arn.splitn(6, ':');
+    loop {
+        match (ps.next(), xs.next()) {
+            (Some(p), Some(x)) => {
+                if !PatternSet::is_match_one(p, x, vars) {
+                    return false;
+                }
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::model::OneOrMore;
+
+    use indexmap::indexmap;
+
+    fn check(op: &str, key: &str, policy_values: &[&str], cx: &ConditionContext) -> bool {
+        let rule = ConditionRule(indexmap! {
+            op.to_owned() => ConditionKeyValues(indexmap! {
+                key.to_owned() => OneOrMore::More(policy_values.iter().map(|&s| s.to_owned()).collect())
+            })
+        });
+        is_match(&rule, cx).unwrap()
+    }
+
+    fn context(kvs: &[(&str, &[&str])]) -> ConditionContext {
+        let mut cx = ConditionContext::new();
+        for &(k, vs) in kvs {
+            cx.insert_multi(k, vs.iter().map(|&s| s.to_owned()));
+        }
+        cx
+    }
+
+    #[test]
+    fn string_operators() {
+        let cx = context(&[("s3:prefix", &["home/alice/"]), ("aws:username", &["alice"])]);
+
+        let cases: &[(&str, &[&str], bool)] = &[
+            ("StringEquals", &["home/alice/"], true),
+            ("StringEquals", &["home/bob/", "home/alice/"], true),
+            ("StringEquals", &["HOME/alice/"], false),
+            ("StringNotEquals", &["home/alice/"], false),
+            ("StringNotEquals", &["home/bob/"], true),
+            ("StringEqualsIgnoreCase", &["HOME/ALICE/"], true),
+            ("StringNotEqualsIgnoreCase", &["HOME/ALICE/"], false),
+            ("StringLike", &["home/*"], true),
+            ("StringLike", &["home/?????/"], true),
+            ("StringLike", &["docs/*"], false),
+            ("StringNotLike", &["home/*"], false),
+            ("StringNotLike", &["docs/*"], true),
+            ("StringLike", &["home/${aws:username}/"], true),
+            ("StringLike", &["home/${aws:userid}/"], false),
+        ];
+
+        for &(op, pvs, expected) in cases {
+            assert_eq!(check(op, "s3:prefix", pvs, &cx), expected, "{op} {pvs:?}");
+        }
+    }
+
+    #[test]
+    fn numeric_and_date_operators() {
+        let cx = context(&[
+            ("s3:max-keys", &["10"]),
+            ("aws:CurrentTime", &["2020-06-30T12:00:00Z"]),
+            ("aws:EpochTime", &["1593518400"]),
+        ]);
+
+        let cases: &[(&str, &str, &[&str], bool)] = &[
+            ("NumericEquals", "s3:max-keys", &["10"], true),
+            ("NumericNotEquals", "s3:max-keys", &["10"], false),
+            ("NumericLessThan", "s3:max-keys", &["10"], false),
+            ("NumericLessThanEquals", "s3:max-keys", &["10"], true),
+            ("NumericGreaterThan", "s3:max-keys", &["5"], true),
+            ("NumericGreaterThanEquals", "s3:max-keys", &["11"], false),
+            ("DateLessThan", "aws:CurrentTime", &["2021-01-01T00:00:00Z"], true),
+            ("DateGreaterThan", "aws:CurrentTime", &["2021-01-01"], false),
+            ("DateEquals", "aws:EpochTime", &["2020-06-30T12:00:00Z"], true),
+            ("DateNotEquals", "aws:EpochTime", &["2020-06-30T12:00:00Z"], false),
+            ("DateGreaterThanEquals", "aws:CurrentTime", &["1593518400"], true),
+        ];
+
+        for &(op, key, pvs, expected) in cases {
+            assert_eq!(check(op, key, pvs, &cx), expected, "{op} {key} {pvs:?}");
+        }
+    }
+
+    #[test]
+    fn ip_address_operators() {
+        let cx = context(&[("aws:SourceIp", &["203.0.113.7"]), ("aws:SourceIpV6", &["2001:db8::1"])]);
+
+        let cases: &[(&str, &str, &[&str], bool)] = &[
+            ("IpAddress", "aws:SourceIp", &["203.0.113.0/24"], true),
+            ("IpAddress", "aws:SourceIp", &["203.0.113.7"], true),
+            ("IpAddress", "aws:SourceIp", &["198.51.100.0/24"], false),
+            ("IpAddress", "aws:SourceIp", &["0.0.0.0/0"], true),
+            ("NotIpAddress", "aws:SourceIp", &["203.0.113.0/24"], false),
+            ("NotIpAddress", "aws:SourceIp", &["198.51.100.0/24"], true),
+            ("IpAddress", "aws:SourceIpV6", &["2001:db8::/32"], true),
+            ("IpAddress", "aws:SourceIpV6", &["2001:db9::/32"], false),
+        ];
+
+        for &(op, key, pvs, expected) in cases {
+            assert_eq!(check(op, key, pvs, &cx), expected, "{op} {key} {pvs:?}");
+        }
+    }
+
+    #[test]
+    fn bool_arn_null_operators() {
+        let cx = context(&[
+            ("aws:SecureTransport", &["false"]),
+            ("aws:SourceArn", &["arn:aws:sns:us-east-1:123456789012:topic"]),
+        ]);
+
+        let cases: &[(&str, &str, &[&str], bool)] = &[
+            ("Bool", "aws:SecureTransport", &["false"], true),
+            ("Bool", "aws:SecureTransport", &["true"], false),
+            ("ArnLike", "aws:SourceArn", &["arn:aws:sns:*:123456789012:*"], true),
+            ("ArnLike", "aws:SourceArn", &["arn:aws:sns:*:000000000000:*"], false),
+            ("ArnEquals", "aws:SourceArn", &["arn:aws:sns:us-east-1:123456789012:topic"], true),
+            ("ArnNotLike", "aws:SourceArn", &["arn:aws:s3:::*"], true),
+            ("Null", "aws:SourceArn", &["false"], true),
+            ("Null", "aws:TokenIssueTime", &["true"], true),
+            ("Null", "aws:TokenIssueTime", &["false"], false),
+        ];
+
+        for &(op, key, pvs, expected) in cases {
+            assert_eq!(check(op, key, pvs, &cx), expected, "{op} {key} {pvs:?}");
+        }
+    }
+
+    #[test]
+    fn missing_keys_and_qualifiers() {
+        let cx = context(&[("aws:TagKeys", &["env", "team"])]);
+
+        let cases: &[(&str, &str, &[&str], bool)] = &[
+            ("StringEquals", "s3:prefix", &["a"], false),
+            ("StringNotEquals", "s3:prefix", &["a"], true),
+            ("StringEqualsIfExists", "s3:prefix", &["a"], true),
+            ("NumericLessThanIfExists", "s3:max-keys", &["10"], true),
+            ("ForAllValues:StringEquals", "aws:TagKeys", &["env", "team", "owner"], true),
+            ("ForAllValues:StringEquals", "aws:TagKeys", &["env"], false),
+            ("ForAllValues:StringEquals", "s3:prefix", &["a"], true),
+            ("ForAnyValue:StringEquals", "aws:TagKeys", &["env"], true),
+            ("ForAnyValue:StringEquals", "aws:TagKeys", &["owner"], false),
+            ("ForAnyValue:StringEquals", "s3:prefix", &["a"], false),
+            ("ForAnyValue:StringNotEquals", "aws:TagKeys", &["env"], true),
+            ("ForAllValues:StringNotEquals", "aws:TagKeys", &["env"], false),
+        ];
+
+        for &(op, key, pvs, expected) in cases {
+            assert_eq!(check(op, key, pvs, &cx), expected, "{op} {key} {pvs:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let cx = ConditionContext::new();
+
+        let rule = ConditionRule(indexmap! {
+            "StringMatches".to_owned() => ConditionKeyValues(indexmap! {
+                "s3:prefix".to_owned() => OneOrMore::One("a".to_owned())
+            })
+        });
+        assert!(matches!(is_match(&rule, &cx), Err(EvalError::UnsupportedConditionOperator(_))));
+
+        let cx = context(&[("s3:max-keys", &["10"])]);
+        let rule = ConditionRule(indexmap! {
+            "NumericLessThan".to_owned() => ConditionKeyValues(indexmap! {
+                "s3:max-keys".to_owned() => OneOrMore::One("ten".to_owned())
+            })
+        });
+        assert!(matches!(is_match(&rule, &cx), Err(EvalError::InvalidConditionValue(_))));
+    }
+}
@@ -0,0 +1,369 @@
+//! <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_evaluation-logic.html>
+
+use crate::condition::{self, ConditionContext};
+use crate::model::{ActionRule, ConditionRule, Effect, Policy, Principal, PrincipalRule, ResourceRule, WildcardOneOrMore};
+use crate::pattern::{PatternError, PatternSet, Variables};
+
+/// The result of a policy evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// At least one statement allows the request and no statement denies it.
+    Allow,
+    /// At least one statement denies the request.
+    ExplicitDeny,
+    /// No statement applies to the request.
+    ImplicitDeny,
+}
+
+impl Decision {
+    #[must_use]
+    pub fn is_allowed(self) -> bool {
+        matches!(self, Decision::Allow)
+    }
+
+    /// Combines the decisions of two policies which apply to the same request.
+    ///
+    /// An explicit deny always wins, then an allow.
+    #[must_use]
+    pub fn combine(self, other: Decision) -> Decision {
+        match (self, other) {
+            (Decision::ExplicitDeny, _) | (_, Decision::ExplicitDeny) => Decision::ExplicitDeny,
+            (Decision::Allow, _) | (_, Decision::Allow) => Decision::Allow,
+            (Decision::ImplicitDeny, Decision::ImplicitDeny) => Decision::ImplicitDeny,
+        }
+    }
+}
+
+/// A request to be evaluated against a policy.
+#[derive(Debug, Clone, Copy)]
+pub struct EvalRequest<'a> {
+    /// The principal who sends the request.
+    ///
+    /// `None` means anonymous request.
+    pub principal: Option<&'a str>,
+
+    /// The action name, such as `s3:GetObject`.
+    pub action: &'a str,
+
+    /// The resource ARN, such as `arn:aws:s3:::bucket/key`.
+    pub resource: &'a str,
+
+    /// The condition keys of the request.
+    pub context: &'a ConditionContext,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EvalError {
+    #[error("Unsupported condition operator: {0}")]
+    UnsupportedConditionOperator(String),
+
+    #[error("Invalid condition value: {0}")]
+    InvalidConditionValue(String),
+}
+
+/// A compiled policy which can be evaluated many times.
+pub struct PolicyEvaluator {
+    statements: Vec<CompiledStatement>,
+}
+
+struct CompiledStatement {
+    effect: Effect,
+    principal: Option<PrincipalMatcher>,
+    action: Matcher,
+    resource: Matcher,
+    condition: Option<ConditionRule>,
+}
+
+struct PrincipalMatcher {
+    negated: bool,
+    principal: Principal,
+}
+
+struct Matcher {
+    negated: bool,
+    /// `None` means wildcard
+    patterns: Option<PatternSet>,
+}
+
+impl PolicyEvaluator {
+    /// Compiles a policy.
+    ///
+    /// # Errors
+    /// Returns an error if any action or resource pattern is invalid.
+    pub fn new(policy: &Policy) -> Result<Self, PatternError> {
+        let statements = policy
+            .statement
+            .as_slice()
+            .iter()
+            .map(|st| {
+                let principal = st.principal.as_ref().map(|rule| match rule {
+                    PrincipalRule::Principal(p) => PrincipalMatcher {
+                        negated: false,
+                        principal: p.clone(),
+                    },
+                    PrincipalRule::NotPrincipal(p) => PrincipalMatcher {
+                        negated: true,
+                        principal: p.clone(),
+                    },
+                });
+
+                // Action names are case-insensitive
+                let action = match &st.action {
+                    ActionRule::Action(a) => Matcher::new(false, a, true)?,
+                    ActionRule::NotAction(a) => Matcher::new(true, a, true)?,
+                };
+
+                let resource = match &st.resource {
+                    ResourceRule::Resource(r) => Matcher::new(false, r, false)?,
+                    ResourceRule::NotResource(r) => Matcher::new(true, r, false)?,
+                };
+
+                Ok(CompiledStatement {
+                    effect: st.effect.clone(),
+                    principal,
+                    action,
+                    resource,
+                    condition: st.condition.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { statements })
+    }
+
+    /// Evaluates the request against the policy.
+    ///
+    /// An explicit deny in any statement overrides any allow.
+    ///
+    /// # Errors
+    /// Returns an error if a matching statement contains an invalid condition.
+    pub fn evaluate(&self, req: &EvalRequest<'_>) -> Result<Decision, EvalError> {
+        let action = req.action.to_ascii_lowercase();
+
+        let mut decision = Decision::ImplicitDeny;
+        for st in &self.statements {
+            if st.is_match(req, &action)? {
+                match st.effect {
+                    Effect::Deny => return Ok(Decision::ExplicitDeny),
+                    Effect::Allow => decision = Decision::Allow,
+                }
+            }
+        }
+        Ok(decision)
+    }
+}
+
+impl CompiledStatement {
+    fn is_match(&self, req: &EvalRequest<'_>, action: &str) -> Result<bool, EvalError> {
+        if self.principal.as_ref().is_some_and(|p| !p.is_match(req.principal)) {
+            return Ok(false);
+        }
+        if !self.action.is_match(action, &()) {
+            return Ok(false);
+        }
+        if !self.resource.is_match(req.resource, req.context) {
+            return Ok(false);
+        }
+        match self.condition {
+            Some(ref rule) => condition::is_match(rule, req.context),
+            None => Ok(true),
+        }
+    }
+}
+
+impl PrincipalMatcher {
+    fn is_match(&self, principal: Option<&str>) -> bool {
+        let ans = match &self.principal {
+            Principal::Wildcard => true,
+            Principal::Map(map) => map.values().any(|values| {
+                values
+                    .as_slice()
+                    .iter()
+                    .any(|v| v == "*" || principal.is_some_and(|p| p == v))
+            }),
+        };
+        ans != self.negated
+    }
+}
+
+impl Matcher {
+    fn new(negated: bool, rule: &WildcardOneOrMore<String>, ignore_case: bool) -> Result<Self, PatternError> {
+        let patterns = match rule.as_slice() {
+            None => None,
+            Some(patterns) if ignore_case => {
+                let patterns: Vec<String> = patterns.iter().map(|p| p.to_ascii_lowercase()).collect();
+                Some(PatternSet::new(patterns.iter().map(String::as_str))?)
+            }
+            Some(patterns) => Some(PatternSet::new(patterns.iter().map(String::as_str))?),
+        };
+        Ok(Self { negated, patterns })
+    }
+
+    fn is_match(&self, input: &str, vars: &dyn Variables) -> bool {
+        let ans = match &self.patterns {
+            None => true,
+            Some(patterns) => patterns.is_match_with(input, vars),
+        };
+        ans != self.negated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluator(json: &str) -> PolicyEvaluator {
+        let policy: Policy = serde_json::from_str(json).unwrap();
+        PolicyEvaluator::new(&policy).unwrap()
+    }
+
+    fn eval(e: &PolicyEvaluator, principal: Option<&str>, action: &str, resource: &str) -> Decision {
+        let context = ConditionContext::new();
+        let req = EvalRequest {
+            principal,
+            action,
+            resource,
+            context: &context,
+        };
+        e.evaluate(&req).unwrap()
+    }
+
+    #[test]
+    fn allow_and_implicit_deny() {
+        let e = evaluator(crate::tests::example2_json());
+
+        let cases = [
+            ("s3:ListBucket", "arn:aws:s3:::example_bucket", Decision::Allow),
+            ("s3:listbucket", "arn:aws:s3:::example_bucket", Decision::Allow),
+            ("s3:GetObject", "arn:aws:s3:::example_bucket", Decision::ImplicitDeny),
+            ("s3:ListBucket", "arn:aws:s3:::other_bucket", Decision::ImplicitDeny),
+        ];
+
+        for (action, resource, expected) in cases {
+            assert_eq!(eval(&e, Some("AKID"), action, resource), expected, "{action} {resource}");
+        }
+    }
+
+    #[test]
+    fn explicit_deny_wins() {
+        let e = evaluator(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {"Effect": "Allow", "Principal": "*", "Action": "s3:*", "Resource": "arn:aws:s3:::bucket/*"},
+                    {"Effect": "Deny", "Principal": "*", "Action": "s3:DeleteObject", "Resource": "arn:aws:s3:::bucket/*"}
+                ]
+            }"#,
+        );
+
+        assert_eq!(eval(&e, None, "s3:GetObject", "arn:aws:s3:::bucket/a"), Decision::Allow);
+        assert_eq!(eval(&e, None, "s3:DeleteObject", "arn:aws:s3:::bucket/a"), Decision::ExplicitDeny);
+    }
+
+    #[test]
+    fn not_elements() {
+        let e = evaluator(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {"Effect": "Allow", "Principal": {"AWS": "alice"}, "NotAction": "s3:Delete*", "Resource": "*"},
+                    {"Effect": "Deny", "NotPrincipal": {"AWS": ["alice", "bob"]}, "Action": "*", "NotResource": "arn:aws:s3:::public/*"}
+                ]
+            }"#,
+        );
+
+        let cases = [
+            (Some("alice"), "s3:GetObject", "arn:aws:s3:::private/a", Decision::Allow),
+            (Some("alice"), "s3:DeleteObject", "arn:aws:s3:::private/a", Decision::ImplicitDeny),
+            (Some("bob"), "s3:GetObject", "arn:aws:s3:::private/a", Decision::ImplicitDeny),
+            (Some("carol"), "s3:GetObject", "arn:aws:s3:::private/a", Decision::ExplicitDeny),
+            (Some("carol"), "s3:GetObject", "arn:aws:s3:::public/a", Decision::ImplicitDeny),
+            (None, "s3:GetObject", "arn:aws:s3:::private/a", Decision::ExplicitDeny),
+        ];
+
+        for (principal, action, resource, expected) in cases {
+            assert_eq!(eval(&e, principal, action, resource), expected, "{principal:?} {action} {resource}");
+        }
+    }
+
+    #[test]
+    fn conditions() {
+        let e = evaluator(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": [
+                    {
+                        "Effect": "Allow",
+                        "Principal": "*",
+                        "Action": "s3:ListBucket",
+                        "Resource": "arn:aws:s3:::bucket",
+                        "Condition": {"StringLike": {"s3:prefix": ["home/alice/*"]}}
+                    },
+                    {
+                        "Effect": "Deny",
+                        "Principal": "*",
+                        "Action": "s3:*",
+                        "Resource": "*",
+                        "Condition": {"NotIpAddress": {"aws:SourceIp": "203.0.113.0/24"}}
+                    }
+                ]
+            }"#,
+        );
+
+        let eval = |prefix: &str, ip: &str| {
+            let mut context = ConditionContext::new();
+            context.insert("s3:prefix", prefix);
+            context.insert("aws:SourceIp", ip);
+            let req = EvalRequest {
+                principal: Some("alice"),
+                action: "s3:ListBucket",
+                resource: "arn:aws:s3:::bucket",
+                context: &context,
+            };
+            e.evaluate(&req).unwrap()
+        };
+
+        assert_eq!(eval("home/alice/docs/", "203.0.113.7"), Decision::Allow);
+        assert_eq!(eval("home/bob/", "203.0.113.7"), Decision::ImplicitDeny);
+        assert_eq!(eval("home/alice/docs/", "198.51.100.1"), Decision::ExplicitDeny);
+    }
+
+    #[test]
+    fn policy_variables() {
+        let e = evaluator(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Allow",
+                    "Action": ["s3:GetObject", "s3:PutObject"],
+                    "Resource": "arn:aws:s3:::bucket/home/${aws:username}/*"
+                }
+            }"#,
+        );
+
+        let mut context = ConditionContext::new();
+        context.insert("aws:username", "alice");
+
+        let eval = |resource| {
+            let req = EvalRequest {
+                principal: Some("alice"),
+                action: "s3:GetObject",
+                resource,
+                context: &context,
+            };
+            e.evaluate(&req).unwrap()
+        };
+
+        assert_eq!(eval("arn:aws:s3:::bucket/home/alice/a.txt"), Decision::Allow);
+        assert_eq!(eval("arn:aws:s3:::bucket/home/bob/a.txt"), Decision::ImplicitDeny);
+    }
+
+    #[test]
+    fn combine() {
+        use Decision::*;
+        assert_eq!(Allow.combine(ImplicitDeny), Allow);
+        assert_eq!(ImplicitDeny.combine(ImplicitDeny), ImplicitDeny);
+        assert_eq!(Allow.combine(ExplicitDeny), ExplicitDeny);
+        assert_eq!(ImplicitDeny.combine(ExplicitDeny), ExplicitDeny);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod access;
+pub mod condition;
+pub mod eval;
 pub mod model;
 pub mod pattern;
 
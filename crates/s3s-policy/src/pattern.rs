@@ -1,6 +1,13 @@
+use std::mem;
+
+/// A set of wildcard patterns compiled into a single matcher.
+///
+/// Patterns without policy variables are merged into a trie which is simulated as an NFA,
+/// so the matching cost depends on the number of active states instead of the number of patterns.
+/// Patterns with policy variables are substituted and matched one by one at evaluation time.
 pub struct PatternSet {
-    // TODO: rewrite the naive implementation with something like Aho-Corasick
-    patterns: Vec<Pattern>,
+    trie: Trie,
+    dynamic: Vec<Vec<Token>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -9,9 +16,33 @@ pub enum PatternError {
     InvalidPattern,
 }
 
-#[derive(Debug)]
-struct Pattern {
-    bytes: Vec<u8>,
+/// The values of policy variables.
+///
+/// <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_variables.html>
+pub trait Variables {
+    /// Returns the value of a variable, or `None` if it is not present in the request.
+    fn get(&self, name: &str) -> Option<&str>;
+}
+
+/// No policy variables are available.
+impl Variables for () {
+    fn get(&self, _: &str) -> Option<&str> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(u8),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `${name}` or `${name, 'default'}`
+    Variable {
+        name: Box<str>,
+        default: Option<Box<str>>,
+    },
 }
 
 impl PatternSet {
@@ -20,78 +51,300 @@ impl PatternSet {
     /// Patterns can contain
     /// + `*` to match any sequence of characters (including empty sequence)
     /// + `?` to match any single character
+    /// + `${name}` to match the value of a policy variable
+    /// + `${*}`, `${?}` and `${$}` to match the literal characters
     /// + any other character to match itself
     ///
     /// # Errors
     /// Returns an error if any pattern is invalid.
     pub fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<PatternSet, PatternError> {
-        let patterns = patterns.into_iter().map(Self::parse_pattern).collect::<Result<_, _>>()?;
-        Ok(PatternSet { patterns })
+        let mut trie = Trie::new();
+        let mut dynamic = Vec::new();
+        for pattern in patterns {
+            let tokens = Self::parse_pattern(pattern)?;
+            if tokens.iter().any(|t| matches!(t, Token::Variable { .. })) {
+                dynamic.push(tokens);
+            } else {
+                trie.insert(&tokens);
+            }
+        }
+        Ok(PatternSet { trie, dynamic })
     }
 
-    fn parse_pattern(pattern: &str) -> Result<Pattern, PatternError> {
+    fn parse_pattern(pattern: &str) -> Result<Vec<Token>, PatternError> {
         if pattern.is_empty() {
             return Err(PatternError::InvalidPattern);
         }
-        Ok(Pattern {
-            bytes: pattern.as_bytes().to_owned(),
-        })
+        parse_tokens(pattern)
     }
 
     /// Check if the input matches any of the patterns.
+    ///
+    /// Patterns with policy variables never match.
     #[must_use]
     pub fn is_match(&self, input: &str) -> bool {
-        for pattern in &self.patterns {
-            if Self::match_pattern(&pattern.bytes, input.as_bytes()) {
-                return true;
+        self.is_match_with(input, &())
+    }
+
+    /// Check if the input matches any of the patterns, substituting policy variables.
+    ///
+    /// A pattern whose variable is missing and has no default value does not match.
+    #[must_use]
+    pub fn is_match_with(&self, input: &str, vars: &dyn Variables) -> bool {
+        if self.trie.is_match(input.as_bytes()) {
+            return true;
+        }
+        self.dynamic.iter().any(|tokens| match substitute(tokens, vars) {
+            Some(tokens) => match_tokens(&tokens, input.as_bytes()),
+            None => false,
+        })
+    }
+
+    /// Check if the input matches a single pattern without compiling it into a set.
+    ///
+    /// An invalid pattern never matches.
+    #[must_use]
+    pub fn is_match_one(pattern: &str, input: &str, vars: &dyn Variables) -> bool {
+        let Ok(tokens) = parse_tokens(pattern) else { return false };
+        match substitute(&tokens, vars) {
+            Some(tokens) => match_tokens(&tokens, input.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+fn parse_tokens(pattern: &str) -> Result<Vec<Token>, PatternError> {
+    let bytes = pattern.as_bytes();
+    let mut tokens = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'*' => tokens.push(Token::Star),
+            b'?' => tokens.push(Token::Any),
+            b'$' if bytes.get(i + 1) == Some(&b'{') => {
+                let len = bytes[i..]
+                    .iter()
+                    .position(|&b| b == b'}')
+                    .ok_or(PatternError::InvalidPattern)?;
+                let inner = &pattern[i + 2..i + len];
+                tokens.push(parse_variable(inner)?);
+                i += len;
             }
+            b => tokens.push(Token::Literal(b)),
         }
-        false
+        i += 1;
     }
+    Ok(tokens)
+}
 
-    /// <https://leetcode.com/problems/wildcard-matching/>
-    fn match_pattern(pattern: &[u8], input: &[u8]) -> bool {
-        let mut p_idx = 0;
-        let mut s_idx = 0;
+fn parse_variable(inner: &str) -> Result<Token, PatternError> {
+    match inner {
+        "*" => return Ok(Token::Literal(b'*')),
+        "?" => return Ok(Token::Literal(b'?')),
+        "$" => return Ok(Token::Literal(b'$')),
+        _ => {}
+    }
 
-        let mut p_back = usize::MAX - 1;
-        let mut s_back = usize::MAX - 1;
+    let (name, default) = match inner.split_once(',') {
+        Some((name, default)) => {
+            let default = default.trim();
+            let default = default
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .ok_or(PatternError::InvalidPattern)?;
+            (name.trim(), Some(default.into()))
+        }
+        None => (inner.trim(), None),
+    };
 
-        loop {
-            if p_idx < pattern.len() {
-                let p = pattern[p_idx];
-                if p == b'*' {
+    if name.is_empty() {
+        return Err(PatternError::InvalidPattern);
+    }
+
+    Ok(Token::Variable {
+        name: name.into(),
+        default,
+    })
+}
+
+/// Replaces variables with their values as literal tokens.
+fn substitute(tokens: &[Token], vars: &dyn Variables) -> Option<Vec<Token>> {
+    let mut ans = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match token {
+            Token::Variable { name, default } => {
+                let value = vars.get(name).or(default.as_deref())?;
+                ans.extend(value.bytes().map(Token::Literal));
+            }
+            _ => ans.push(token.clone()),
+        }
+    }
+    Some(ans)
+}
+
+/// Matches a single pattern with greedy backtracking.
+///
+/// <https://leetcode.com/problems/wildcard-matching/>
+fn match_tokens(pattern: &[Token], input: &[u8]) -> bool {
+    let mut p_idx = 0;
+    let mut s_idx = 0;
+
+    let mut p_back = usize::MAX - 1;
+    let mut s_back = usize::MAX - 1;
+
+    loop {
+        if p_idx < pattern.len() {
+            let p = &pattern[p_idx];
+            if *p == Token::Star {
+                p_idx += 1;
+                p_back = p_idx;
+                s_back = s_idx;
+                continue;
+            }
+
+            if s_idx < input.len() {
+                let c = input[s_idx];
+                if *p == Token::Literal(c) || *p == Token::Any {
                     p_idx += 1;
-                    p_back = p_idx;
-                    s_back = s_idx;
+                    s_idx += 1;
                     continue;
                 }
+            }
+        } else if s_idx == input.len() {
+            return true;
+        }
+
+        if p_back == pattern.len() {
+            return true;
+        }
+
+        if s_back + 1 < input.len() {
+            s_back += 1;
+            p_idx = p_back;
+            s_idx = s_back;
+            continue;
+        }
+
+        return false;
+    }
+}
+
+/// A trie of patterns.
+///
+/// A node reached by `*` has a self-loop on every byte.
+/// Consecutive `*` are collapsed, so a star node never has a star child.
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+#[derive(Default)]
+struct Node {
+    /// sorted by byte
+    literals: Vec<(u8, u32)>,
+    any: Option<u32>,
+    star: Option<u32>,
+    is_star: bool,
+    accept: bool,
+}
+
+impl Trie {
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+        }
+    }
+
+    fn push_node(&mut self, is_star: bool) -> u32 {
+        let idx = u32::try_from(self.nodes.len()).expect("too many trie nodes");
+        self.nodes.push(Node {
+            is_star,
+            ..Node::default()
+        });
+        idx
+    }
 
-                if s_idx < input.len() {
-                    let c = input[s_idx];
-                    if p == c || p == b'?' {
-                        p_idx += 1;
-                        s_idx += 1;
-                        continue;
+    fn insert(&mut self, tokens: &[Token]) {
+        let mut cur = 0;
+        for token in tokens {
+            let node = &self.nodes[cur as usize];
+            cur = match token {
+                Token::Literal(b) => match node.literals.binary_search_by_key(b, |&(b, _)| b) {
+                    Ok(i) => node.literals[i].1,
+                    Err(i) => {
+                        let next = self.push_node(false);
+                        self.nodes[cur as usize].literals.insert(i, (*b, next));
+                        next
+                    }
+                },
+                Token::Any => {
+                    if let Some(next) = node.any {
+                        next
+                    } else {
+                        let next = self.push_node(false);
+                        self.nodes[cur as usize].any = Some(next);
+                        next
                     }
                 }
-            } else if s_idx == input.len() {
-                return true;
-            }
-
-            if p_back == pattern.len() {
-                return true;
-            }
+                Token::Star if node.is_star => cur,
+                Token::Star => {
+                    if let Some(next) = node.star {
+                        next
+                    } else {
+                        let next = self.push_node(true);
+                        self.nodes[cur as usize].star = Some(next);
+                        next
+                    }
+                }
+                Token::Variable { .. } => unreachable!(),
+            };
+        }
+        self.nodes[cur as usize].accept = true;
+    }
 
-            if s_back + 1 < input.len() {
-                s_back += 1;
-                p_idx = p_back;
-                s_idx = s_back;
-                continue;
-            }
+    /// Enters a state and its epsilon closure.
+    fn enter(&self, state: u32, states: &mut Vec<u32>) {
+        states.push(state);
+        if let Some(star) = self.nodes[state as usize].star {
+            states.push(star);
+        }
+    }
 
+    fn is_match(&self, input: &[u8]) -> bool {
+        if self.nodes.len() == 1 {
             return false;
         }
+
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        self.enter(0, &mut current);
+
+        for &b in input {
+            next.clear();
+            for &state in &current {
+                let node = &self.nodes[state as usize];
+                if node.is_star {
+                    if node.accept {
+                        // matches the rest of the input
+                        return true;
+                    }
+                    next.push(state);
+                }
+                if let Ok(i) = node.literals.binary_search_by_key(&b, |&(b, _)| b) {
+                    self.enter(node.literals[i].1, &mut next);
+                }
+                if let Some(any) = node.any {
+                    self.enter(any, &mut next);
+                }
+            }
+            if next.is_empty() {
+                return false;
+            }
+            next.sort_unstable();
+            next.dedup();
+            mem::swap(&mut current, &mut next);
+        }
+
+        current.iter().any(|&state| self.nodes[state as usize].accept)
     }
 }
 
@@ -99,6 +352,28 @@ impl PatternSet {
 mod tests {
     use super::*;
 
+    use std::collections::HashMap;
+
+    use proptest::prelude::*;
+
+    /// A reference matcher with plain recursive backtracking.
+    fn reference_match(pattern: &[u8], input: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => input.is_empty(),
+            Some((b'*', rest)) => (0..=input.len()).any(|i| reference_match(rest, &input[i..])),
+            Some((b'?', rest)) => !input.is_empty() && reference_match(rest, &input[1..]),
+            Some((&p, rest)) => input.first() == Some(&p) && reference_match(rest, &input[1..]),
+        }
+    }
+
+    struct Vars(HashMap<&'static str, &'static str>);
+
+    impl Variables for Vars {
+        fn get(&self, name: &str) -> Option<&str> {
+            self.0.get(name).copied()
+        }
+    }
+
     #[test]
     fn test_match() {
         let cases = &[
@@ -116,9 +391,98 @@ mod tests {
         ];
 
         for &(pattern, input, expected) in cases {
-            let pattern = PatternSet::parse_pattern(pattern).unwrap();
-            let ans = PatternSet::match_pattern(&pattern.bytes, input.as_bytes());
-            assert_eq!(ans, expected, "pattern: {pattern:?}, input: {input:?}");
+            let tokens = PatternSet::parse_pattern(pattern).unwrap();
+            assert_eq!(
+                match_tokens(&tokens, input.as_bytes()),
+                expected,
+                "pattern: {pattern:?}, input: {input:?}"
+            );
+
+            let set = PatternSet::new([pattern]).unwrap();
+            assert_eq!(set.is_match(input), expected, "pattern: {pattern:?}, input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_set() {
+        let set = PatternSet::new([
+            "arn:aws:s3:::bucket",
+            "arn:aws:s3:::bucket/public/*",
+            "arn:aws:s3:::bucket/?/*.txt",
+            "arn:aws:s3:::logs-*",
+        ])
+        .unwrap();
+
+        let cases = [
+            ("arn:aws:s3:::bucket", true),
+            ("arn:aws:s3:::bucket/", false),
+            ("arn:aws:s3:::bucket/public/a/b", true),
+            ("arn:aws:s3:::bucket/a/b.txt", true),
+            ("arn:aws:s3:::bucket/ab/b.txt", false),
+            ("arn:aws:s3:::logs-2020", true),
+            ("arn:aws:s3:::other", false),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(set.is_match(input), expected, "input: {input:?}");
+        }
+
+        assert!(PatternSet::new([""]).is_err());
+        assert!(!PatternSet::new([]).unwrap().is_match(""));
+    }
+
+    #[test]
+    fn test_variables() {
+        let set = PatternSet::new([
+            "arn:aws:s3:::bucket/home/${aws:username}/*",
+            "arn:aws:s3:::bucket/shared/${s3:prefix, 'default'}",
+            "arn:aws:s3:::bucket/literal/${*}${?}${$}",
+        ])
+        .unwrap();
+
+        let vars = Vars([("aws:username", "a*")].into_iter().collect());
+
+        let cases = [
+            ("arn:aws:s3:::bucket/home/a*/file", true),
+            ("arn:aws:s3:::bucket/home/ab/file", false),
+            ("arn:aws:s3:::bucket/shared/default", true),
+            ("arn:aws:s3:::bucket/shared/other", false),
+            ("arn:aws:s3:::bucket/literal/*?$", true),
+            ("arn:aws:s3:::bucket/literal/ab$", false),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(set.is_match_with(input, &vars), expected, "input: {input:?}");
+        }
+
+        assert!(!set.is_match("arn:aws:s3:::bucket/home/a*/file"));
+
+        assert!(PatternSet::new(["${aws:username"]).is_err());
+        assert!(PatternSet::new(["${}"]).is_err());
+        assert!(PatternSet::new(["${a, default}"]).is_err());
+    }
+
+    fn pattern_strategy() -> impl Strategy<Value = String> {
+        "[ab?*/]{1,8}"
+    }
+
+    proptest! {
+        #[test]
+        fn single_matches_reference(pattern in pattern_strategy(), input in "[ab/]{0,12}") {
+            let expected = reference_match(pattern.as_bytes(), input.as_bytes());
+
+            let tokens = PatternSet::parse_pattern(&pattern).unwrap();
+            prop_assert_eq!(match_tokens(&tokens, input.as_bytes()), expected);
+
+            let set = PatternSet::new([pattern.as_str()]).unwrap();
+            prop_assert_eq!(set.is_match(&input), expected);
+        }
+
+        #[test]
+        fn set_matches_reference(patterns in prop::collection::vec(pattern_strategy(), 1..16), input in "[ab/]{0,12}") {
+            let expected = patterns.iter().any(|p| reference_match(p.as_bytes(), input.as_bytes()));
+            let set = PatternSet::new(patterns.iter().map(String::as_str)).unwrap();
+            prop_assert_eq!(set.is_match(&input), expected);
         }
     }
 }
@@ -1,10 +1,13 @@
 use s3s::auth::SimpleAuth;
+use s3s::connection::IpCidr;
 use s3s::host::SingleDomain;
 use s3s::service::S3ServiceBuilder;
+use s3s::tls::{TlsAcceptor, TlsListener};
 use tokio::net::TcpListener;
 
 use std::error::Error;
 use std::io::IsTerminal;
+use std::path::PathBuf;
 
 use aws_credential_types::provider::ProvideCredentials;
 
@@ -27,6 +30,19 @@ struct Opt {
 
     #[clap(long)]
     endpoint_url: String,
+
+    /// Networks of trusted proxies, such as `10.0.0.0/8`.
+    /// The client IP of a request from a trusted proxy is taken from the forwarded headers.
+    #[clap(long)]
+    trusted_proxy: Vec<IpCidr>,
+
+    /// Certificate chain file (PEM) for serving HTTPS. Reloaded on SIGHUP.
+    #[clap(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Private key file (PEM) for serving HTTPS. Reloaded on SIGHUP.
+    #[clap(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
 }
 
 fn setup_tracing() {
@@ -67,11 +83,27 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
             b.set_host(SingleDomain::new(&domain)?);
         }
 
+        // Resolve client IPs behind proxies
+        for proxy in opt.trusted_proxy {
+            b.add_trusted_proxy(proxy);
+        }
+
         b.build()
     };
 
+    // Setup TLS
+    let tls_acceptor = match (opt.tls_cert, opt.tls_key) {
+        (Some(cert), Some(key)) => {
+            let acceptor = TlsAcceptor::from_pem_files(cert, key)?;
+            acceptor.reload_on_sighup()?;
+            Some(acceptor)
+        }
+        _ => None,
+    };
+
     // Run server
     let listener = TcpListener::bind((opt.host.as_str(), opt.port)).await?;
+    let mut listener = TlsListener::new(listener, tls_acceptor)?;
 
     let hyper_service = service.into_shared();
 
@@ -80,12 +112,13 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
 
     let mut ctrl_c = std::pin::pin!(tokio::signal::ctrl_c());
 
-    info!("server is running at http://{}:{}/", opt.host, opt.port);
+    let scheme = if listener.is_tls() { "https" } else { "http" };
+    info!("server is running at {scheme}://{}:{}/", opt.host, opt.port);
     info!("server is forwarding requests to {}", opt.endpoint_url);
 
     loop {
-        let (socket, _) = tokio::select! {
-            res =  listener.accept() => {
+        let (stream, connection) = tokio::select! {
+            res = listener.accept() => {
                 match res {
                     Ok(conn) => conn,
                     Err(err) => {
@@ -99,13 +132,16 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
             }
         };
 
-        let conn = http_server.serve_connection(TokioIo::new(socket), hyper_service.clone());
+        let service = hyper_service.with_connection(connection);
+        let conn = http_server.serve_connection(TokioIo::new(stream), service);
         let conn = graceful.watch(conn.into_owned());
         tokio::spawn(async move {
             let _ = conn.await;
         });
     }
 
+    drop(listener);
+
     tokio::select! {
         () = graceful.shutdown() => {
              tracing::debug!("Gracefully shutdown!");
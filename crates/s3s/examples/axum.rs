@@ -5,6 +5,8 @@ use axum::http;
 use http::{Extensions, HeaderMap, Method, StatusCode, Uri};
 use tower::Service;
 
+use std::net::IpAddr;
+
 pub struct CustomRoute {
     router: axum::Router,
 }
@@ -21,7 +23,7 @@ impl CustomRoute {
 #[derive(Debug, Clone)]
 pub struct Extra {
     pub credentials: Option<s3s::auth::Credentials>,
-    pub region: Option<String>,
+    pub region: Option<s3s::region::Region>,
     pub service: Option<String>,
 }
 
@@ -49,7 +51,14 @@ fn convert_response(resp: http::Response<axum::body::Body>) -> S3Response<(Statu
 
 #[async_trait::async_trait]
 impl S3Route for CustomRoute {
-    fn is_match(&self, _method: &Method, uri: &Uri, _headers: &HeaderMap, _extensions: &mut Extensions) -> bool {
+    fn is_match(
+        &self,
+        _method: &Method,
+        uri: &Uri,
+        _headers: &HeaderMap,
+        _extensions: &mut Extensions,
+        _client_ip: Option<IpAddr>,
+    ) -> bool {
         let path = uri.path();
         let prefix = const_str::concat!(self::handlers::PREFIX, "/");
         path.starts_with(prefix)
@@ -1,6 +1,10 @@
 use crate::S3Operation;
 use crate::auth::Credentials;
+use crate::connection::ConnectionInfo;
 use crate::path::S3Path;
+use crate::tls::TlsInfo;
+
+use std::net::IpAddr;
 
 use hyper::HeaderMap;
 use hyper::Method;
@@ -12,6 +16,9 @@ pub struct S3AccessContext<'a> {
     pub(crate) s3_path: &'a S3Path,
     pub(crate) s3_op: &'a S3Operation,
 
+    pub(crate) connection: Option<&'a ConnectionInfo>,
+    pub(crate) client_ip: Option<IpAddr>,
+
     pub(crate) method: &'a Method,
     pub(crate) uri: &'a Uri,
     pub(crate) headers: &'a HeaderMap,
@@ -57,6 +64,32 @@ impl S3AccessContext<'_> {
         self.headers
     }
 
+    /// Returns the connection information of current request.
+    ///
+    /// `None` means the server does not provide the connection information.
+    #[must_use]
+    pub fn connection(&self) -> Option<&ConnectionInfo> {
+        self.connection
+    }
+
+    /// Returns the IP address of the client,
+    /// which can be used to implement `aws:SourceIp` checks.
+    ///
+    /// It is the peer address of the connection, or the forwarded address if the peer is a trusted proxy.
+    #[must_use]
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
+    /// Returns the TLS information of current request.
+    ///
+    /// `None` means the request is not sent over a TLS connection,
+    /// which can be used to implement `aws:SecureTransport` checks.
+    #[must_use]
+    pub fn tls_info(&self) -> Option<&TlsInfo> {
+        self.connection.and_then(|c| c.tls.as_ref())
+    }
+
     /// Returns the extensions of current request.
     ///
     /// It is used to pass custom data between middlewares.
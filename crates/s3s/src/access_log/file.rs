@@ -0,0 +1,240 @@
+use super::{AccessLogRecord, AccessLogSink};
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use time::OffsetDateTime;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use tracing::{error, warn};
+
+/// How often [`RollingFileSink`] starts a new file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+/// An access log sink which appends records to files in a directory
+///
+/// The files are named `{prefix}.{suffix}`, where the suffix is
+/// `YYYY-MM-DD-HH` for hourly rotation, `YYYY-MM-DD` for daily rotation,
+/// or `log` if the file is never rotated.
+///
+/// The records are written by a background thread, so that [`AccessLogSink::write`] never blocks on the disk.
+/// At most 4096 records are queued. If the disk can not keep up, new records are dropped
+/// and counted by [`RollingFileSink::dropped`].
+/// Dropping the sink waits for the pending records to be written.
+#[derive(Debug)]
+pub struct RollingFileSink {
+    dir: PathBuf,
+    sender: Option<mpsc::SyncSender<(OffsetDateTime, String)>>,
+    writer: Option<thread::JoinHandle<()>>,
+    dropped: AtomicU64,
+}
+
+const QUEUE_CAPACITY: usize = 4096;
+
+const HOURLY: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]-[hour]");
+const DAILY: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]");
+
+impl RollingFileSink {
+    /// Creates a sink which writes files in `dir`.
+    ///
+    /// # Errors
+    /// Returns an error if the directory can not be created, or the writer thread can not be spawned.
+    pub fn new(dir: impl Into<PathBuf>, prefix: impl Into<String>, rotation: Rotation) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut writer = Writer {
+            dir: dir.clone(),
+            prefix: prefix.into(),
+            rotation,
+            current: None,
+        };
+        let (sender, receiver) = mpsc::sync_channel::<(OffsetDateTime, String)>(QUEUE_CAPACITY);
+        let handle = thread::Builder::new().name("s3s-access-log".into()).spawn(move || {
+            for (now, line) in receiver {
+                if let Err(err) = writer.write_line(now, line.as_bytes()) {
+                    error!(?err, dir = ?writer.dir, "failed to write access log");
+                }
+            }
+        })?;
+
+        Ok(Self {
+            dir,
+            sender: Some(sender),
+            writer: Some(handle),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the number of records dropped because the queue was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl AccessLogSink for RollingFileSink {
+    fn write(&self, record: &AccessLogRecord) {
+        let Some(sender) = &self.sender else { return };
+        let line = format!("{record}\n");
+        match sender.try_send((OffsetDateTime::now_utc(), line)) {
+            Ok(()) => {}
+            Err(mpsc::TrySendError::Full(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                // Logs the first drop, then less and less often.
+                if dropped.is_power_of_two() {
+                    warn!(dir = ?self.dir, dropped, "access log queue is full, dropping records");
+                }
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => error!(dir = ?self.dir, "access log writer has stopped"),
+        }
+    }
+}
+
+impl Drop for RollingFileSink {
+    fn drop(&mut self) {
+        // Closing the channel stops the writer after the pending records.
+        drop(self.sender.take());
+        if let Some(handle) = self.writer.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// The state of the writer thread
+struct Writer {
+    dir: PathBuf,
+    prefix: String,
+    rotation: Rotation,
+    current: Option<(String, File)>,
+}
+
+impl Writer {
+    fn suffix(&self, now: OffsetDateTime) -> String {
+        let format = match self.rotation {
+            Rotation::Hourly => HOURLY,
+            Rotation::Daily => DAILY,
+            Rotation::Never => return "log".to_owned(),
+        };
+        now.format(format).unwrap_or_else(|_| "log".to_owned())
+    }
+
+    fn file_name(&self, now: OffsetDateTime) -> String {
+        format!("{}.{}", self.prefix, self.suffix(now))
+    }
+
+    fn write_line(&mut self, now: OffsetDateTime, line: &[u8]) -> io::Result<()> {
+        let name = self.file_name(now);
+        let file = match &mut self.current {
+            Some((current, file)) if *current == name => file,
+            _ => {
+                let file = OpenOptions::new().create(true).append(true).open(self.dir.join(&name))?;
+                &mut self.current.insert((name, file)).1
+            }
+        };
+        file.write_all(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("s3s-access-log-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn writer(dir: &Path, rotation: Rotation) -> Writer {
+        Writer {
+            dir: dir.to_owned(),
+            prefix: "access".into(),
+            rotation,
+            current: None,
+        }
+    }
+
+    #[test]
+    fn file_names() {
+        let now = datetime!(2025-03-04 05:06:07 UTC);
+        assert_eq!(writer(Path::new("."), Rotation::Hourly).file_name(now), "access.2025-03-04-05");
+        assert_eq!(writer(Path::new("."), Rotation::Daily).file_name(now), "access.2025-03-04");
+        assert_eq!(writer(Path::new("."), Rotation::Never).file_name(now), "access.log");
+    }
+
+    #[test]
+    fn rotation() {
+        let dir = temp_dir("rotation");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut w = writer(&dir, Rotation::Hourly);
+        w.write_line(datetime!(2025-03-04 05:00:00 UTC), b"a\n").unwrap();
+        w.write_line(datetime!(2025-03-04 05:59:59 UTC), b"b\n").unwrap();
+        w.write_line(datetime!(2025-03-04 06:00:00 UTC), b"c\n").unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("access.2025-03-04-05"), "a\nb\n");
+        assert_eq!(read("access.2025-03-04-06"), "c\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn background_writer() {
+        let dir = temp_dir("background");
+        let sink = RollingFileSink::new(&dir, "access", Rotation::Never).unwrap();
+        let record = AccessLogRecord {
+            bucket_owner: None,
+            bucket: None,
+            time: OffsetDateTime::now_utc(),
+            remote_ip: None,
+            requester: None,
+            request_id: None,
+            operation: "REST.GET.OBJECT".into(),
+            key: None,
+            request_uri: "GET / HTTP/1.1".into(),
+            http_status: 200,
+            error_code: None,
+            bytes_sent: 0,
+            object_size: None,
+            total_time: std::time::Duration::ZERO,
+            turn_around_time: std::time::Duration::ZERO,
+            referer: None,
+            user_agent: None,
+            version_id: None,
+            host_id: None,
+            signature_version: None,
+            cipher_suite: None,
+            authentication_type: None,
+            host_header: None,
+            tls_version: None,
+        };
+        sink.write(&record);
+        sink.write(&record);
+        assert_eq!(sink.dropped(), 0);
+        drop(sink);
+
+        let content = std::fs::read_to_string(dir.join("access.log")).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.lines().all(|line| line.contains("REST.GET.OBJECT")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// Identity data attached to the credentials by an authentication provider
+///
+/// Access checks can use it to make decisions based on the caller,
+/// for example the role assumed by temporary credentials.
+#[derive(Clone)]
+pub struct Identity(Arc<dyn Any + Send + Sync>);
+
+impl Identity {
+    #[must_use]
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// Returns a reference to the inner value if it is of type `T`.
+    #[must_use]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identity").finish_non_exhaustive()
+    }
+}
+
+/// Two identities are equal if they share the same value.
+impl PartialEq for Identity {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Identity {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downcast() {
+        let identity = Identity::new(String::from("alice"));
+        assert_eq!(identity.downcast_ref::<String>().map(String::as_str), Some("alice"));
+        assert!(identity.downcast_ref::<u32>().is_none());
+
+        assert_eq!(identity, identity.clone());
+        assert_ne!(identity, Identity::new(String::from("alice")));
+    }
+}
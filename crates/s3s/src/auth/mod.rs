@@ -3,9 +3,15 @@
 mod secret_key;
 pub use self::secret_key::{Credentials, SecretKey};
 
+mod identity;
+pub use self::identity::Identity;
+
 mod simple_auth;
 pub use self::simple_auth::SimpleAuth;
 
+mod signature_policy;
+pub use self::signature_policy::SignaturePolicy;
+
 use crate::error::S3Result;
 
 /// S3 Authentication Provider
@@ -15,4 +21,17 @@ pub trait S3Auth: Send + Sync + 'static {
     ///
     /// This method is usually implemented as a database query.
     async fn get_secret_key(&self, access_key: &str) -> S3Result<SecretKey>;
+
+    /// Checks the session token of the access key after the signature is verified.
+    ///
+    /// `session_token` is the value of `x-amz-security-token`,
+    /// which comes from the headers, the query of a presigned URL, or the fields of a POST form.
+    /// Temporary credentials should reject a missing or invalid session token.
+    ///
+    /// The returned identity is attached to [`Credentials`].
+    /// The default implementation accepts any session token and attaches nothing.
+    async fn check_session_token(&self, access_key: &str, session_token: Option<&str>) -> S3Result<Option<Identity>> {
+        let _ = (access_key, session_token);
+        Ok(None)
+    }
 }
@@ -1,13 +1,50 @@
+use super::Identity;
+
 use std::fmt;
 
 use serde::Deserialize;
 use serde::Serialize;
 use zeroize::Zeroize;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Credentials {
     pub access_key: String,
     pub secret_key: SecretKey,
+
+    /// The value of `x-amz-security-token`, if any.
+    pub session_token: Option<String>,
+
+    /// The identity data attached by [`S3Auth::check_session_token`](super::S3Auth::check_session_token).
+    pub identity: Option<Identity>,
+}
+
+impl Credentials {
+    /// Creates credentials without a session token or identity.
+    #[must_use]
+    pub fn new(access_key: impl Into<String>, secret_key: impl Into<SecretKey>) -> Self {
+        Self {
+            access_key: access_key.into(),
+            secret_key: secret_key.into(),
+            session_token: None,
+            identity: None,
+        }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Credentials");
+        d.field("access_key", &self.access_key);
+        d.field("secret_key", &self.secret_key);
+        if self.session_token.is_some() {
+            d.field("session_token", &PLACEHOLDER);
+        }
+        if let Some(ref val) = self.identity {
+            d.field("identity", val);
+        }
+        d.finish_non_exhaustive()
+    }
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+/// Restrictions on how requests are signed.
+///
+/// The default policy accepts all signature versions and anonymous requests,
+/// allows 15 minutes of clock skew, and limits presigned URLs to 7 days.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SignaturePolicy {
+    /// The maximum difference between the request time and the server time.
+    ///
+    /// Requests beyond this window are rejected with `RequestTimeTooSkewed`.
+    pub max_skew_time: Duration,
+
+    /// The maximum value of `X-Amz-Expires` in presigned URLs.
+    ///
+    /// Presigned URLs with a longer expiry are rejected with `AuthorizationQueryParametersError`.
+    pub max_presigned_url_expires: Duration,
+
+    /// Whether requests signed with signature version 2 are accepted.
+    pub allow_sig_v2: bool,
+
+    /// Whether unsigned requests are accepted.
+    ///
+    /// If it is `false`, anonymous requests are rejected before the access check.
+    pub allow_anonymous: bool,
+}
+
+impl Default for SignaturePolicy {
+    fn default() -> Self {
+        Self {
+            max_skew_time: Duration::from_mins(15),
+            max_presigned_url_expires: Duration::from_hours(7 * 24),
+            allow_sig_v2: true,
+            allow_anonymous: true,
+        }
+    }
+}
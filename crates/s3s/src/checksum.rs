@@ -1,11 +1,39 @@
+//! Checksums of request bodies
+//!
+//! When checksum verification is enabled by [`S3ServiceBuilder::set_verify_checksums`](crate::service::S3ServiceBuilder::set_verify_checksums),
+//! the bodies of `PutObject` and `UploadPart` requests are verified against `Content-MD5`
+//! and `x-amz-checksum-*` values, which can be sent in the headers or the trailing headers.
+//!
+//! The last chunk of the body is held back until the end of the body has been verified.
+//! A mismatch fails the body stream in place of the last chunk, so that a backend never receives a complete corrupted object.
+//! The computed checksums are available to the backend by [`S3Request::body_checksums`](crate::S3Request::body_checksums)
+//! after the body has been fully consumed.
+
+use crate::StdError;
 use crate::crypto::Checksum as _;
 use crate::crypto::Crc32;
 use crate::crypto::Crc32c;
 use crate::crypto::Crc64Nvme;
+use crate::crypto::Md5;
 use crate::crypto::Sha1;
 use crate::crypto::Sha256;
 use crate::dto::Checksum;
+use crate::error::{S3Error, S3ErrorCode, S3Result};
+use crate::header;
+use crate::http::{Body, TrailingHeaders};
+use crate::stream::{ByteStream, DynByteStream, RemainingLength};
+
+use std::fmt;
+use std::mem;
+use std::ops::Not;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
 
+use bytes::Bytes;
+use futures::Stream;
+use hyper::HeaderMap;
+use hyper::header::HeaderName;
 use stdx::default::default;
 
 #[derive(Default)]
@@ -66,3 +94,462 @@ impl ChecksumHasher {
         base64_simd::STANDARD.encode_to_string(input)
     }
 }
+
+/// The checksums computed from a request body
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComputedChecksums {
+    /// The MD5 digest of the body, which is the `ETag` of a single part object.
+    pub md5: [u8; 16],
+
+    /// The checksums of the algorithms requested by the client.
+    pub checksum: Checksum,
+}
+
+/// The checksums of a request body, which are computed while the body is consumed.
+///
+/// The checksums are only available after the body has been fully consumed and verified.
+#[derive(Clone, Default)]
+pub struct BodyChecksums(Arc<Mutex<BodyChecksumsState>>);
+
+#[derive(Default)]
+struct BodyChecksumsState {
+    checksums: Option<ComputedChecksums>,
+    error: Option<(S3ErrorCode, String)>,
+}
+
+impl fmt::Debug for BodyChecksums {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BodyChecksums").field(&self.lock().checksums).finish()
+    }
+}
+
+impl BodyChecksums {
+    fn lock(&self) -> MutexGuard<'_, BodyChecksumsState> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the computed checksums.
+    ///
+    /// Returns `None` if the body has not been fully consumed or the verification has failed.
+    #[must_use]
+    pub fn get(&self) -> Option<ComputedChecksums> {
+        self.lock().checksums.clone()
+    }
+
+    /// Returns the error of the failed verification.
+    pub(crate) fn error(&self) -> Option<S3Error> {
+        let state = self.lock();
+        let (code, msg) = state.error.as_ref()?;
+        Some(S3Error::with_message(code.clone(), msg.clone()))
+    }
+}
+
+const CHECKSUM_HEADERS: [HeaderName; 5] = [
+    header::X_AMZ_CHECKSUM_CRC32,
+    header::X_AMZ_CHECKSUM_CRC32C,
+    header::X_AMZ_CHECKSUM_CRC64NVME,
+    header::X_AMZ_CHECKSUM_SHA1,
+    header::X_AMZ_CHECKSUM_SHA256,
+];
+
+/// Returns the index of a checksum algorithm in [`CHECKSUM_HEADERS`], and the length of its digest.
+fn checksum_algorithm(name: &str) -> Option<(usize, usize)> {
+    let name = name.trim().to_ascii_lowercase();
+    let name = name.strip_prefix("x-amz-checksum-").unwrap_or(&name);
+    match name {
+        "crc32" => Some((0, 4)),
+        "crc32c" => Some((1, 4)),
+        "crc64nvme" => Some((2, 8)),
+        "sha1" => Some((3, 20)),
+        "sha256" => Some((4, 32)),
+        _ => None,
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &HeaderName) -> S3Result<Option<&'a str>> {
+    match headers.get(name) {
+        Some(val) => match val.to_str() {
+            Ok(s) => Ok(Some(s)),
+            Err(_) => Err(invalid_request!("Value for {name} header is invalid.")),
+        },
+        None => Ok(None),
+    }
+}
+
+fn checksum_fields(checksum: &Checksum) -> [Option<&String>; 5] {
+    [
+        checksum.checksum_crc32.as_ref(),
+        checksum.checksum_crc32c.as_ref(),
+        checksum.checksum_crc64nvme.as_ref(),
+        checksum.checksum_sha1.as_ref(),
+        checksum.checksum_sha256.as_ref(),
+    ]
+}
+
+/// The values sent by the client
+#[derive(Default)]
+struct Expected {
+    md5: Option<[u8; 16]>,
+    checksum: Checksum,
+    /// The trailing headers declared in `x-amz-trailer`
+    trailers: Vec<String>,
+}
+
+impl Expected {
+    /// Parses the expected values and enables the requested algorithms.
+    fn parse(headers: &HeaderMap, hasher: &mut ChecksumHasher) -> S3Result<Self> {
+        let mut expected = Self::default();
+
+        if let Some(val) = header_str(headers, &header::CONTENT_MD5)? {
+            let md5 = base64_simd::STANDARD
+                .decode_to_vec(val)
+                .ok()
+                .and_then(|v| <[u8; 16]>::try_from(v).ok());
+            match md5 {
+                Some(md5) => expected.md5 = Some(md5),
+                None => return Err(s3_error!(InvalidDigest, "The Content-MD5 you specified was invalid.")),
+            }
+        }
+
+        let mut enabled = [false; 5];
+        let mut values: [Option<String>; 5] = default();
+        for (i, name) in CHECKSUM_HEADERS.iter().enumerate() {
+            let Some(val) = header_str(headers, name)? else { continue };
+            let (_, len) = checksum_algorithm(name.as_str()).unwrap_or_default();
+            let is_valid = base64_simd::STANDARD.decode_to_vec(val).is_ok_and(|v| v.len() == len);
+            if is_valid.not() {
+                return Err(invalid_request!("Value for {name} header is invalid."));
+            }
+            enabled[i] = true;
+            values[i] = Some(val.to_owned());
+        }
+        if values.iter().flatten().count() > 1 {
+            return Err(invalid_request!(
+                "Expecting a single x-amz-checksum- header. Multiple checksum Types are not allowed."
+            ));
+        }
+
+        for name in [header::X_AMZ_SDK_CHECKSUM_ALGORITHM, header::X_AMZ_CHECKSUM_ALGORITHM] {
+            let Some(val) = header_str(headers, &name)? else { continue };
+            match checksum_algorithm(val) {
+                Some((i, _)) => enabled[i] = true,
+                None => return Err(invalid_request!("Checksum algorithm provided is unsupported.")),
+            }
+        }
+
+        if let Some(val) = header_str(headers, &header::X_AMZ_TRAILER)? {
+            for name in val.split(',').map(str::trim).filter(|name| name.is_empty().not()) {
+                if let Some((i, _)) = checksum_algorithm(name) {
+                    enabled[i] = true;
+                }
+                expected.trailers.push(name.to_ascii_lowercase());
+            }
+        }
+
+        let [crc32, crc32c, crc64nvme, sha1, sha256] = values;
+        expected.checksum = Checksum {
+            checksum_crc32: crc32,
+            checksum_crc32c: crc32c,
+            checksum_crc64nvme: crc64nvme,
+            checksum_sha1: sha1,
+            checksum_sha256: sha256,
+            checksum_type: None,
+        };
+
+        let [crc32, crc32c, crc64nvme, sha1, sha256] = enabled;
+        hasher.crc32 = crc32.then(default);
+        hasher.crc32c = crc32c.then(default);
+        hasher.crc64nvme = crc64nvme.then(default);
+        hasher.sha1 = sha1.then(default);
+        hasher.sha256 = sha256.then(default);
+
+        Ok(expected)
+    }
+}
+
+struct VerifyingStream {
+    inner: Body,
+    md5: Md5,
+    hasher: ChecksumHasher,
+    expected: Expected,
+    trailing_headers: Option<TrailingHeaders>,
+    result: BodyChecksums,
+    /// The most recent chunk, which is yielded after the next chunk arrives or the body is verified
+    pending: Option<Bytes>,
+    done: bool,
+}
+
+impl VerifyingStream {
+    fn finish(&mut self) -> Result<ComputedChecksums, (S3ErrorCode, String)> {
+        let md5 = mem::take(&mut self.md5).finalize();
+        if self.expected.md5.is_some_and(|expected| expected != md5) {
+            let msg = "The Content-MD5 you specified did not match what we received.";
+            return Err((S3ErrorCode::BadDigest, msg.to_owned()));
+        }
+
+        let checksum = mem::take(&mut self.hasher).finalize();
+
+        let received: Vec<String> = self
+            .trailing_headers
+            .as_ref()
+            .and_then(|trailing| trailing.read(|headers| headers.keys().map(|name| name.as_str().to_owned()).collect()))
+            .unwrap_or_default();
+        if let Some(name) = received.iter().find(|name| self.expected.trailers.contains(name).not()) {
+            let msg = format!("The trailing header {name} is not declared in x-amz-trailer.");
+            return Err((S3ErrorCode::InvalidRequest, msg));
+        }
+        let is_missing = |name: &&String| checksum_algorithm(name).is_some() && received.contains(name).not();
+        if let Some(name) = self.expected.trailers.iter().find(is_missing) {
+            let msg = format!("The trailing checksum {name} declared in x-amz-trailer is missing.");
+            return Err((S3ErrorCode::InvalidRequest, msg));
+        }
+
+        let mut expected = mem::take(&mut self.expected.checksum);
+        if let Some(trailing) = self.trailing_headers.as_ref().and_then(TrailingHeaders::checksum) {
+            expected.checksum_crc32 = expected.checksum_crc32.or(trailing.checksum_crc32);
+            expected.checksum_crc32c = expected.checksum_crc32c.or(trailing.checksum_crc32c);
+            expected.checksum_crc64nvme = expected.checksum_crc64nvme.or(trailing.checksum_crc64nvme);
+            expected.checksum_sha1 = expected.checksum_sha1.or(trailing.checksum_sha1);
+            expected.checksum_sha256 = expected.checksum_sha256.or(trailing.checksum_sha256);
+        }
+
+        let fields = checksum_fields(&checksum).into_iter().zip(checksum_fields(&expected));
+        for (name, (computed, expected)) in CHECKSUM_HEADERS.iter().zip(fields) {
+            let is_mismatched = matches!((computed, expected), (Some(c), Some(e)) if c != e.trim());
+            if is_mismatched {
+                let msg = format!("The {name} you specified did not match the calculated checksum.");
+                return Err((S3ErrorCode::BadDigest, msg));
+            }
+        }
+
+        Ok(ComputedChecksums { md5, checksum })
+    }
+}
+
+impl Stream for VerifyingStream {
+    type Item = Result<Bytes, StdError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.done {
+            return Poll::Ready(this.pending.take().map(Ok));
+        }
+        loop {
+            match std::task::ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(bytes)) => {
+                    if bytes.is_empty() {
+                        continue;
+                    }
+                    this.md5.update(&bytes);
+                    this.hasher.update(&bytes);
+                    if let Some(prev) = this.pending.replace(bytes) {
+                        return Poll::Ready(Some(Ok(prev)));
+                    }
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => {
+                    this.done = true;
+                    let result = this.finish();
+                    let mut state = this.result.lock();
+                    return match result {
+                        Ok(checksums) => {
+                            state.checksums = Some(checksums);
+                            Poll::Ready(this.pending.take().map(Ok))
+                        }
+                        Err((code, msg)) => {
+                            this.pending = None;
+                            let err = S3Error::with_message(code.clone(), msg.clone());
+                            state.error = Some((code, msg));
+                            Poll::Ready(Some(Err(Box::new(err))))
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl ByteStream for VerifyingStream {
+    fn remaining_length(&self) -> RemainingLength {
+        let pending = self.pending.as_ref().map_or(0, Bytes::len);
+        self.inner.remaining_length().saturating_add(pending)
+    }
+}
+
+/// Wraps a request body to compute its checksums and verify them against the values sent by the client.
+pub(crate) fn verify_body(
+    body: Body,
+    headers: &HeaderMap,
+    trailing_headers: Option<TrailingHeaders>,
+) -> S3Result<(Body, BodyChecksums)> {
+    let mut hasher = ChecksumHasher::default();
+    let expected = Expected::parse(headers, &mut hasher)?;
+    let result = BodyChecksums::default();
+    let stream = VerifyingStream {
+        inner: body,
+        md5: Md5::default(),
+        hasher,
+        expected,
+        trailing_headers,
+        result: result.clone(),
+        pending: None,
+        done: false,
+    };
+    let stream: DynByteStream = Box::pin(stream);
+    Ok((Body::from(stream), result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+
+    const DATA: &[u8] = b"hello world";
+
+    fn base64(data: &[u8]) -> String {
+        base64_simd::STANDARD.encode_to_string(data)
+    }
+
+    fn headers(pairs: &[(HeaderName, &str)]) -> HeaderMap {
+        pairs.iter().map(|(name, val)| (name.clone(), val.parse().unwrap())).collect()
+    }
+
+    async fn consume(body: Body) -> Result<usize, StdError> {
+        let mut body = body;
+        let mut len = 0;
+        while let Some(bytes) = body.next().await {
+            len += bytes?.len();
+        }
+        Ok(len)
+    }
+
+    fn error_code(err: &StdError) -> Option<&S3ErrorCode> {
+        err.downcast_ref::<S3Error>().map(S3Error::code)
+    }
+
+    #[tokio::test]
+    async fn verified() {
+        let md5 = Md5::checksum(DATA);
+        let crc32 = base64(&Crc32::checksum(DATA));
+        let hs = headers(&[
+            (header::CONTENT_MD5, &base64(&md5)),
+            (header::X_AMZ_CHECKSUM_CRC32, &crc32),
+            (header::X_AMZ_SDK_CHECKSUM_ALGORITHM, "SHA256"),
+        ]);
+
+        let (body, checksums) = verify_body(Body::from(DATA.to_vec()), &hs, None).unwrap();
+        assert!(checksums.get().is_none());
+        assert_eq!(consume(body).await.unwrap(), DATA.len());
+
+        let computed = checksums.get().unwrap();
+        assert_eq!(computed.md5, md5);
+        assert_eq!(computed.checksum.checksum_crc32, Some(crc32));
+        assert_eq!(computed.checksum.checksum_sha256, Some(base64(Sha256::checksum(DATA).as_ref())));
+        assert!(computed.checksum.checksum_crc32c.is_none());
+        assert!(checksums.error().is_none());
+    }
+
+    #[tokio::test]
+    async fn mismatch() {
+        let wrong_md5 = base64(&Md5::checksum(b"hello"));
+        let wrong_crc32 = base64(&Crc32::checksum(b"hello"));
+
+        for hs in [
+            headers(&[(header::CONTENT_MD5, &wrong_md5)]),
+            headers(&[(header::X_AMZ_CHECKSUM_CRC32, &wrong_crc32)]),
+        ] {
+            let (body, checksums) = verify_body(Body::from(DATA.to_vec()), &hs, None).unwrap();
+            let err = consume(body).await.unwrap_err();
+            assert_eq!(error_code(&err), Some(&S3ErrorCode::BadDigest));
+            assert_eq!(checksums.error().unwrap().code(), &S3ErrorCode::BadDigest);
+            assert!(checksums.get().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn mismatch_before_last_chunk() {
+        let chunks: [Result<Bytes, std::io::Error>; 2] = [Ok(Bytes::from_static(b"hello ")), Ok(Bytes::from_static(b"world"))];
+        let wrong_md5 = base64(&Md5::checksum(b"hello"));
+        let hs = headers(&[(header::CONTENT_MD5, &wrong_md5)]);
+
+        let body = Body::from(crate::dto::StreamingBlob::wrap(futures::stream::iter(chunks)));
+        let (mut body, _) = verify_body(body, &hs, None).unwrap();
+
+        // A backend which reads exactly content-length bytes never receives the whole body.
+        let mut received = Vec::new();
+        while received.len() < DATA.len() {
+            match body.next().await.unwrap() {
+                Ok(bytes) => received.extend_from_slice(&bytes),
+                Err(err) => {
+                    assert_eq!(error_code(&err), Some(&S3ErrorCode::BadDigest));
+                    break;
+                }
+            }
+        }
+        assert_eq!(received, b"hello ");
+    }
+
+    #[tokio::test]
+    async fn trailing() {
+        let hs = headers(&[(header::X_AMZ_TRAILER, "x-amz-checksum-crc32c")]);
+
+        for (value, ok) in [
+            (base64(&Crc32c::checksum(DATA)), true),
+            (base64(&Crc32c::checksum(b"hello")), false),
+        ] {
+            let trailers = TrailingHeaders::new();
+            trailers.set(headers(&[(header::X_AMZ_CHECKSUM_CRC32C, &value)]));
+
+            let (body, checksums) = verify_body(Body::from(DATA.to_vec()), &hs, Some(trailers)).unwrap();
+            assert_eq!(consume(body).await.is_ok(), ok);
+            assert_eq!(checksums.get().is_some(), ok);
+        }
+    }
+
+    #[tokio::test]
+    async fn trailing_mismatched_names() {
+        let crc32c = base64(&Crc32c::checksum(DATA));
+        let cases = [
+            // declared but missing
+            (Some("x-amz-checksum-crc32c"), None),
+            (Some("x-amz-checksum-crc32c"), Some(headers(&[]))),
+            // received but not declared
+            (None, Some(headers(&[(header::X_AMZ_CHECKSUM_CRC32C, &crc32c)]))),
+            (Some("x-amz-checksum-sha256"), Some(headers(&[(header::X_AMZ_CHECKSUM_CRC32C, &crc32c)]))),
+        ];
+        for (declared, received) in cases {
+            let hs = headers(&declared.map(|v| (header::X_AMZ_TRAILER, v)).into_iter().collect::<Vec<_>>());
+            let trailers = received.map(|received| {
+                let trailers = TrailingHeaders::new();
+                trailers.set(received);
+                trailers
+            });
+
+            let (body, checksums) = verify_body(Body::from(DATA.to_vec()), &hs, trailers).unwrap();
+            let err = consume(body).await.unwrap_err();
+            assert_eq!(error_code(&err), Some(&S3ErrorCode::InvalidRequest), "{declared:?}");
+            assert!(checksums.get().is_none());
+        }
+    }
+
+    #[test]
+    fn invalid_headers() {
+        let cases = [
+            (headers(&[(header::CONTENT_MD5, "not-base64")]), S3ErrorCode::InvalidDigest),
+            (headers(&[(header::X_AMZ_CHECKSUM_CRC32, "AAAA")]), S3ErrorCode::InvalidRequest),
+            (headers(&[(header::X_AMZ_SDK_CHECKSUM_ALGORITHM, "MD5")]), S3ErrorCode::InvalidRequest),
+            (
+                headers(&[
+                    (header::X_AMZ_CHECKSUM_CRC32, "AAAAAA=="),
+                    (header::X_AMZ_CHECKSUM_CRC32C, "AAAAAA=="),
+                ]),
+                S3ErrorCode::InvalidRequest,
+            ),
+        ];
+        for (hs, code) in cases {
+            let err = verify_body(Body::empty(), &hs, None).unwrap_err();
+            assert_eq!(err.code(), &code, "{hs:?}");
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Conditional requests
+//!
+//! [`Preconditions`] evaluates the `If-Match`, `If-None-Match`, `If-Modified-Since` and `If-Unmodified-Since`
+//! headers of a request against the current state of an object.
+//!
+//! + Reads (`GetObject`, `HeadObject`) fail with `304 Not Modified` or `412 Precondition Failed`.
+//! + Copy sources (`CopyObject`, `UploadPartCopy`) fail with `412 Precondition Failed`.
+//! + Conditional writes (`PutObject`, `CompleteMultipartUpload`) fail with `412 Precondition Failed`
+//!   if `If-None-Match: *` is given and the object exists, or if `If-Match` does not match the existing object.
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/userguide/conditional-requests.html>
+
+use crate::S3Result;
+use crate::dto::{
+    CompleteMultipartUploadInput, CopyObjectInput, GetObjectInput, HeadObjectInput, PutObjectInput, Timestamp, TimestampFormat,
+    UploadPartCopyInput,
+};
+
+use std::ops::Not;
+
+use hyper::HeaderMap;
+use hyper::header::{ETAG, HeaderValue, LAST_MODIFIED};
+
+/// The result of evaluating preconditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluation {
+    /// All preconditions are satisfied.
+    Proceed,
+    /// The object has not been modified. (304)
+    NotModified,
+    /// A precondition is not satisfied. (412)
+    PreconditionFailed,
+}
+
+/// The conditional headers of a request
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Preconditions<'a> {
+    pub if_match: Option<&'a str>,
+    pub if_none_match: Option<&'a str>,
+    pub if_modified_since: Option<&'a Timestamp>,
+    pub if_unmodified_since: Option<&'a Timestamp>,
+}
+
+impl Preconditions<'_> {
+    /// Evaluates the preconditions against an existing object.
+    ///
+    /// The order follows RFC 9110:
+    /// a satisfied `If-Match` overrides `If-Unmodified-Since`,
+    /// and a present `If-None-Match` overrides `If-Modified-Since`.
+    ///
+    /// See <https://www.rfc-editor.org/rfc/rfc9110#section-13.2.2>
+    #[must_use]
+    pub fn evaluate(&self, e_tag: &str, last_modified: &Timestamp) -> Evaluation {
+        let modified_since = |since: &Timestamp| unix_secs(last_modified) > unix_secs(since);
+
+        let precondition_failed = match (self.if_match, self.if_unmodified_since) {
+            (Some(if_match), _) => etag_list_contains(if_match, e_tag).not(),
+            (None, Some(since)) => modified_since(since),
+            (None, None) => false,
+        };
+        if precondition_failed {
+            return Evaluation::PreconditionFailed;
+        }
+
+        let not_modified = match (self.if_none_match, self.if_modified_since) {
+            (Some(if_none_match), _) => etag_list_contains(if_none_match, e_tag),
+            (None, Some(since)) => modified_since(since).not(),
+            (None, None) => false,
+        };
+        if not_modified {
+            return Evaluation::NotModified;
+        }
+
+        Evaluation::Proceed
+    }
+
+    /// Checks the preconditions of a read request.
+    ///
+    /// # Errors
+    /// Returns `NotModified` with the `ETag` and `Last-Modified` headers,
+    /// or `PreconditionFailed`.
+    pub fn check_read(&self, e_tag: &str, last_modified: &Timestamp) -> S3Result {
+        match self.evaluate(e_tag, last_modified) {
+            Evaluation::Proceed => Ok(()),
+            Evaluation::NotModified => {
+                let mut err = s3_error!(NotModified);
+                err.set_headers(not_modified_headers(e_tag, last_modified));
+                Err(err)
+            }
+            Evaluation::PreconditionFailed => Err(s3_error!(PreconditionFailed)),
+        }
+    }
+
+    /// Checks the preconditions of a copy source.
+    ///
+    /// # Errors
+    /// Returns `PreconditionFailed` if any precondition is not satisfied.
+    pub fn check_copy_source(&self, e_tag: &str, last_modified: &Timestamp) -> S3Result {
+        match self.evaluate(e_tag, last_modified) {
+            Evaluation::Proceed => Ok(()),
+            Evaluation::NotModified | Evaluation::PreconditionFailed => Err(s3_error!(PreconditionFailed)),
+        }
+    }
+
+    /// Checks the preconditions of a conditional write.
+    ///
+    /// `current_e_tag` is the `ETag` of the current object, or `None` if the object does not exist.
+    ///
+    /// # Errors
+    /// + `NotImplemented` if `If-None-Match` is not `*`.
+    /// + `PreconditionFailed` if `If-None-Match: *` is given and the object exists.
+    /// + `NoSuchKey` if `If-Match` is given and the object does not exist.
+    /// + `PreconditionFailed` if `If-Match` does not match the object.
+    pub fn check_write(&self, current_e_tag: Option<&str>) -> S3Result {
+        if let Some(if_none_match) = self.if_none_match {
+            if if_none_match.trim() != "*" {
+                return Err(s3_error!(NotImplemented, "If-None-Match only supports `*` for write requests"));
+            }
+            if current_e_tag.is_some() {
+                return Err(s3_error!(PreconditionFailed));
+            }
+        }
+
+        if let Some(if_match) = self.if_match {
+            let Some(e_tag) = current_e_tag else { return Err(s3_error!(NoSuchKey)) };
+            if etag_list_contains(if_match, e_tag).not() {
+                return Err(s3_error!(PreconditionFailed));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a GetObjectInput> for Preconditions<'a> {
+    fn from(input: &'a GetObjectInput) -> Self {
+        Self {
+            if_match: input.if_match.as_deref(),
+            if_none_match: input.if_none_match.as_deref(),
+            if_modified_since: input.if_modified_since.as_ref(),
+            if_unmodified_since: input.if_unmodified_since.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a HeadObjectInput> for Preconditions<'a> {
+    fn from(input: &'a HeadObjectInput) -> Self {
+        Self {
+            if_match: input.if_match.as_deref(),
+            if_none_match: input.if_none_match.as_deref(),
+            if_modified_since: input.if_modified_since.as_ref(),
+            if_unmodified_since: input.if_unmodified_since.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a PutObjectInput> for Preconditions<'a> {
+    fn from(input: &'a PutObjectInput) -> Self {
+        Self {
+            if_match: input.if_match.as_deref(),
+            if_none_match: input.if_none_match.as_deref(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> From<&'a CompleteMultipartUploadInput> for Preconditions<'a> {
+    fn from(input: &'a CompleteMultipartUploadInput) -> Self {
+        Self {
+            if_match: input.if_match.as_deref(),
+            if_none_match: input.if_none_match.as_deref(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The preconditions of the copy source
+impl<'a> From<&'a CopyObjectInput> for Preconditions<'a> {
+    fn from(input: &'a CopyObjectInput) -> Self {
+        Self {
+            if_match: input.copy_source_if_match.as_deref(),
+            if_none_match: input.copy_source_if_none_match.as_deref(),
+            if_modified_since: input.copy_source_if_modified_since.as_ref(),
+            if_unmodified_since: input.copy_source_if_unmodified_since.as_ref(),
+        }
+    }
+}
+
+/// The preconditions of the copy source
+impl<'a> From<&'a UploadPartCopyInput> for Preconditions<'a> {
+    fn from(input: &'a UploadPartCopyInput) -> Self {
+        Self {
+            if_match: input.copy_source_if_match.as_deref(),
+            if_none_match: input.copy_source_if_none_match.as_deref(),
+            if_modified_since: input.copy_source_if_modified_since.as_ref(),
+            if_unmodified_since: input.copy_source_if_unmodified_since.as_ref(),
+        }
+    }
+}
+
+/// Removes the quotes and the weak indicator of an entity tag.
+fn opaque_tag(s: &str) -> &str {
+    let s = s.trim();
+    let s = s.strip_prefix("W/").unwrap_or(s);
+    s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s)
+}
+
+/// Checks whether a comma-separated list of entity tags (or `*`) matches the entity tag.
+///
+/// Weak comparison is used, as S3 does not issue weak entity tags.
+fn etag_list_contains(list: &str, e_tag: &str) -> bool {
+    if list.trim() == "*" {
+        return true;
+    }
+    let e_tag = opaque_tag(e_tag);
+    list.split(',').any(|s| opaque_tag(s) == e_tag)
+}
+
+/// HTTP dates have a precision of one second.
+fn unix_secs(t: &Timestamp) -> i64 {
+    time::OffsetDateTime::from(t.clone()).unix_timestamp()
+}
+
+fn not_modified_headers(e_tag: &str, last_modified: &Timestamp) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(val) = HeaderValue::try_from(e_tag) {
+        headers.insert(ETAG, val);
+    }
+    let mut buf = Vec::new();
+    let val = last_modified.format(TimestampFormat::HttpDate, &mut buf).ok();
+    if let Some(val) = val.and_then(|()| HeaderValue::try_from(buf).ok()) {
+        headers.insert(LAST_MODIFIED, val);
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::S3ErrorCode;
+
+    fn ts(s: &str) -> Timestamp {
+        Timestamp::parse(TimestampFormat::HttpDate, s).unwrap()
+    }
+
+    #[test]
+    fn evaluate() {
+        let e_tag = "\"abc\"";
+        let last_modified = ts("Wed, 21 Oct 2015 07:28:00 GMT");
+        let before = ts("Tue, 20 Oct 2015 07:28:00 GMT");
+        let after = ts("Thu, 22 Oct 2015 07:28:00 GMT");
+
+        let eval = |p: Preconditions<'_>| p.evaluate(e_tag, &last_modified);
+
+        assert_eq!(eval(Preconditions::default()), Evaluation::Proceed);
+
+        let p = Preconditions {
+            if_match: Some("\"xyz\", \"abc\""),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+        let p = Preconditions {
+            if_match: Some("\"xyz\""),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::PreconditionFailed);
+        let p = Preconditions {
+            if_match: Some("*"),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+
+        let p = Preconditions {
+            if_none_match: Some("abc"),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::NotModified);
+        let p = Preconditions {
+            if_none_match: Some("W/\"xyz\""),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+
+        let p = Preconditions {
+            if_modified_since: Some(&last_modified),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::NotModified);
+        let p = Preconditions {
+            if_modified_since: Some(&before),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+
+        let p = Preconditions {
+            if_unmodified_since: Some(&before),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::PreconditionFailed);
+        let p = Preconditions {
+            if_unmodified_since: Some(&after),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+
+        // If-Match is true and If-Unmodified-Since is false
+        let p = Preconditions {
+            if_match: Some(e_tag),
+            if_unmodified_since: Some(&before),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+
+        // If-None-Match is true and If-Modified-Since is false
+        let p = Preconditions {
+            if_none_match: Some("\"xyz\""),
+            if_modified_since: Some(&after),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::Proceed);
+
+        // If-None-Match is false and If-Modified-Since is true
+        let p = Preconditions {
+            if_none_match: Some(e_tag),
+            if_modified_since: Some(&before),
+            ..Default::default()
+        };
+        assert_eq!(eval(p), Evaluation::NotModified);
+    }
+
+    #[test]
+    fn check_read() {
+        let last_modified = ts("Wed, 21 Oct 2015 07:28:00 GMT");
+        let p = Preconditions {
+            if_none_match: Some("\"abc\""),
+            ..Default::default()
+        };
+
+        let err = p.check_read("\"abc\"", &last_modified).unwrap_err();
+        assert_eq!(err.code(), &S3ErrorCode::NotModified);
+        let headers = err.headers().unwrap();
+        assert_eq!(headers[ETAG], "\"abc\"");
+        assert_eq!(headers[LAST_MODIFIED], "Wed, 21 Oct 2015 07:28:00 GMT");
+
+        let err = p.check_copy_source("\"abc\"", &last_modified).unwrap_err();
+        assert_eq!(err.code(), &S3ErrorCode::PreconditionFailed);
+    }
+
+    #[test]
+    fn check_write() {
+        let create = Preconditions {
+            if_none_match: Some("*"),
+            ..Default::default()
+        };
+        assert!(create.check_write(None).is_ok());
+        let err = create.check_write(Some("\"abc\"")).unwrap_err();
+        assert_eq!(err.code(), &S3ErrorCode::PreconditionFailed);
+
+        let replace = Preconditions {
+            if_match: Some("\"abc\""),
+            ..Default::default()
+        };
+        assert!(replace.check_write(Some("\"abc\"")).is_ok());
+        let err = replace.check_write(Some("\"xyz\"")).unwrap_err();
+        assert_eq!(err.code(), &S3ErrorCode::PreconditionFailed);
+        let err = replace.check_write(None).unwrap_err();
+        assert_eq!(err.code(), &S3ErrorCode::NoSuchKey);
+
+        let p = Preconditions {
+            if_none_match: Some("\"abc\""),
+            ..Default::default()
+        };
+        let err = p.check_write(None).unwrap_err();
+        assert_eq!(err.code(), &S3ErrorCode::NotImplemented);
+    }
+}
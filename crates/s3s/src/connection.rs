@@ -0,0 +1,297 @@
+//! Client connections
+//!
+//! A server inserts a [`ConnectionInfo`] into the extensions of every request,
+//! or wraps the service with [`SharedS3Service::with_connection`](crate::service::SharedS3Service::with_connection)
+//! for every accepted connection.
+//! [`MakeConnectionService`](crate::service::MakeConnectionService) does the latter for any [`Connected`] target.
+//!
+//! The client IP of a request is the peer address of its connection.
+//! If the peer is a trusted proxy, the client IP is taken from the `Forwarded` or `X-Forwarded-For` header instead.
+
+use crate::tls::TlsInfo;
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr, SocketAddrV4};
+use std::ops::Not;
+use std::str::FromStr;
+
+use hyper::HeaderMap;
+use hyper::header::{FORWARDED, HeaderName};
+
+/// Information about the connection of a request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The address of the remote peer
+    pub peer_addr: Option<SocketAddr>,
+    /// The local address which accepted the connection
+    pub local_addr: Option<SocketAddr>,
+    /// The TLS session, or `None` for plain HTTP connections
+    pub tls: Option<TlsInfo>,
+}
+
+/// A connection target which knows its connection information
+///
+/// [`MakeConnectionService`](crate::service::MakeConnectionService) reads the connection information
+/// from the target of every accepted connection.
+pub trait Connected {
+    /// Returns the connection information.
+    fn connection_info(&self) -> ConnectionInfo;
+}
+
+impl Connected for ConnectionInfo {
+    fn connection_info(&self) -> ConnectionInfo {
+        self.clone()
+    }
+}
+
+/// The address of the remote peer
+impl Connected for SocketAddr {
+    fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            peer_addr: Some(*self),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+impl Connected for tokio::net::TcpStream {
+    fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            peer_addr: self.peer_addr().ok(),
+            local_addr: self.local_addr().ok(),
+            tls: None,
+        }
+    }
+}
+
+impl<T: Connected + ?Sized> Connected for &T {
+    fn connection_info(&self) -> ConnectionInfo {
+        T::connection_info(self)
+    }
+}
+
+/// An IP network, such as `10.0.0.0/8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpCidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("The IP network is invalid")]
+pub struct InvalidIpCidr(());
+
+impl IpCidr {
+    /// Creates an IP network from an address and a prefix length.
+    ///
+    /// # Errors
+    /// Returns an error if the prefix length is greater than the length of the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, InvalidIpCidr> {
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix_len > max_len {
+            return Err(InvalidIpCidr(()));
+        }
+        Ok(Self { addr, prefix_len })
+    }
+
+    #[must_use]
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    #[must_use]
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Checks whether the network contains the address.
+    ///
+    /// IPv4-mapped IPv6 addresses are treated as IPv4 addresses.
+    #[must_use]
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix_len)).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix_len)).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<IpAddr> for IpCidr {
+    fn from(addr: IpAddr) -> Self {
+        let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix_len }
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Parses an address with an optional prefix length, such as `10.0.0.0/8`, `192.0.2.1` or `2001:db8::/32`.
+impl FromStr for IpCidr {
+    type Err = InvalidIpCidr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| InvalidIpCidr(()))?;
+        match prefix_len {
+            Some(len) => Self::new(addr, len.parse().map_err(|_| InvalidIpCidr(()))?),
+            None => Ok(Self::from(addr)),
+        }
+    }
+}
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Parses a node of the `Forwarded` header or an address of the `X-Forwarded-For` header.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc7239#section-6>
+fn parse_node(s: &str) -> Option<IpAddr> {
+    let s = s.trim().trim_matches('"');
+    if let Some(rest) = s.strip_prefix('[') {
+        let (ip, _) = rest.split_once(']')?;
+        return ip.parse().ok();
+    }
+    if let Ok(ip) = s.parse() {
+        return Some(ip);
+    }
+    s.parse::<SocketAddrV4>().ok().map(|addr| IpAddr::V4(*addr.ip()))
+}
+
+/// Returns the forwarded chain of addresses, from the original client to the nearest proxy.
+///
+/// The `Forwarded` header takes precedence over the `X-Forwarded-For` header.
+/// An unrecognized node is returned as `None`.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let values = |name| {
+        headers
+            .get_all(name)
+            .into_iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+    };
+
+    let forwarded: Vec<_> = values(FORWARDED)
+        .map(|element| {
+            let node = element.split(';').find_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                name.trim().eq_ignore_ascii_case("for").then_some(value)
+            });
+            node.and_then(parse_node)
+        })
+        .collect();
+    if forwarded.is_empty().not() {
+        return forwarded;
+    }
+
+    values(X_FORWARDED_FOR).map(parse_node).collect()
+}
+
+/// Resolves the client IP of a request.
+///
+/// Starting from the peer, the forwarded addresses are walked from right to left
+/// as long as the current hop is a trusted proxy.
+pub(crate) fn resolve_client_ip(peer_ip: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpCidr]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+
+    let mut client_ip = peer_ip;
+    if is_trusted(client_ip).not() {
+        return client_ip;
+    }
+
+    for node in forwarded_chain(headers).into_iter().rev() {
+        let Some(ip) = node else { break };
+        client_ip = ip;
+        if is_trusted(client_ip).not() {
+            break;
+        }
+    }
+    client_ip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hyper::header::HeaderValue;
+
+    fn cidr(s: &str) -> IpCidr {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ip_cidr() {
+        let net = cidr("10.1.0.0/16");
+        assert!(net.contains(ip("10.1.2.3")));
+        assert!(net.contains(ip("::ffff:10.1.2.3")));
+        assert!(net.contains(ip("10.2.0.0")).not());
+        assert!(net.contains(ip("2001:db8::1")).not());
+        assert_eq!(net.to_string(), "10.1.0.0/16");
+
+        assert!(cidr("0.0.0.0/0").contains(ip("192.0.2.1")));
+        assert!(cidr("2001:db8::/32").contains(ip("2001:db8:1::1")));
+        assert!(cidr("192.0.2.1").contains(ip("192.0.2.1")));
+        assert!(cidr("192.0.2.1").contains(ip("192.0.2.2")).not());
+
+        for s in ["", "10.0.0.0/33", "::/129", "10.0.0.0/", "localhost"] {
+            assert!(s.parse::<IpCidr>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn client_ip() {
+        let trusted = [cidr("10.0.0.0/8")];
+        let headers = |pairs: &[(&str, &str)]| {
+            let mut map = HeaderMap::new();
+            for &(name, value) in pairs {
+                map.append(HeaderName::from_bytes(name.as_bytes()).unwrap(), HeaderValue::from_str(value).unwrap());
+            }
+            map
+        };
+
+        let xff = headers(&[("x-forwarded-for", "203.0.113.7, 10.0.0.2")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &xff, &trusted), ip("203.0.113.7"));
+        assert_eq!(resolve_client_ip(ip("198.51.100.1"), &xff, &trusted), ip("198.51.100.1"));
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &xff, &[]), ip("10.0.0.1"));
+
+        // Spoofed addresses before an untrusted hop are ignored.
+        let xff = headers(&[("x-forwarded-for", "192.0.2.1"), ("x-forwarded-for", "203.0.113.7")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &xff, &trusted), ip("203.0.113.7"));
+
+        let fwd = headers(&[
+            ("forwarded", r#"for="[2001:db8:cafe::17]:4711";proto=https, for=10.0.0.3:80"#),
+            ("x-forwarded-for", "192.0.2.1"),
+        ]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &fwd, &trusted), ip("2001:db8:cafe::17"));
+
+        let unknown = headers(&[("forwarded", "for=unknown, for=10.0.0.3")]);
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &unknown, &trusted), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn connected() {
+        let addr: SocketAddr = "192.0.2.1:4711".parse().unwrap();
+        let info = addr.connection_info();
+        assert_eq!(info.peer_addr, Some(addr));
+        assert!(info.tls.is_none());
+        assert_eq!(info.connection_info(), info);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::error::S3Result;
 
 use std::borrow::Cow;
+use std::net::Ipv6Addr;
+use std::ops::Not;
 
 use stdx::default::default;
 
@@ -8,7 +10,7 @@ use stdx::default::default;
 pub struct VirtualHost<'a> {
     domain: Cow<'a, str>,
     bucket: Option<Cow<'a, str>>,
-    // pub(crate) region: Option<Cow<'a, str>>,
+    region: Option<Cow<'a, str>>,
 }
 
 impl<'a> VirtualHost<'a> {
@@ -16,6 +18,7 @@ impl<'a> VirtualHost<'a> {
         Self {
             domain: domain.into(),
             bucket: None,
+            region: None,
         }
     }
 
@@ -23,9 +26,16 @@ impl<'a> VirtualHost<'a> {
         Self {
             domain: domain.into(),
             bucket: Some(bucket.into()),
+            region: None,
         }
     }
 
+    #[must_use]
+    pub fn with_region(mut self, region: impl Into<Cow<'a, str>>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
     #[inline]
     #[must_use]
     pub fn domain(&self) -> &str {
@@ -37,11 +47,29 @@ impl<'a> VirtualHost<'a> {
     pub fn bucket(&self) -> Option<&str> {
         self.bucket.as_deref()
     }
+
+    #[inline]
+    #[must_use]
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    #[must_use]
+    pub fn into_owned(self) -> VirtualHost<'static> {
+        VirtualHost {
+            domain: Cow::Owned(self.domain.into_owned()),
+            bucket: self.bucket.map(|s| Cow::Owned(s.into_owned())),
+            region: self.region.map(|s| Cow::Owned(s.into_owned())),
+        }
+    }
 }
 
 pub trait S3Host: Send + Sync + 'static {
     /// Parses the `Host` header of the HTTP request.
     ///
+    /// The region of the virtual host is checked against the credential scope of `SigV4` requests.
+    /// The parsed virtual host is stored in the request extensions as `VirtualHost<'static>`.
+    ///
     /// # Errors
     /// Returns an error if the `Host` is invalid for this service.
     fn parse_host_header<'a>(&'a self, host: &'a str) -> S3Result<VirtualHost<'a>>;
@@ -57,6 +85,9 @@ pub enum DomainError {
 
     #[error("No base domains are specified")]
     ZeroDomains,
+
+    #[error("The host pattern is invalid")]
+    InvalidPattern,
 }
 
 /// Naive check for a valid domain.
@@ -201,6 +232,214 @@ impl S3Host for MultiDomain {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Label {
+    Literal(String),
+    Bucket,
+    Region,
+}
+
+/// A parsed host pattern
+#[derive(Debug)]
+struct HostPattern {
+    labels: Vec<Label>,
+    port: Option<String>,
+    region: Option<String>,
+}
+
+/// Splits off the port after the last colon outside of an IPv6 literal like `[::1]:8014`.
+fn split_port(host: &str) -> (&str, Option<&str>) {
+    match host.rsplit_once(':') {
+        Some((name, port)) if port.contains(']').not() => (name, Some(port)),
+        _ => (host, None),
+    }
+}
+
+fn is_ipv6_literal(s: &str) -> bool {
+    let addr = s.strip_prefix('[').and_then(|s| s.strip_suffix(']'));
+    addr.is_some_and(|a| a.parse::<Ipv6Addr>().is_ok())
+}
+
+/// Splits the host name into labels. An IPv6 literal is a single label.
+fn split_labels(name: &str) -> Vec<&str> {
+    if is_ipv6_literal(name) {
+        vec![name]
+    } else {
+        name.split('.').collect()
+    }
+}
+
+fn is_valid_label(s: &str) -> bool {
+    s.is_empty().not() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+impl HostPattern {
+    fn parse(pattern: &str, region: Option<&str>) -> Result<Self, DomainError> {
+        let (name, port) = split_port(pattern);
+        if port.is_some_and(|p| p.parse::<u16>().is_err()) {
+            return Err(DomainError::InvalidPattern);
+        }
+        if region.is_some_and(|r| is_valid_label(r).not()) {
+            return Err(DomainError::InvalidPattern);
+        }
+
+        let mut labels = Vec::new();
+        for part in split_labels(name) {
+            let label = match part {
+                "{bucket}" => Label::Bucket,
+                "{region}" => Label::Region,
+                _ if is_valid_label(part) || is_ipv6_literal(part) => Label::Literal(part.to_ascii_lowercase()),
+                _ => return Err(DomainError::InvalidPattern),
+            };
+            if matches!(label, Label::Literal(_)).not() && labels.contains(&label) {
+                return Err(DomainError::InvalidPattern);
+            }
+            labels.push(label);
+        }
+
+        if labels.iter().all(|l| matches!(l, Label::Literal(_)).not()) {
+            return Err(DomainError::InvalidPattern);
+        }
+        if region.is_some() && labels.contains(&Label::Region) {
+            return Err(DomainError::InvalidPattern);
+        }
+
+        Ok(Self {
+            labels,
+            port: port.map(ToOwned::to_owned),
+            region: region.map(ToOwned::to_owned),
+        })
+    }
+
+    fn matches<'a>(&'a self, host: &'a str) -> Option<VirtualHost<'a>> {
+        let (name, port) = split_port(host);
+        if self.port.is_some() && port != self.port.as_deref() {
+            return None;
+        }
+
+        let parts = split_labels(name);
+
+        // `{bucket}` matches one or more labels and others match exactly one label
+        let bucket_len = if self.labels.contains(&Label::Bucket) {
+            parts.len().checked_sub(self.labels.len() - 1).filter(|&n| n > 0)?
+        } else if parts.len() == self.labels.len() {
+            0
+        } else {
+            return None;
+        };
+
+        let mut bucket = None;
+        let mut region = self.region.as_deref();
+        let mut i = 0;
+        for label in &self.labels {
+            match label {
+                Label::Literal(s) => {
+                    if parts[i].eq_ignore_ascii_case(s).not() {
+                        return None;
+                    }
+                }
+                Label::Region => {
+                    if is_valid_label(parts[i]).not() {
+                        return None;
+                    }
+                    region = Some(parts[i]);
+                }
+                Label::Bucket => {
+                    bucket = Some((i, i + bucket_len));
+                    i += bucket_len;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+
+        let vh = match bucket {
+            Some((start, end)) => {
+                // byte offset of the `i`-th label
+                let offset = |i: usize| parts[..i].iter().map(|p| p.len() + 1).sum::<usize>();
+                let bucket = &name[offset(start)..offset(end) - 1];
+                // the domain is the remaining part after a leading bucket
+                let domain = if start == 0 { &host[offset(end)..] } else { host };
+                VirtualHost::with_bucket(domain, bucket)
+            }
+            None => VirtualHost::new(host),
+        };
+
+        Some(match region {
+            Some(region) => vh.with_region(region),
+            None => vh,
+        })
+    }
+}
+
+/// An [`S3Host`] which matches the `Host` header against patterns
+///
+/// A pattern is a domain name whose labels may be `{bucket}` or `{region}`, for example:
+///
+/// + `{bucket}.s3.{region}.example.com`
+/// + `s3.{region}.example.com`
+/// + `{bucket}.s3-website.example.com`
+///
+/// `{bucket}` matches one or more labels and `{region}` matches exactly one label.
+/// A pattern without `{bucket}` matches path-style requests.
+/// If the pattern has no port, the port of the `Host` header is ignored.
+/// An IPv6 literal such as `[::1]:8014` is matched as a single label.
+///
+/// The patterns are tried in the order they are added.
+/// If no pattern matches and CNAME is enabled, the whole host is used as the bucket name.
+#[derive(Debug, Default)]
+pub struct PatternHost {
+    patterns: Vec<HostPattern>,
+    cname: bool,
+}
+
+impl PatternHost {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pattern.
+    ///
+    /// # Errors
+    /// Returns an error if the pattern is invalid.
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<(), DomainError> {
+        self.patterns.push(HostPattern::parse(pattern, None)?);
+        Ok(())
+    }
+
+    /// Adds a pattern whose requests are in `region`.
+    ///
+    /// # Errors
+    /// Returns an error if the pattern is invalid, or the pattern contains `{region}`.
+    pub fn add_pattern_with_region(&mut self, pattern: &str, region: &str) -> Result<(), DomainError> {
+        self.patterns.push(HostPattern::parse(pattern, Some(region))?);
+        Ok(())
+    }
+
+    /// Sets whether a host matching no pattern is used as the bucket name.
+    pub fn set_cname(&mut self, cname: bool) {
+        self.cname = cname;
+    }
+}
+
+impl S3Host for PatternHost {
+    fn parse_host_header<'a>(&'a self, host: &'a str) -> S3Result<VirtualHost<'a>> {
+        for pattern in &self.patterns {
+            if let Some(vh) = pattern.matches(host) {
+                return Ok(vh);
+            }
+        }
+
+        if self.cname && is_valid_domain(host) {
+            let bucket = host.to_ascii_lowercase();
+            return Ok(VirtualHost::with_bucket(host, bucket));
+        }
+
+        Err(s3_error!(InvalidRequest, "Invalid host header"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,4 +530,87 @@ mod tests {
         assert_eq!(vh.domain(), "example.com");
         assert_eq!(vh.bucket(), Some("example.com.org"));
     }
+
+    #[test]
+    fn split_host_port() {
+        let cases = [
+            ("localhost", ("localhost", None)),
+            ("localhost:8014", ("localhost", Some("8014"))),
+            ("[::1]", ("[::1]", None)),
+            ("[::1]:8014", ("[::1]", Some("8014"))),
+        ];
+        for (host, expected) in cases {
+            assert_eq!(split_port(host), expected, "{host}");
+        }
+    }
+
+    #[test]
+    fn pattern_host() {
+        let mut ph = PatternHost::new();
+        ph.add_pattern("{bucket}.s3.{region}.example.com").unwrap();
+        ph.add_pattern("s3.{region}.example.com").unwrap();
+        ph.add_pattern_with_region("{bucket}.s3-website.example.com", "us-east-1")
+            .unwrap();
+        ph.add_pattern("localhost:8014").unwrap();
+        ph.add_pattern("[::1]:8014").unwrap();
+
+        let cases = [
+            (
+                "my.bucket.s3.us-west-2.example.com",
+                "s3.us-west-2.example.com",
+                Some("my.bucket"),
+                Some("us-west-2"),
+            ),
+            ("s3.eu-west-1.example.com", "s3.eu-west-1.example.com", None, Some("eu-west-1")),
+            ("S3.eu-west-1.Example.com:9000", "S3.eu-west-1.Example.com:9000", None, Some("eu-west-1")),
+            ("site.s3-website.example.com", "s3-website.example.com", Some("site"), Some("us-east-1")),
+            ("localhost:8014", "localhost:8014", None, None),
+            ("[::1]:8014", "[::1]:8014", None, None),
+        ];
+        for (host, domain, bucket, region) in cases {
+            let vh = ph.parse_host_header(host).unwrap();
+            assert_eq!(vh.domain(), domain, "{host}");
+            assert_eq!(vh.bucket(), bucket, "{host}");
+            assert_eq!(vh.region(), region, "{host}");
+        }
+
+        for host in [
+            "s3.example.com",
+            "s3.us.west.example.com",
+            "localhost",
+            "localhost:80",
+            "[::1]",
+            "[::1]:80",
+            "[::2]:8014",
+            "cdn.example.org",
+        ] {
+            let err = ph.parse_host_header(host).unwrap_err();
+            assert!(matches!(err.code(), S3ErrorCode::InvalidRequest), "{host}");
+        }
+
+        ph.set_cname(true);
+        let vh = ph.parse_host_header("cdn.example.org").unwrap();
+        assert_eq!(vh.bucket(), Some("cdn.example.org"));
+        assert_eq!(vh.region(), None);
+    }
+
+    #[test]
+    fn pattern_host_invalid() {
+        let mut ph = PatternHost::new();
+        for pattern in [
+            "{bucket}",
+            "{bucket}.{region}",
+            "{bucket}.{bucket}.com",
+            "{key}.example.com",
+            "example.com:",
+            "[::1",
+            "[example.com]:8014",
+        ] {
+            assert_eq!(ph.add_pattern(pattern), Err(DomainError::InvalidPattern), "{pattern}");
+        }
+        let result = ph.add_pattern_with_region("s3.{region}.example.com", "us-east-1");
+        assert_eq!(result, Err(DomainError::InvalidPattern));
+        let result = ph.add_pattern_with_region("s3.example.com", "us.east");
+        assert_eq!(result, Err(DomainError::InvalidPattern));
+    }
 }
@@ -1,19 +1,25 @@
 //! aws-chunked stream
 
+use super::TrailingHeaders;
+
 use crate::auth::SecretKey;
 use crate::error::StdError;
+use crate::region::Region;
 use crate::sig_v4;
 use crate::sig_v4::AmzDate;
 use crate::stream::{ByteStream, DynByteStream, RemainingLength};
 use crate::utils::SyncBoxFuture;
 
 use std::fmt::{self, Debug};
+use std::ops::Not;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures::pin_mut;
 use futures::stream::{Stream, StreamExt};
+use hyper::HeaderMap;
 use hyper::body::{Buf, Bytes};
+use hyper::header::{HeaderName, HeaderValue};
 use memchr::memchr;
 use transform_stream::AsyncTryStream;
 
@@ -33,21 +39,27 @@ impl Debug for AwsChunkedStream {
 
 /// signature ctx
 #[derive(Debug)]
-struct SignatureCtx {
+pub struct SignatureCtx {
     /// date
-    amz_date: AmzDate,
+    pub amz_date: AmzDate,
 
-    /// region
-    region: Box<str>,
+    /// region (`None` for `SigV4a`)
+    pub region: Option<Region>,
 
     //// service
-    service: Box<str>,
+    pub service: Box<str>,
 
     /// secret key
-    secret_key: SecretKey,
+    pub secret_key: SecretKey,
+
+    /// previous chunk's signature (initially the seed signature)
+    pub prev_signature: Box<str>,
 
-    /// previous chunk's signature
-    prev_signature: Box<str>,
+    /// verifying key of `SigV4a`
+    ///
+    /// If it is `Some`, the chunks are signed with `AWS4-ECDSA-P256-SHA256`.
+    #[cfg(feature = "sigv4a")]
+    pub ecdsa_key: Option<p256::ecdsa::VerifyingKey>,
 }
 
 /// [`AwsChunkedStream`]
@@ -72,48 +84,100 @@ struct ChunkMeta<'a> {
     /// chunk size
     size: usize,
     /// chunk signature
-    signature: &'a [u8],
+    signature: Option<&'a [u8]>,
 }
 
 /// nom parser
 fn parse_chunk_meta(mut input: &[u8]) -> nom::IResult<&[u8], ChunkMeta<'_>> {
     use crate::utils::parser::consume;
 
-    use nom::bytes::complete::{tag, take, take_till1};
-    use nom::combinator::{all_consuming, map_res};
+    use nom::bytes::complete::{tag, take_till1};
+    use nom::combinator::{all_consuming, map_res, opt};
     use nom::number::complete::hex_u32;
-    use nom::sequence::delimited;
+    use nom::sequence::{preceded, terminated};
 
     let s = &mut input;
 
-    let size = consume(s, take_till1(|c| c == b';'))?;
-    let (_, size) = map_res(hex_u32, TryInto::try_into)(size)?;
+    let size = consume(s, take_till1(|c| c == b';' || c == b'\r'))?;
+    let (_, size) = map_res(all_consuming(hex_u32), TryInto::try_into)(size)?;
 
-    let signature = consume(s, all_consuming(delimited(tag(b";chunk-signature="), take(64_usize), tag(b"\r\n"))))?;
+    let signature = consume(
+        s,
+        all_consuming(terminated(
+            opt(preceded(tag(b";chunk-signature="), take_till1(|c| c == b'\r'))),
+            tag(b"\r\n"),
+        )),
+    )?;
 
     Ok((input, ChunkMeta { size, signature }))
 }
 
 /// check signature
 fn check_signature(ctx: &SignatureCtx, expected_signature: &[u8], chunk_data: &[Bytes]) -> Option<Box<str>> {
+    #[cfg(feature = "sigv4a")]
+    if let Some(key) = &ctx.ecdsa_key {
+        let expected_signature = std::str::from_utf8(sig_v4::v4a::trim_padded_signature(expected_signature)).ok()?;
+        let string_to_sign =
+            sig_v4::v4a::create_chunk_string_to_sign(&ctx.amz_date, &ctx.service, &ctx.prev_signature, chunk_data);
+        return sig_v4::v4a::verify_signature(key, &string_to_sign, expected_signature).then(|| expected_signature.into());
+    }
+
+    let region = ctx.region.as_ref()?;
     let string_to_sign =
-        sig_v4::create_chunk_string_to_sign(&ctx.amz_date, &ctx.region, &ctx.service, &ctx.prev_signature, chunk_data);
+        sig_v4::create_chunk_string_to_sign(&ctx.amz_date, region, &ctx.service, &ctx.prev_signature, chunk_data);
 
-    let chunk_signature = sig_v4::calculate_signature(&string_to_sign, &ctx.secret_key, &ctx.amz_date, &ctx.region, &ctx.service);
+    let chunk_signature = sig_v4::calculate_signature(&string_to_sign, &ctx.secret_key, &ctx.amz_date, region, &ctx.service);
 
     (chunk_signature.as_bytes() == expected_signature).then(|| chunk_signature.into())
 }
 
+/// check trailer signature
+fn check_trailer_signature(ctx: &SignatureCtx, expected_signature: &[u8], trailers: &[(String, String)]) -> bool {
+    #[cfg(feature = "sigv4a")]
+    if let Some(key) = &ctx.ecdsa_key {
+        let Ok(expected_signature) = std::str::from_utf8(sig_v4::v4a::trim_padded_signature(expected_signature)) else {
+            return false;
+        };
+        let string_to_sign =
+            sig_v4::v4a::create_trailer_string_to_sign(&ctx.amz_date, &ctx.service, &ctx.prev_signature, trailers);
+        return sig_v4::v4a::verify_signature(key, &string_to_sign, expected_signature);
+    }
+
+    let Some(region) = ctx.region.as_ref() else { return false };
+    let string_to_sign =
+        sig_v4::create_trailer_string_to_sign(&ctx.amz_date, region, &ctx.service, &ctx.prev_signature, trailers);
+
+    let trailer_signature = sig_v4::calculate_signature(&string_to_sign, &ctx.secret_key, &ctx.amz_date, region, &ctx.service);
+
+    trailer_signature.as_bytes() == expected_signature
+}
+
+/// parse a trailing header line
+fn parse_trailer_line(line: &[u8]) -> Option<(&str, &str)> {
+    let line = line.strip_suffix(b"\r\n")?;
+    let line = std::str::from_utf8(line).ok()?;
+    let (name, value) = line.split_once(':')?;
+    Some((name.trim(), value.trim()))
+}
+
+/// Trailer section
+struct Trailers {
+    /// trailing headers, excluding `x-amz-trailer-signature`
+    headers: Vec<(String, String)>,
+    /// trailer signature
+    signature: Option<String>,
+}
+
 impl AwsChunkedStream {
     /// Constructs a `ChunkedStream`
+    ///
+    /// If `signature_ctx` is `None`, the chunks are not signed.
+    /// If `trailing_headers` is `Some`, the trailer section is parsed and stored into it.
     pub fn new<S>(
         body: S,
-        seed_signature: Box<str>,
-        amz_date: AmzDate,
-        region: Box<str>,
-        service: Box<str>,
-        secret_key: SecretKey,
+        signature_ctx: Option<SignatureCtx>,
         decoded_content_length: usize,
+        trailing_headers: Option<TrailingHeaders>,
     ) -> Self
     where
         S: Stream<Item = Result<Bytes, StdError>> + Send + Sync + 'static,
@@ -124,13 +188,7 @@ impl AwsChunkedStream {
                 pin_mut!(body);
                 let mut prev_bytes = Bytes::new();
                 let mut buf: Vec<u8> = Vec::new();
-                let mut ctx = SignatureCtx {
-                    amz_date,
-                    region,
-                    service,
-                    secret_key,
-                    prev_signature: seed_signature,
-                };
+                let mut signature_ctx = signature_ctx;
 
                 loop {
                     let meta = {
@@ -146,7 +204,11 @@ impl AwsChunkedStream {
                         }
                     };
 
-                    let data: Vec<Bytes> = {
+                    let is_last_chunk_with_trailer = meta.size == 0 && trailing_headers.is_some();
+
+                    let data: Vec<Bytes> = if is_last_chunk_with_trailer {
+                        Vec::new()
+                    } else {
                         match Self::read_data(body.as_mut(), prev_bytes, meta.size).await {
                             None => return Err(AwsChunkedStreamError::Incomplete),
                             Some(Err(e)) => return Err(e),
@@ -157,9 +219,38 @@ impl AwsChunkedStream {
                         }
                     };
 
-                    match check_signature(&ctx, meta.signature, &data) {
-                        None => return Err(AwsChunkedStreamError::SignatureMismatch),
-                        Some(signature) => ctx.prev_signature = signature,
+                    if let Some(ctx) = &mut signature_ctx {
+                        let Some(expected_signature) = meta.signature else {
+                            return Err(AwsChunkedStreamError::FormatError);
+                        };
+                        match check_signature(ctx, expected_signature, &data) {
+                            None => return Err(AwsChunkedStreamError::SignatureMismatch),
+                            Some(signature) => ctx.prev_signature = signature,
+                        }
+                    }
+
+                    if is_last_chunk_with_trailer {
+                        let trailers = Self::read_trailers(body.as_mut(), prev_bytes, &mut buf).await?;
+
+                        if let Some(ctx) = &signature_ctx {
+                            let Some(expected_signature) = trailers.signature else {
+                                return Err(AwsChunkedStreamError::FormatError);
+                            };
+                            if check_trailer_signature(ctx, expected_signature.as_bytes(), &trailers.headers).not() {
+                                return Err(AwsChunkedStreamError::SignatureMismatch);
+                            }
+                        }
+
+                        let mut headers = HeaderMap::with_capacity(trailers.headers.len());
+                        for (name, value) in trailers.headers {
+                            let name = HeaderName::try_from(name).map_err(|_| AwsChunkedStreamError::FormatError)?;
+                            let value = HeaderValue::try_from(value).map_err(|_| AwsChunkedStreamError::FormatError)?;
+                            headers.append(name, value);
+                        }
+                        if let Some(trailing_headers) = &trailing_headers {
+                            trailing_headers.set(headers);
+                        }
+                        break;
                     }
 
                     for bytes in data {
@@ -176,6 +267,50 @@ impl AwsChunkedStream {
         }
     }
 
+    /// read the trailer section
+    async fn read_trailers<S>(
+        mut body: Pin<&mut S>,
+        mut prev_bytes: Bytes,
+        buf: &mut Vec<u8>,
+    ) -> Result<Trailers, AwsChunkedStreamError>
+    where
+        S: Stream<Item = Result<Bytes, StdError>> + Send + 'static,
+    {
+        let mut trailers = Trailers {
+            headers: Vec::new(),
+            signature: None,
+        };
+
+        loop {
+            match Self::read_meta_bytes(body.as_mut(), prev_bytes, buf).await {
+                None if buf.is_empty() => break,
+                None => return Err(AwsChunkedStreamError::Incomplete),
+                Some(Err(e)) => return Err(AwsChunkedStreamError::Underlying(e)),
+                Some(Ok(remaining_bytes)) => prev_bytes = remaining_bytes,
+            }
+
+            if buf.as_slice() == b"\r\n" {
+                break;
+            }
+
+            let (name, value) = parse_trailer_line(buf).ok_or(AwsChunkedStreamError::FormatError)?;
+            let name = name.to_ascii_lowercase();
+
+            if trailers.signature.is_some() {
+                // the signature must be the last trailing header
+                return Err(AwsChunkedStreamError::FormatError);
+            }
+
+            if name == "x-amz-trailer-signature" {
+                trailers.signature = Some(value.to_owned());
+            } else {
+                trailers.headers.push((name, value.to_owned()));
+            }
+        }
+
+        Ok(trailers)
+    }
+
     /// read meta bytes and return remaining bytes
     async fn read_meta_bytes<S>(mut body: Pin<&mut S>, prev_bytes: Bytes, buf: &mut Vec<u8>) -> Option<Result<Bytes, StdError>>
     where
@@ -345,22 +480,23 @@ mod tests {
 
         let seed_signature = "4f232c4386841ef735655705268965c44a0e4690baa4adea153f7db9fa80a0a9";
         let timestamp = "20130524T000000Z";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let secret_access_key = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
 
         let date = AmzDate::parse(timestamp).unwrap();
 
         let stream = futures::stream::iter(chunk_results);
-        let mut chunked_stream = AwsChunkedStream::new(
-            stream,
-            seed_signature.into(),
-            date,
-            region.into(),
-            service.into(),
-            secret_access_key.into(),
-            decoded_content_length,
-        );
+        let ctx = SignatureCtx {
+            amz_date: date,
+            region: Some(region.clone()),
+            service: service.into(),
+            secret_key: secret_access_key.into(),
+            prev_signature: seed_signature.into(),
+            #[cfg(feature = "sigv4a")]
+            ecdsa_key: None,
+        };
+        let mut chunked_stream = AwsChunkedStream::new(stream, Some(ctx), decoded_content_length, None);
 
         let ans1 = chunked_stream.next().await.unwrap();
         assert_eq!(ans1.unwrap(), chunk1_data.as_slice());
@@ -374,4 +510,173 @@ mod tests {
             assert!(chunked_stream.next().await.is_none());
         }
     }
+
+    async fn collect(mut stream: AwsChunkedStream) -> Result<Vec<u8>, AwsChunkedStreamError> {
+        let mut buf = Vec::new();
+        while let Some(bytes) = stream.next().await {
+            buf.extend_from_slice(&bytes?);
+        }
+        Ok(buf)
+    }
+
+    #[tokio::test]
+    async fn unsigned_payload_trailer() {
+        let body: &[&[u8]] = &[
+            b"b\r\nHello ",
+            b"world\r\n0\r",
+            b"\nx-amz-checksum-crc32:i9aeUg==\r\n",
+            b"\r\n",
+        ];
+        let stream = futures::stream::iter(body.iter().map(|&b| Ok(Bytes::from_static(b))));
+
+        let trailing_headers = TrailingHeaders::new();
+        let chunked_stream = AwsChunkedStream::new(stream, None, 11, Some(trailing_headers.clone()));
+        assert!(trailing_headers.is_ready().not());
+
+        let data = collect(chunked_stream).await.unwrap();
+        assert_eq!(data, b"Hello world");
+
+        let checksum = trailing_headers.checksum().unwrap();
+        assert_eq!(checksum.checksum_crc32.as_deref(), Some("i9aeUg=="));
+    }
+
+    #[tokio::test]
+    async fn signed_payload_trailer() {
+        let amz_date = AmzDate::parse("20130524T000000Z").unwrap();
+        let region = &Region::new("us-east-1").unwrap();
+        let service = "s3";
+        let secret_key: SecretKey = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY".into();
+        let seed_signature = "106e2a8a18243abcf37539882f36619c00e2dfc72633413f02d3b74544bfeb8e";
+
+        let sign_chunk = |prev: &str, data: &[u8]| {
+            let data = [Bytes::copy_from_slice(data)];
+            let data: &[Bytes] = if data[0].is_empty() { &[] } else { &data };
+            let string_to_sign = sig_v4::create_chunk_string_to_sign(&amz_date, region, service, prev, data);
+            sig_v4::calculate_signature(&string_to_sign, &secret_key, &amz_date, region, service)
+        };
+        let sign_trailer = |prev: &str, trailers: &[(&str, &str)]| {
+            let string_to_sign = sig_v4::create_trailer_string_to_sign(&amz_date, region, service, prev, trailers);
+            sig_v4::calculate_signature(&string_to_sign, &secret_key, &amz_date, region, service)
+        };
+
+        let data = vec![b'a'; 1024];
+        let sig1 = sign_chunk(seed_signature, &data);
+        let sig2 = sign_chunk(&sig1, &[]);
+        let trailer_sig = sign_trailer(&sig2, &[("x-amz-checksum-crc32c", "sOO8/Q==")]);
+
+        let make_body = |checksum: &str| {
+            join(&[
+                format!("400;chunk-signature={sig1}\r\n").as_bytes(),
+                &data,
+                b"\r\n",
+                format!("0;chunk-signature={sig2}\r\n").as_bytes(),
+                format!("x-amz-checksum-crc32c:{checksum}\r\n").as_bytes(),
+                format!("x-amz-trailer-signature:{trailer_sig}\r\n").as_bytes(),
+                b"\r\n",
+            ])
+        };
+        let make_ctx = || SignatureCtx {
+            amz_date: amz_date.clone(),
+            region: Some(region.clone()),
+            service: service.into(),
+            secret_key: secret_key.clone(),
+            prev_signature: seed_signature.into(),
+            #[cfg(feature = "sigv4a")]
+            ecdsa_key: None,
+        };
+
+        {
+            let stream = futures::stream::iter([Ok(make_body("sOO8/Q=="))]);
+            let trailing_headers = TrailingHeaders::new();
+            let chunked_stream = AwsChunkedStream::new(stream, Some(make_ctx()), data.len(), Some(trailing_headers.clone()));
+
+            assert_eq!(collect(chunked_stream).await.unwrap(), data);
+            let checksum = trailing_headers.checksum().unwrap();
+            assert_eq!(checksum.checksum_crc32c.as_deref(), Some("sOO8/Q=="));
+        }
+
+        {
+            let stream = futures::stream::iter([Ok(make_body("AAAAAA=="))]);
+            let trailing_headers = TrailingHeaders::new();
+            let chunked_stream = AwsChunkedStream::new(stream, Some(make_ctx()), data.len(), Some(trailing_headers.clone()));
+
+            let err = collect(chunked_stream).await.unwrap_err();
+            assert!(matches!(err, AwsChunkedStreamError::SignatureMismatch));
+            assert!(trailing_headers.is_ready().not());
+        }
+    }
+
+    #[cfg(feature = "sigv4a")]
+    #[tokio::test]
+    async fn ecdsa_payload_trailer() {
+        use crate::utils::crypto::hex;
+
+        use p256::ecdsa::signature::Signer;
+        use p256::ecdsa::{Signature, SigningKey};
+
+        let amz_date = AmzDate::parse("20130524T000000Z").unwrap();
+        let service = "s3";
+        let seed_signature = "3045022100a28466db6c76223d5aa6ed436a8451b30e21ba0a076e7f2f943dd1f3d2816249022049e4b3746929cd2a2cb7b7984dbe0f5d40a300e7d9a7a7f1307c196b6f96b61a";
+
+        let signing_key = SigningKey::from_bytes(&[0x42; 32].into()).unwrap();
+        let sign = |string_to_sign: &str| {
+            let signature: Signature = signing_key.sign(string_to_sign.as_bytes());
+            hex(signature.to_der())
+        };
+        // chunk signatures are padded to 144 characters
+        let pad = |signature: &str| format!("{signature:*<144}");
+
+        let data = vec![b'a'; 1024];
+        let data_signature = sign(&sig_v4::v4a::create_chunk_string_to_sign(
+            &amz_date,
+            service,
+            seed_signature,
+            &[Bytes::copy_from_slice(&data)],
+        ));
+        let final_signature = sign(&sig_v4::v4a::create_chunk_string_to_sign(&amz_date, service, &data_signature, &[]));
+        let trailer_signature = sign(&sig_v4::v4a::create_trailer_string_to_sign(
+            &amz_date,
+            service,
+            &final_signature,
+            &[("x-amz-checksum-crc32", "AAAAAA==")],
+        ));
+
+        let make_body = |chunk_signature: &str| {
+            join(&[
+                format!("400;chunk-signature={}\r\n", pad(chunk_signature)).as_bytes(),
+                &data,
+                b"\r\n",
+                format!("0;chunk-signature={}\r\n", pad(&final_signature)).as_bytes(),
+                b"x-amz-checksum-crc32:AAAAAA==\r\n",
+                format!("x-amz-trailer-signature:{}\r\n", pad(&trailer_signature)).as_bytes(),
+                b"\r\n",
+            ])
+        };
+        let make_ctx = || SignatureCtx {
+            amz_date: amz_date.clone(),
+            region: None,
+            service: service.into(),
+            secret_key: "".into(),
+            prev_signature: seed_signature.into(),
+            ecdsa_key: Some(*signing_key.verifying_key()),
+        };
+
+        {
+            let stream = futures::stream::iter([Ok(make_body(&data_signature))]);
+            let trailing_headers = TrailingHeaders::new();
+            let chunked_stream = AwsChunkedStream::new(stream, Some(make_ctx()), data.len(), Some(trailing_headers.clone()));
+
+            assert_eq!(collect(chunked_stream).await.unwrap(), data);
+            let checksum = trailing_headers.checksum().unwrap();
+            assert_eq!(checksum.checksum_crc32.as_deref(), Some("AAAAAA=="));
+        }
+
+        {
+            let stream = futures::stream::iter([Ok(make_body(&final_signature))]);
+            let chunked_stream = AwsChunkedStream::new(stream, Some(make_ctx()), data.len(), Some(TrailingHeaders::new()));
+
+            let err = collect(chunked_stream).await.unwrap_err();
+            assert!(matches!(err, AwsChunkedStreamError::SignatureMismatch));
+        }
+    }
 }
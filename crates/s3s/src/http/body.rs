@@ -272,6 +272,18 @@ impl Body {
         Ok(bytes)
     }
 
+    /// Stores all bytes in memory, up to `limit` bytes.
+    ///
+    /// # Errors
+    /// Returns an error if `hyper` fails to read the body,
+    /// or [`http_body_util::LengthLimitError`] if the body is larger than `limit`.
+    pub async fn store_all_limited(&mut self, limit: usize) -> Result<Bytes, StdError> {
+        let body = http_body_util::Limited::new(mem::take(self), limit);
+        let bytes = http_body_util::BodyExt::collect(body).await?.to_bytes();
+        *self = Self::from(bytes.clone());
+        Ok(bytes)
+    }
+
     pub fn bytes(&self) -> Option<Bytes> {
         match &self.kind {
             Kind::Empty => Some(Bytes::new()),
@@ -4,6 +4,7 @@ use crate::dto::{List, Metadata, StreamingBlob, Timestamp, TimestampFormat};
 use crate::error::*;
 use crate::http::{HeaderName, HeaderValue};
 use crate::path::S3Path;
+use crate::stream::ByteStream;
 use crate::xml;
 
 use std::fmt;
@@ -135,16 +136,16 @@ pub fn parse_opt_query_timestamp(req: &Request, name: &str, fmt: TimestampFormat
 
 #[track_caller]
 pub fn unwrap_bucket(req: &mut Request) -> String {
-    match req.s3ext.s3_path.take() {
-        Some(S3Path::Bucket { bucket }) => bucket.into(),
+    match &req.s3ext.s3_path {
+        Some(S3Path::Bucket { bucket }) => bucket.as_ref().into(),
         _ => panic!("s3 path not found, expected bucket"),
     }
 }
 
 #[track_caller]
 pub fn unwrap_object(req: &mut Request) -> (String, String) {
-    match req.s3ext.s3_path.take() {
-        Some(S3Path::Object { bucket, key }) => (bucket.into(), key.into()),
+    match &req.s3ext.s3_path {
+        Some(S3Path::Object { bucket, key }) => (bucket.as_ref().into(), key.as_ref().into()),
         _ => panic!("s3 path not found, expected object"),
     }
 }
@@ -303,6 +304,11 @@ where
     }
 }
 
+/// Returns the length of the file of POST Object, if the whole file has been received with the form fields.
+pub fn file_content_length(body: &StreamingBlob) -> Option<i64> {
+    body.remaining_length().exact().and_then(|n| i64::try_from(n).ok())
+}
+
 pub fn parse_field_value_timestamp(m: &Multipart, name: &str, fmt: TimestampFormat) -> S3Result<Option<Timestamp>> {
     let Some(val) = m.find_field_value(name) else { return Ok(None) };
     match Timestamp::parse(fmt, val) {
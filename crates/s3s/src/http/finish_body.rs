@@ -0,0 +1,116 @@
+use super::Body;
+
+use crate::error::StdError;
+
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use bytes::Bytes;
+use http_body::{Frame, SizeHint};
+
+pin_project_lite::pin_project! {
+    /// A body which counts the data bytes
+    ///
+    /// `on_finish` is called with the number of data bytes when the body is finished, fails or is dropped.
+    #[project = FinishBodyProj]
+    pub(crate) struct FinishBody<F: FnOnce(u64)> {
+        #[pin]
+        inner: Body,
+        bytes: u64,
+        on_finish: Option<F>,
+    }
+
+    impl<F: FnOnce(u64)> PinnedDrop for FinishBody<F> {
+        fn drop(this: Pin<&mut Self>) {
+            this.project().finish();
+        }
+    }
+}
+
+impl<F: FnOnce(u64)> FinishBodyProj<'_, F> {
+    fn finish(&mut self) {
+        if let Some(on_finish) = self.on_finish.take() {
+            on_finish(*self.bytes);
+        }
+    }
+}
+
+impl<F> FinishBody<F>
+where
+    F: FnOnce(u64) + Send + Sync + 'static,
+{
+    pub fn new(inner: Body, on_finish: F) -> Self {
+        Self {
+            inner,
+            bytes: 0,
+            on_finish: Some(on_finish),
+        }
+    }
+
+    /// Wraps a body.
+    pub fn wrap(inner: Body, on_finish: F) -> Body {
+        Body::http_body(Self::new(inner, on_finish))
+    }
+}
+
+impl<F: FnOnce(u64)> http_body::Body for FinishBody<F> {
+    type Data = Bytes;
+
+    type Error = StdError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let ans = ready!(this.inner.as_mut().poll_frame(cx));
+        match &ans {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    *this.bytes = this.bytes.saturating_add(data.len() as u64);
+                }
+            }
+            Some(Err(_)) | None => this.finish(),
+        }
+        Poll::Ready(ans)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn finish() {
+        let count = Arc::new(AtomicU64::new(u64::MAX));
+        let on_finish = {
+            let count = count.clone();
+            move |n| count.store(n, Ordering::Relaxed)
+        };
+        let body = FinishBody::wrap(Body::from("hello".to_owned()), on_finish);
+        let bytes = body.collect().await.unwrap().to_bytes();
+        assert_eq!(bytes.as_ref(), b"hello");
+        assert_eq!(count.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn drop_unfinished() {
+        let count = Arc::new(AtomicU64::new(u64::MAX));
+        let on_finish = {
+            let count = count.clone();
+            move |n| count.store(n, Ordering::Relaxed)
+        };
+        drop(FinishBody::wrap(Body::from("hello".to_owned()), on_finish));
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+    }
+}
@@ -13,6 +13,9 @@ pub use self::ordered_headers::*;
 mod aws_chunked_stream;
 pub use self::aws_chunked_stream::*;
 
+mod trailing_headers;
+pub use self::trailing_headers::TrailingHeaders;
+
 mod multipart;
 pub use self::multipart::*;
 
@@ -22,6 +25,9 @@ pub use self::body::*;
 mod keep_alive_body;
 pub use self::keep_alive_body::KeepAliveBody;
 
+mod finish_body;
+pub(crate) use self::finish_body::FinishBody;
+
 mod request;
 pub use self::request::Request;
 
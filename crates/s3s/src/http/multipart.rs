@@ -47,6 +47,13 @@ impl Multipart {
         self.file.stream.take()
     }
 
+    /// Returns the object key, with `${filename}` replaced by the name of the file.
+    #[must_use]
+    pub fn object_key(&self) -> Option<String> {
+        let key = self.find_field_value("key")?;
+        Some(key.replace("${filename}", &self.file.name))
+    }
+
     /// Finds field value
     #[must_use]
     pub fn find_field_value<'a>(&'a self, name: &str) -> Option<&'a str> {
@@ -236,6 +243,8 @@ pub enum FileStreamError {
 pub struct FileStream {
     /// inner stream
     inner: AsyncTryStream<Bytes, FileStreamError, SyncBoxFuture<'static, Result<(), FileStreamError>>>,
+    /// remaining length, which is known if the whole file has been received with the form fields
+    remaining: Option<usize>,
 }
 
 impl Debug for FileStream {
@@ -337,10 +346,13 @@ impl FileStream {
             v.into()
         };
 
+        let remaining = prev_bytes.as_deref().and_then(|b| memchr::memmem::find(b, &crlf_pat));
+
         Self {
             inner: AsyncTryStream::new(|y| -> SyncBoxFuture<'static, Result<(), FileStreamError>> {
                 Box::pin(generate(y, body, crlf_pat, prev_bytes))
             }),
+            remaining,
         }
     }
 }
@@ -349,17 +361,27 @@ impl Stream for FileStream {
     type Item = Result<Bytes, FileStreamError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Self::Item>> {
-        Pin::new(&mut self.inner).poll_next(cx)
+        let ans = Pin::new(&mut self.inner).poll_next(cx);
+        if let (std::task::Poll::Ready(Some(Ok(bytes))), Some(remaining)) = (&ans, &mut self.remaining) {
+            *remaining = remaining.saturating_sub(bytes.len());
+        }
+        ans
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, None)
+        match self.remaining {
+            Some(n) => (n, Some(n)),
+            None => (0, None),
+        }
     }
 }
 
 impl ByteStream for FileStream {
     fn remaining_length(&self) -> crate::stream::RemainingLength {
-        crate::stream::RemainingLength::unknown()
+        match self.remaining {
+            Some(n) => crate::stream::RemainingLength::new_exact(n),
+            None => crate::stream::RemainingLength::unknown(),
+        }
     }
 }
 
@@ -1,10 +1,18 @@
 use super::Body;
 use super::Multipart;
 use super::OrderedQs;
+use super::TrailingHeaders;
 
+use crate::S3ErrorCode;
 use crate::auth::Credentials;
+use crate::checksum::BodyChecksums;
+use crate::connection::ConnectionInfo;
+use crate::dto::StreamingBlob;
+use crate::ops::PostObjectContext;
 use crate::path::S3Path;
-use crate::stream::VecByteStream;
+use crate::region::Region;
+
+use std::net::IpAddr;
 
 use hyper::HeaderMap;
 use hyper::Method;
@@ -27,11 +35,29 @@ pub(crate) struct S3Extensions {
     pub qs: Option<OrderedQs>,
 
     pub multipart: Option<Multipart>,
-    pub vec_stream: Option<VecByteStream>,
+    pub file_stream: Option<StreamingBlob>,
+    pub post_object: Option<PostObjectContext>,
 
     pub credentials: Option<Credentials>,
-    pub region: Option<String>,
+    pub region: Option<Region>,
     pub service: Option<String>,
+
+    pub trailing_headers: Option<TrailingHeaders>,
+    pub body_checksums: Option<BodyChecksums>,
+
+    pub connection: Option<ConnectionInfo>,
+    /// The client IP resolved from the connection and the forwarded headers
+    pub client_ip: Option<IpAddr>,
+
+    /// The resolved operation
+    pub op_name: Option<&'static str>,
+    /// The access key of the verified credentials
+    pub requester: Option<String>,
+    /// The error code of the error response
+    pub error_code: Option<S3ErrorCode>,
+    /// Whether the signature check has failed
+    #[cfg(feature = "metrics")]
+    pub signature_failed: bool,
 }
 
 impl From<hyper::Request<Body>> for Request {
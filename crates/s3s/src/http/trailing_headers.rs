@@ -0,0 +1,101 @@
+use crate::dto::Checksum;
+use crate::header;
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use hyper::HeaderMap;
+
+/// Trailing headers of an aws-chunked request body.
+///
+/// The headers are only available after the body has been fully consumed.
+///
+/// See <https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-streaming-trailers.html>
+#[derive(Clone, Default)]
+pub struct TrailingHeaders(Arc<Mutex<Option<HeaderMap>>>);
+
+impl fmt::Debug for TrailingHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TrailingHeaders").field(&*self.lock()).finish()
+    }
+}
+
+impl TrailingHeaders {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<HeaderMap>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn set(&self, headers: HeaderMap) {
+        *self.lock() = Some(headers);
+    }
+
+    /// Returns `true` if the trailing headers have been received.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Takes the trailing headers.
+    ///
+    /// Returns `None` if the body has not been fully consumed or the headers have been taken.
+    #[must_use]
+    pub fn take(&self) -> Option<HeaderMap> {
+        self.lock().take()
+    }
+
+    /// Reads the trailing headers without taking them.
+    pub fn read<R>(&self, f: impl FnOnce(&HeaderMap) -> R) -> Option<R> {
+        self.lock().as_ref().map(f)
+    }
+
+    /// Extracts the trailing checksum values (`x-amz-checksum-*`).
+    ///
+    /// Returns `None` if the trailing headers have not been received.
+    #[must_use]
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.read(|headers| {
+            let get = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(ToOwned::to_owned);
+            Checksum {
+                checksum_crc32: get(header::X_AMZ_CHECKSUM_CRC32),
+                checksum_crc32c: get(header::X_AMZ_CHECKSUM_CRC32C),
+                checksum_crc64nvme: get(header::X_AMZ_CHECKSUM_CRC64NVME),
+                checksum_sha1: get(header::X_AMZ_CHECKSUM_SHA1),
+                checksum_sha256: get(header::X_AMZ_CHECKSUM_SHA256),
+                checksum_type: None,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ops::Not;
+
+    #[test]
+    fn checksum() {
+        let trailers = TrailingHeaders::new();
+        assert!(trailers.is_ready().not());
+        assert!(trailers.checksum().is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::X_AMZ_CHECKSUM_CRC32, "i9aeUg==".parse().unwrap());
+        trailers.clone().set(headers);
+
+        assert!(trailers.is_ready());
+        let checksum = trailers.checksum().unwrap();
+        assert_eq!(checksum.checksum_crc32.as_deref(), Some("i9aeUg=="));
+        assert!(checksum.checksum_sha256.is_none());
+
+        assert!(trailers.take().is_some());
+        assert!(trailers.take().is_none());
+    }
+}
@@ -0,0 +1,245 @@
+//! Interceptors of S3 operations
+//!
+//! An interceptor runs before and after every S3 operation,
+//! with access to the typed operation input and output.
+//!
+//! The input of operation `Foo` is `FooInput` and the output is `FooOutput`,
+//! which can be accessed by [`Box::downcast_mut`].
+
+use crate::S3Operation;
+use crate::error::*;
+use crate::request::S3Request;
+use crate::response::S3Response;
+
+use std::any::Any;
+use std::future::Future;
+use std::ops::Not;
+
+/// Type-erased operation input
+pub type AnyInput = Box<dyn Any + Send>;
+
+/// Type-erased operation output
+pub type AnyOutput = Box<dyn Any + Send>;
+
+/// An interceptor of S3 operations
+///
+/// Interceptors are registered in order on [`S3ServiceBuilder`](crate::service::S3ServiceBuilder).
+/// `before` hooks are called in the registration order after the access check,
+/// and `after` hooks are called in the reverse order.
+///
+/// # Access control
+/// The access check has already passed when `before` is called, and it is not repeated.
+/// So `before` must not change the fields which the access check depends on,
+/// such as the bucket, the key, the version id, the copy source or the objects to delete.
+/// Otherwise the changed values are never authorized.
+/// Reject the request in `before` instead, or enforce such rules in [`S3Access`](crate::access::S3Access).
+///
+/// # Example
+/// ```
+/// use s3s::dto::PutObjectInput;
+/// use s3s::interceptor::{AnyInput, AnyOutput, S3Interceptor};
+/// use s3s::{S3Operation, S3Request, S3Response, S3Result};
+///
+/// struct UploadedBy;
+///
+/// #[async_trait::async_trait]
+/// impl S3Interceptor for UploadedBy {
+///     async fn before(&self, op: &S3Operation, req: &mut S3Request<AnyInput>) -> S3Result<Option<S3Response<AnyOutput>>> {
+///         let requester = req.credentials.as_ref().map(|c| c.access_key.clone());
+///         if let (Some(input), Some(requester)) = (req.input.downcast_mut::<PutObjectInput>(), requester) {
+///             input.metadata.get_or_insert_with(Default::default).insert("uploaded-by".into(), requester);
+///         }
+///         Ok(None)
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait S3Interceptor: Send + Sync + 'static {
+    /// Called before the operation.
+    ///
+    /// Returning a response or an error short-circuits the operation.
+    /// In that case, the remaining interceptors are skipped.
+    ///
+    /// The type of the returned output must be the output type of the operation.
+    async fn before(&self, _op: &S3Operation, _req: &mut S3Request<AnyInput>) -> S3Result<Option<S3Response<AnyOutput>>> {
+        Ok(None)
+    }
+
+    /// Called after the operation.
+    ///
+    /// This method is called for every interceptor whose `before` has been called,
+    /// including the one which short-circuited the operation.
+    async fn after(&self, _op: &S3Operation, _result: &mut S3Result<S3Response<AnyOutput>>) {}
+}
+
+/// Runs `f` through the interceptors.
+pub(crate) async fn intercept<I, O, F, Fut>(
+    interceptors: &[Box<dyn S3Interceptor>],
+    op_name: &'static str,
+    req: S3Request<I>,
+    f: F,
+) -> S3Result<S3Response<O>>
+where
+    I: Send + 'static,
+    O: Send + 'static,
+    F: FnOnce(S3Request<I>) -> Fut + Send,
+    Fut: Future<Output = S3Result<S3Response<O>>> + Send,
+{
+    if interceptors.is_empty() {
+        return f(req).await;
+    }
+
+    let op = &S3Operation { name: op_name };
+    let mut req = req.map_input(|input| Box::new(input) as AnyInput);
+
+    let mut entered = 0;
+    let mut short_circuit = None;
+    for interceptor in interceptors {
+        entered += 1;
+        match interceptor.before(op, &mut req).await {
+            Ok(None) => {}
+            Ok(Some(resp)) => {
+                short_circuit = Some(Ok(resp));
+                break;
+            }
+            Err(err) => {
+                short_circuit = Some(Err(err));
+                break;
+            }
+        }
+    }
+
+    let mut result = match short_circuit {
+        Some(result) => result,
+        None => match downcast_input(req) {
+            Ok(req) => {
+                let result = f(req).await;
+                result.map(|resp| resp.map_output(|output| Box::new(output) as AnyOutput))
+            }
+            Err(err) => Err(err),
+        },
+    };
+
+    for interceptor in interceptors[..entered].iter().rev() {
+        interceptor.after(op, &mut result).await;
+    }
+
+    let resp = result?;
+    let Ok(output) = resp.output.downcast::<O>() else {
+        return Err(s3_error!(InternalError, "interceptor returned an output of a wrong type"));
+    };
+    Ok(S3Response {
+        output: *output,
+        headers: resp.headers,
+        extensions: resp.extensions,
+    })
+}
+
+fn downcast_input<I: 'static>(req: S3Request<AnyInput>) -> S3Result<S3Request<I>> {
+    if req.input.is::<I>().not() {
+        return Err(s3_error!(InternalError, "interceptor replaced the input with a wrong type"));
+    }
+    Ok(req.map_input(|input| *input.downcast::<I>().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        short_circuit: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl S3Interceptor for Recorder {
+        async fn before(&self, op: &S3Operation, req: &mut S3Request<AnyInput>) -> S3Result<Option<S3Response<AnyOutput>>> {
+            self.log.lock().unwrap().push(format!("before {} {}", self.name, op.name()));
+            if let Some(input) = req.input.downcast_mut::<String>() {
+                input.push_str(self.name);
+            }
+            if self.short_circuit {
+                return Ok(Some(S3Response::new(Box::new(0_usize))));
+            }
+            Ok(None)
+        }
+
+        async fn after(&self, _: &S3Operation, result: &mut S3Result<S3Response<AnyOutput>>) {
+            self.log.lock().unwrap().push(format!("after {}", self.name));
+            if let Ok(resp) = result
+                && let Some(output) = resp.output.downcast_mut::<usize>()
+            {
+                *output *= 10;
+            }
+        }
+    }
+
+    fn recorders(log: &Arc<Mutex<Vec<String>>>, short_circuit: &[bool]) -> Vec<Box<dyn S3Interceptor>> {
+        let names = ["a", "b", "c"];
+        short_circuit
+            .iter()
+            .zip(names)
+            .map(|(&short_circuit, name)| {
+                let log = log.clone();
+                Box::new(Recorder {
+                    name,
+                    log,
+                    short_circuit,
+                }) as Box<dyn S3Interceptor>
+            })
+            .collect()
+    }
+
+    async fn op(req: S3Request<String>) -> S3Result<S3Response<usize>> {
+        Ok(S3Response::new(req.input.len()))
+    }
+
+    #[tokio::test]
+    async fn order() {
+        let log = Arc::default();
+        let interceptors = recorders(&log, &[false, false]);
+
+        let resp = intercept(&interceptors, "Test", S3Request::new(String::from("xyz")), op)
+            .await
+            .unwrap();
+        assert_eq!(resp.output, 500);
+        assert_eq!(*log.lock().unwrap(), ["before a Test", "before b Test", "after b", "after a"]);
+    }
+
+    struct ReplaceInput;
+
+    #[async_trait::async_trait]
+    impl S3Interceptor for ReplaceInput {
+        async fn before(&self, _: &S3Operation, req: &mut S3Request<AnyInput>) -> S3Result<Option<S3Response<AnyOutput>>> {
+            req.input = Box::new(0_u8);
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn wrong_input_type() {
+        let log = Arc::default();
+        let mut interceptors = recorders(&log, &[false]);
+        interceptors.push(Box::new(ReplaceInput));
+
+        let result = intercept(&interceptors, "Test", S3Request::new(String::new()), op).await;
+        let err = result.err().unwrap();
+        assert_eq!(*err.code(), S3ErrorCode::InternalError);
+        assert_eq!(*log.lock().unwrap(), ["before a Test", "after a"]);
+    }
+
+    #[tokio::test]
+    async fn short_circuit() {
+        let log = Arc::default();
+        let interceptors = recorders(&log, &[false, true, false]);
+
+        let resp = intercept(&interceptors, "Test", S3Request::new(String::new()), op)
+            .await
+            .unwrap();
+        assert_eq!(resp.output, 0);
+        assert_eq!(*log.lock().unwrap(), ["before a Test", "before b Test", "after b", "after a"]);
+    }
+}
@@ -0,0 +1,892 @@
+//! Auto generated by `s3s_codegen::v1::layer::codegen`
+
+use super::{S3Hook, S3Layer};
+
+use crate::S3Operation;
+use crate::dto::*;
+use crate::error::S3Result;
+use crate::request::S3Request;
+use crate::response::S3Response;
+use crate::s3_trait::S3;
+
+#[async_trait::async_trait]
+impl<H: S3Hook> S3 for S3Layer<H> {
+    async fn abort_multipart_upload(
+        &self,
+        req: S3Request<AbortMultipartUploadInput>,
+    ) -> S3Result<S3Response<AbortMultipartUploadOutput>> {
+        let op = S3Operation {
+            name: "AbortMultipartUpload",
+        };
+        self.hook.call(&op, req, |req| self.inner.abort_multipart_upload(req)).await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        req: S3Request<CompleteMultipartUploadInput>,
+    ) -> S3Result<S3Response<CompleteMultipartUploadOutput>> {
+        let op = S3Operation {
+            name: "CompleteMultipartUpload",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.complete_multipart_upload(req))
+            .await
+    }
+
+    async fn copy_object(&self, req: S3Request<CopyObjectInput>) -> S3Result<S3Response<CopyObjectOutput>> {
+        let op = S3Operation { name: "CopyObject" };
+        self.hook.call(&op, req, |req| self.inner.copy_object(req)).await
+    }
+
+    async fn create_bucket(&self, req: S3Request<CreateBucketInput>) -> S3Result<S3Response<CreateBucketOutput>> {
+        let op = S3Operation { name: "CreateBucket" };
+        self.hook.call(&op, req, |req| self.inner.create_bucket(req)).await
+    }
+
+    async fn create_bucket_metadata_table_configuration(
+        &self,
+        req: S3Request<CreateBucketMetadataTableConfigurationInput>,
+    ) -> S3Result<S3Response<CreateBucketMetadataTableConfigurationOutput>> {
+        let op = S3Operation {
+            name: "CreateBucketMetadataTableConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.create_bucket_metadata_table_configuration(req))
+            .await
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        req: S3Request<CreateMultipartUploadInput>,
+    ) -> S3Result<S3Response<CreateMultipartUploadOutput>> {
+        let op = S3Operation {
+            name: "CreateMultipartUpload",
+        };
+        self.hook.call(&op, req, |req| self.inner.create_multipart_upload(req)).await
+    }
+
+    async fn delete_bucket(&self, req: S3Request<DeleteBucketInput>) -> S3Result<S3Response<DeleteBucketOutput>> {
+        let op = S3Operation { name: "DeleteBucket" };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket(req)).await
+    }
+
+    async fn delete_bucket_analytics_configuration(
+        &self,
+        req: S3Request<DeleteBucketAnalyticsConfigurationInput>,
+    ) -> S3Result<S3Response<DeleteBucketAnalyticsConfigurationOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketAnalyticsConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_analytics_configuration(req))
+            .await
+    }
+
+    async fn delete_bucket_cors(&self, req: S3Request<DeleteBucketCorsInput>) -> S3Result<S3Response<DeleteBucketCorsOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketCors",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket_cors(req)).await
+    }
+
+    async fn delete_bucket_encryption(
+        &self,
+        req: S3Request<DeleteBucketEncryptionInput>,
+    ) -> S3Result<S3Response<DeleteBucketEncryptionOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketEncryption",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket_encryption(req)).await
+    }
+
+    async fn delete_bucket_intelligent_tiering_configuration(
+        &self,
+        req: S3Request<DeleteBucketIntelligentTieringConfigurationInput>,
+    ) -> S3Result<S3Response<DeleteBucketIntelligentTieringConfigurationOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketIntelligentTieringConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_intelligent_tiering_configuration(req))
+            .await
+    }
+
+    async fn delete_bucket_inventory_configuration(
+        &self,
+        req: S3Request<DeleteBucketInventoryConfigurationInput>,
+    ) -> S3Result<S3Response<DeleteBucketInventoryConfigurationOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketInventoryConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_inventory_configuration(req))
+            .await
+    }
+
+    async fn delete_bucket_lifecycle(
+        &self,
+        req: S3Request<DeleteBucketLifecycleInput>,
+    ) -> S3Result<S3Response<DeleteBucketLifecycleOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketLifecycle",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket_lifecycle(req)).await
+    }
+
+    async fn delete_bucket_metadata_table_configuration(
+        &self,
+        req: S3Request<DeleteBucketMetadataTableConfigurationInput>,
+    ) -> S3Result<S3Response<DeleteBucketMetadataTableConfigurationOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketMetadataTableConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_metadata_table_configuration(req))
+            .await
+    }
+
+    async fn delete_bucket_metrics_configuration(
+        &self,
+        req: S3Request<DeleteBucketMetricsConfigurationInput>,
+    ) -> S3Result<S3Response<DeleteBucketMetricsConfigurationOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketMetricsConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_metrics_configuration(req))
+            .await
+    }
+
+    async fn delete_bucket_ownership_controls(
+        &self,
+        req: S3Request<DeleteBucketOwnershipControlsInput>,
+    ) -> S3Result<S3Response<DeleteBucketOwnershipControlsOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketOwnershipControls",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_ownership_controls(req))
+            .await
+    }
+
+    async fn delete_bucket_policy(
+        &self,
+        req: S3Request<DeleteBucketPolicyInput>,
+    ) -> S3Result<S3Response<DeleteBucketPolicyOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketPolicy",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket_policy(req)).await
+    }
+
+    async fn delete_bucket_replication(
+        &self,
+        req: S3Request<DeleteBucketReplicationInput>,
+    ) -> S3Result<S3Response<DeleteBucketReplicationOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketReplication",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_bucket_replication(req))
+            .await
+    }
+
+    async fn delete_bucket_tagging(
+        &self,
+        req: S3Request<DeleteBucketTaggingInput>,
+    ) -> S3Result<S3Response<DeleteBucketTaggingOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketTagging",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket_tagging(req)).await
+    }
+
+    async fn delete_bucket_website(
+        &self,
+        req: S3Request<DeleteBucketWebsiteInput>,
+    ) -> S3Result<S3Response<DeleteBucketWebsiteOutput>> {
+        let op = S3Operation {
+            name: "DeleteBucketWebsite",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_bucket_website(req)).await
+    }
+
+    async fn delete_object(&self, req: S3Request<DeleteObjectInput>) -> S3Result<S3Response<DeleteObjectOutput>> {
+        let op = S3Operation { name: "DeleteObject" };
+        self.hook.call(&op, req, |req| self.inner.delete_object(req)).await
+    }
+
+    async fn delete_object_tagging(
+        &self,
+        req: S3Request<DeleteObjectTaggingInput>,
+    ) -> S3Result<S3Response<DeleteObjectTaggingOutput>> {
+        let op = S3Operation {
+            name: "DeleteObjectTagging",
+        };
+        self.hook.call(&op, req, |req| self.inner.delete_object_tagging(req)).await
+    }
+
+    async fn delete_objects(&self, req: S3Request<DeleteObjectsInput>) -> S3Result<S3Response<DeleteObjectsOutput>> {
+        let op = S3Operation { name: "DeleteObjects" };
+        self.hook.call(&op, req, |req| self.inner.delete_objects(req)).await
+    }
+
+    async fn delete_public_access_block(
+        &self,
+        req: S3Request<DeletePublicAccessBlockInput>,
+    ) -> S3Result<S3Response<DeletePublicAccessBlockOutput>> {
+        let op = S3Operation {
+            name: "DeletePublicAccessBlock",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.delete_public_access_block(req))
+            .await
+    }
+
+    async fn get_bucket_accelerate_configuration(
+        &self,
+        req: S3Request<GetBucketAccelerateConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketAccelerateConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketAccelerateConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_accelerate_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_acl(&self, req: S3Request<GetBucketAclInput>) -> S3Result<S3Response<GetBucketAclOutput>> {
+        let op = S3Operation { name: "GetBucketAcl" };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_acl(req)).await
+    }
+
+    async fn get_bucket_analytics_configuration(
+        &self,
+        req: S3Request<GetBucketAnalyticsConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketAnalyticsConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketAnalyticsConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_analytics_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_cors(&self, req: S3Request<GetBucketCorsInput>) -> S3Result<S3Response<GetBucketCorsOutput>> {
+        let op = S3Operation { name: "GetBucketCors" };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_cors(req)).await
+    }
+
+    async fn get_bucket_encryption(
+        &self,
+        req: S3Request<GetBucketEncryptionInput>,
+    ) -> S3Result<S3Response<GetBucketEncryptionOutput>> {
+        let op = S3Operation {
+            name: "GetBucketEncryption",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_encryption(req)).await
+    }
+
+    async fn get_bucket_intelligent_tiering_configuration(
+        &self,
+        req: S3Request<GetBucketIntelligentTieringConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketIntelligentTieringConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketIntelligentTieringConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_intelligent_tiering_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_inventory_configuration(
+        &self,
+        req: S3Request<GetBucketInventoryConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketInventoryConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketInventoryConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_inventory_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_lifecycle_configuration(
+        &self,
+        req: S3Request<GetBucketLifecycleConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketLifecycleConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketLifecycleConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_lifecycle_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_location(&self, req: S3Request<GetBucketLocationInput>) -> S3Result<S3Response<GetBucketLocationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketLocation",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_location(req)).await
+    }
+
+    async fn get_bucket_logging(&self, req: S3Request<GetBucketLoggingInput>) -> S3Result<S3Response<GetBucketLoggingOutput>> {
+        let op = S3Operation {
+            name: "GetBucketLogging",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_logging(req)).await
+    }
+
+    async fn get_bucket_metadata_table_configuration(
+        &self,
+        req: S3Request<GetBucketMetadataTableConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketMetadataTableConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketMetadataTableConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_metadata_table_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_metrics_configuration(
+        &self,
+        req: S3Request<GetBucketMetricsConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketMetricsConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketMetricsConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_metrics_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_notification_configuration(
+        &self,
+        req: S3Request<GetBucketNotificationConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketNotificationConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketNotificationConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_notification_configuration(req))
+            .await
+    }
+
+    async fn get_bucket_ownership_controls(
+        &self,
+        req: S3Request<GetBucketOwnershipControlsInput>,
+    ) -> S3Result<S3Response<GetBucketOwnershipControlsOutput>> {
+        let op = S3Operation {
+            name: "GetBucketOwnershipControls",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_ownership_controls(req))
+            .await
+    }
+
+    async fn get_bucket_policy(&self, req: S3Request<GetBucketPolicyInput>) -> S3Result<S3Response<GetBucketPolicyOutput>> {
+        let op = S3Operation { name: "GetBucketPolicy" };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_policy(req)).await
+    }
+
+    async fn get_bucket_policy_status(
+        &self,
+        req: S3Request<GetBucketPolicyStatusInput>,
+    ) -> S3Result<S3Response<GetBucketPolicyStatusOutput>> {
+        let op = S3Operation {
+            name: "GetBucketPolicyStatus",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_policy_status(req)).await
+    }
+
+    async fn get_bucket_replication(
+        &self,
+        req: S3Request<GetBucketReplicationInput>,
+    ) -> S3Result<S3Response<GetBucketReplicationOutput>> {
+        let op = S3Operation {
+            name: "GetBucketReplication",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_replication(req)).await
+    }
+
+    async fn get_bucket_request_payment(
+        &self,
+        req: S3Request<GetBucketRequestPaymentInput>,
+    ) -> S3Result<S3Response<GetBucketRequestPaymentOutput>> {
+        let op = S3Operation {
+            name: "GetBucketRequestPayment",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_bucket_request_payment(req))
+            .await
+    }
+
+    async fn get_bucket_tagging(&self, req: S3Request<GetBucketTaggingInput>) -> S3Result<S3Response<GetBucketTaggingOutput>> {
+        let op = S3Operation {
+            name: "GetBucketTagging",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_tagging(req)).await
+    }
+
+    async fn get_bucket_versioning(
+        &self,
+        req: S3Request<GetBucketVersioningInput>,
+    ) -> S3Result<S3Response<GetBucketVersioningOutput>> {
+        let op = S3Operation {
+            name: "GetBucketVersioning",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_versioning(req)).await
+    }
+
+    async fn get_bucket_website(&self, req: S3Request<GetBucketWebsiteInput>) -> S3Result<S3Response<GetBucketWebsiteOutput>> {
+        let op = S3Operation {
+            name: "GetBucketWebsite",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_bucket_website(req)).await
+    }
+
+    async fn get_object(&self, req: S3Request<GetObjectInput>) -> S3Result<S3Response<GetObjectOutput>> {
+        let op = S3Operation { name: "GetObject" };
+        self.hook.call(&op, req, |req| self.inner.get_object(req)).await
+    }
+
+    async fn get_object_acl(&self, req: S3Request<GetObjectAclInput>) -> S3Result<S3Response<GetObjectAclOutput>> {
+        let op = S3Operation { name: "GetObjectAcl" };
+        self.hook.call(&op, req, |req| self.inner.get_object_acl(req)).await
+    }
+
+    async fn get_object_attributes(
+        &self,
+        req: S3Request<GetObjectAttributesInput>,
+    ) -> S3Result<S3Response<GetObjectAttributesOutput>> {
+        let op = S3Operation {
+            name: "GetObjectAttributes",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_object_attributes(req)).await
+    }
+
+    async fn get_object_legal_hold(
+        &self,
+        req: S3Request<GetObjectLegalHoldInput>,
+    ) -> S3Result<S3Response<GetObjectLegalHoldOutput>> {
+        let op = S3Operation {
+            name: "GetObjectLegalHold",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_object_legal_hold(req)).await
+    }
+
+    async fn get_object_lock_configuration(
+        &self,
+        req: S3Request<GetObjectLockConfigurationInput>,
+    ) -> S3Result<S3Response<GetObjectLockConfigurationOutput>> {
+        let op = S3Operation {
+            name: "GetObjectLockConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.get_object_lock_configuration(req))
+            .await
+    }
+
+    async fn get_object_retention(
+        &self,
+        req: S3Request<GetObjectRetentionInput>,
+    ) -> S3Result<S3Response<GetObjectRetentionOutput>> {
+        let op = S3Operation {
+            name: "GetObjectRetention",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_object_retention(req)).await
+    }
+
+    async fn get_object_tagging(&self, req: S3Request<GetObjectTaggingInput>) -> S3Result<S3Response<GetObjectTaggingOutput>> {
+        let op = S3Operation {
+            name: "GetObjectTagging",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_object_tagging(req)).await
+    }
+
+    async fn get_object_torrent(&self, req: S3Request<GetObjectTorrentInput>) -> S3Result<S3Response<GetObjectTorrentOutput>> {
+        let op = S3Operation {
+            name: "GetObjectTorrent",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_object_torrent(req)).await
+    }
+
+    async fn get_public_access_block(
+        &self,
+        req: S3Request<GetPublicAccessBlockInput>,
+    ) -> S3Result<S3Response<GetPublicAccessBlockOutput>> {
+        let op = S3Operation {
+            name: "GetPublicAccessBlock",
+        };
+        self.hook.call(&op, req, |req| self.inner.get_public_access_block(req)).await
+    }
+
+    async fn head_bucket(&self, req: S3Request<HeadBucketInput>) -> S3Result<S3Response<HeadBucketOutput>> {
+        let op = S3Operation { name: "HeadBucket" };
+        self.hook.call(&op, req, |req| self.inner.head_bucket(req)).await
+    }
+
+    async fn head_object(&self, req: S3Request<HeadObjectInput>) -> S3Result<S3Response<HeadObjectOutput>> {
+        let op = S3Operation { name: "HeadObject" };
+        self.hook.call(&op, req, |req| self.inner.head_object(req)).await
+    }
+
+    async fn list_bucket_analytics_configurations(
+        &self,
+        req: S3Request<ListBucketAnalyticsConfigurationsInput>,
+    ) -> S3Result<S3Response<ListBucketAnalyticsConfigurationsOutput>> {
+        let op = S3Operation {
+            name: "ListBucketAnalyticsConfigurations",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.list_bucket_analytics_configurations(req))
+            .await
+    }
+
+    async fn list_bucket_intelligent_tiering_configurations(
+        &self,
+        req: S3Request<ListBucketIntelligentTieringConfigurationsInput>,
+    ) -> S3Result<S3Response<ListBucketIntelligentTieringConfigurationsOutput>> {
+        let op = S3Operation {
+            name: "ListBucketIntelligentTieringConfigurations",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.list_bucket_intelligent_tiering_configurations(req))
+            .await
+    }
+
+    async fn list_bucket_inventory_configurations(
+        &self,
+        req: S3Request<ListBucketInventoryConfigurationsInput>,
+    ) -> S3Result<S3Response<ListBucketInventoryConfigurationsOutput>> {
+        let op = S3Operation {
+            name: "ListBucketInventoryConfigurations",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.list_bucket_inventory_configurations(req))
+            .await
+    }
+
+    async fn list_bucket_metrics_configurations(
+        &self,
+        req: S3Request<ListBucketMetricsConfigurationsInput>,
+    ) -> S3Result<S3Response<ListBucketMetricsConfigurationsOutput>> {
+        let op = S3Operation {
+            name: "ListBucketMetricsConfigurations",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.list_bucket_metrics_configurations(req))
+            .await
+    }
+
+    async fn list_buckets(&self, req: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
+        let op = S3Operation { name: "ListBuckets" };
+        self.hook.call(&op, req, |req| self.inner.list_buckets(req)).await
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        req: S3Request<ListMultipartUploadsInput>,
+    ) -> S3Result<S3Response<ListMultipartUploadsOutput>> {
+        let op = S3Operation {
+            name: "ListMultipartUploads",
+        };
+        self.hook.call(&op, req, |req| self.inner.list_multipart_uploads(req)).await
+    }
+
+    async fn list_object_versions(
+        &self,
+        req: S3Request<ListObjectVersionsInput>,
+    ) -> S3Result<S3Response<ListObjectVersionsOutput>> {
+        let op = S3Operation {
+            name: "ListObjectVersions",
+        };
+        self.hook.call(&op, req, |req| self.inner.list_object_versions(req)).await
+    }
+
+    async fn list_objects(&self, req: S3Request<ListObjectsInput>) -> S3Result<S3Response<ListObjectsOutput>> {
+        let op = S3Operation { name: "ListObjects" };
+        self.hook.call(&op, req, |req| self.inner.list_objects(req)).await
+    }
+
+    async fn list_objects_v2(&self, req: S3Request<ListObjectsV2Input>) -> S3Result<S3Response<ListObjectsV2Output>> {
+        let op = S3Operation { name: "ListObjectsV2" };
+        self.hook.call(&op, req, |req| self.inner.list_objects_v2(req)).await
+    }
+
+    async fn list_parts(&self, req: S3Request<ListPartsInput>) -> S3Result<S3Response<ListPartsOutput>> {
+        let op = S3Operation { name: "ListParts" };
+        self.hook.call(&op, req, |req| self.inner.list_parts(req)).await
+    }
+
+    async fn put_bucket_accelerate_configuration(
+        &self,
+        req: S3Request<PutBucketAccelerateConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketAccelerateConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketAccelerateConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_accelerate_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_acl(&self, req: S3Request<PutBucketAclInput>) -> S3Result<S3Response<PutBucketAclOutput>> {
+        let op = S3Operation { name: "PutBucketAcl" };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_acl(req)).await
+    }
+
+    async fn put_bucket_analytics_configuration(
+        &self,
+        req: S3Request<PutBucketAnalyticsConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketAnalyticsConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketAnalyticsConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_analytics_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_cors(&self, req: S3Request<PutBucketCorsInput>) -> S3Result<S3Response<PutBucketCorsOutput>> {
+        let op = S3Operation { name: "PutBucketCors" };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_cors(req)).await
+    }
+
+    async fn put_bucket_encryption(
+        &self,
+        req: S3Request<PutBucketEncryptionInput>,
+    ) -> S3Result<S3Response<PutBucketEncryptionOutput>> {
+        let op = S3Operation {
+            name: "PutBucketEncryption",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_encryption(req)).await
+    }
+
+    async fn put_bucket_intelligent_tiering_configuration(
+        &self,
+        req: S3Request<PutBucketIntelligentTieringConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketIntelligentTieringConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketIntelligentTieringConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_intelligent_tiering_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_inventory_configuration(
+        &self,
+        req: S3Request<PutBucketInventoryConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketInventoryConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketInventoryConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_inventory_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_lifecycle_configuration(
+        &self,
+        req: S3Request<PutBucketLifecycleConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketLifecycleConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketLifecycleConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_lifecycle_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_logging(&self, req: S3Request<PutBucketLoggingInput>) -> S3Result<S3Response<PutBucketLoggingOutput>> {
+        let op = S3Operation {
+            name: "PutBucketLogging",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_logging(req)).await
+    }
+
+    async fn put_bucket_metrics_configuration(
+        &self,
+        req: S3Request<PutBucketMetricsConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketMetricsConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketMetricsConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_metrics_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_notification_configuration(
+        &self,
+        req: S3Request<PutBucketNotificationConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketNotificationConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketNotificationConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_notification_configuration(req))
+            .await
+    }
+
+    async fn put_bucket_ownership_controls(
+        &self,
+        req: S3Request<PutBucketOwnershipControlsInput>,
+    ) -> S3Result<S3Response<PutBucketOwnershipControlsOutput>> {
+        let op = S3Operation {
+            name: "PutBucketOwnershipControls",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_ownership_controls(req))
+            .await
+    }
+
+    async fn put_bucket_policy(&self, req: S3Request<PutBucketPolicyInput>) -> S3Result<S3Response<PutBucketPolicyOutput>> {
+        let op = S3Operation { name: "PutBucketPolicy" };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_policy(req)).await
+    }
+
+    async fn put_bucket_replication(
+        &self,
+        req: S3Request<PutBucketReplicationInput>,
+    ) -> S3Result<S3Response<PutBucketReplicationOutput>> {
+        let op = S3Operation {
+            name: "PutBucketReplication",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_replication(req)).await
+    }
+
+    async fn put_bucket_request_payment(
+        &self,
+        req: S3Request<PutBucketRequestPaymentInput>,
+    ) -> S3Result<S3Response<PutBucketRequestPaymentOutput>> {
+        let op = S3Operation {
+            name: "PutBucketRequestPayment",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_bucket_request_payment(req))
+            .await
+    }
+
+    async fn put_bucket_tagging(&self, req: S3Request<PutBucketTaggingInput>) -> S3Result<S3Response<PutBucketTaggingOutput>> {
+        let op = S3Operation {
+            name: "PutBucketTagging",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_tagging(req)).await
+    }
+
+    async fn put_bucket_versioning(
+        &self,
+        req: S3Request<PutBucketVersioningInput>,
+    ) -> S3Result<S3Response<PutBucketVersioningOutput>> {
+        let op = S3Operation {
+            name: "PutBucketVersioning",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_versioning(req)).await
+    }
+
+    async fn put_bucket_website(&self, req: S3Request<PutBucketWebsiteInput>) -> S3Result<S3Response<PutBucketWebsiteOutput>> {
+        let op = S3Operation {
+            name: "PutBucketWebsite",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_bucket_website(req)).await
+    }
+
+    async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+        let op = S3Operation { name: "PutObject" };
+        self.hook.call(&op, req, |req| self.inner.put_object(req)).await
+    }
+
+    async fn put_object_acl(&self, req: S3Request<PutObjectAclInput>) -> S3Result<S3Response<PutObjectAclOutput>> {
+        let op = S3Operation { name: "PutObjectAcl" };
+        self.hook.call(&op, req, |req| self.inner.put_object_acl(req)).await
+    }
+
+    async fn put_object_legal_hold(
+        &self,
+        req: S3Request<PutObjectLegalHoldInput>,
+    ) -> S3Result<S3Response<PutObjectLegalHoldOutput>> {
+        let op = S3Operation {
+            name: "PutObjectLegalHold",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_object_legal_hold(req)).await
+    }
+
+    async fn put_object_lock_configuration(
+        &self,
+        req: S3Request<PutObjectLockConfigurationInput>,
+    ) -> S3Result<S3Response<PutObjectLockConfigurationOutput>> {
+        let op = S3Operation {
+            name: "PutObjectLockConfiguration",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.put_object_lock_configuration(req))
+            .await
+    }
+
+    async fn put_object_retention(
+        &self,
+        req: S3Request<PutObjectRetentionInput>,
+    ) -> S3Result<S3Response<PutObjectRetentionOutput>> {
+        let op = S3Operation {
+            name: "PutObjectRetention",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_object_retention(req)).await
+    }
+
+    async fn put_object_tagging(&self, req: S3Request<PutObjectTaggingInput>) -> S3Result<S3Response<PutObjectTaggingOutput>> {
+        let op = S3Operation {
+            name: "PutObjectTagging",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_object_tagging(req)).await
+    }
+
+    async fn put_public_access_block(
+        &self,
+        req: S3Request<PutPublicAccessBlockInput>,
+    ) -> S3Result<S3Response<PutPublicAccessBlockOutput>> {
+        let op = S3Operation {
+            name: "PutPublicAccessBlock",
+        };
+        self.hook.call(&op, req, |req| self.inner.put_public_access_block(req)).await
+    }
+
+    async fn restore_object(&self, req: S3Request<RestoreObjectInput>) -> S3Result<S3Response<RestoreObjectOutput>> {
+        let op = S3Operation { name: "RestoreObject" };
+        self.hook.call(&op, req, |req| self.inner.restore_object(req)).await
+    }
+
+    async fn select_object_content(
+        &self,
+        req: S3Request<SelectObjectContentInput>,
+    ) -> S3Result<S3Response<SelectObjectContentOutput>> {
+        let op = S3Operation {
+            name: "SelectObjectContent",
+        };
+        self.hook.call(&op, req, |req| self.inner.select_object_content(req)).await
+    }
+
+    async fn upload_part(&self, req: S3Request<UploadPartInput>) -> S3Result<S3Response<UploadPartOutput>> {
+        let op = S3Operation { name: "UploadPart" };
+        self.hook.call(&op, req, |req| self.inner.upload_part(req)).await
+    }
+
+    async fn upload_part_copy(&self, req: S3Request<UploadPartCopyInput>) -> S3Result<S3Response<UploadPartCopyOutput>> {
+        let op = S3Operation { name: "UploadPartCopy" };
+        self.hook.call(&op, req, |req| self.inner.upload_part_copy(req)).await
+    }
+
+    async fn write_get_object_response(
+        &self,
+        req: S3Request<WriteGetObjectResponseInput>,
+    ) -> S3Result<S3Response<WriteGetObjectResponseOutput>> {
+        let op = S3Operation {
+            name: "WriteGetObjectResponse",
+        };
+        self.hook
+            .call(&op, req, |req| self.inner.write_get_object_response(req))
+            .await
+    }
+}
@@ -0,0 +1,168 @@
+//! S3 decorator
+//!
+//! [`S3Layer`] wraps an inner [`S3`] implementation and forwards every operation to it
+//! through an [`S3Hook`]. The forwarding is generated from the Smithy model.
+
+mod generated;
+
+use crate::S3Operation;
+use crate::error::S3Result;
+use crate::request::S3Request;
+use crate::response::S3Response;
+use crate::s3_trait::S3;
+
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
+
+/// A hook which is called for every operation of [`S3Layer`]
+///
+/// The input of operation `Foo` is `FooInput` and the output is `FooOutput`.
+/// They can be accessed by downcasting through [`Any`].
+///
+/// # Example
+/// ```
+/// use s3s::layer::S3Hook;
+/// use s3s::{S3Operation, S3Request, S3Response, S3Result};
+///
+/// use std::future::Future;
+///
+/// struct Logger;
+///
+/// impl S3Hook for Logger {
+///     async fn call<I, O, F, Fut>(&self, op: &S3Operation, req: S3Request<I>, next: F) -> S3Result<S3Response<O>>
+///     where
+///         I: Send + 'static,
+///         O: Send + 'static,
+///         F: FnOnce(S3Request<I>) -> Fut + Send,
+///         Fut: Future<Output = S3Result<S3Response<O>>> + Send,
+///     {
+///         let result = next(req).await;
+///         println!("{}: {}", op.name(), if result.is_ok() { "ok" } else { "error" });
+///         result
+///     }
+/// }
+/// ```
+pub trait S3Hook: Send + Sync + 'static {
+    /// Calls the operation.
+    ///
+    /// `next` calls the inner implementation. A hook may inspect or modify the request
+    /// and the response, or return a response without calling `next`.
+    ///
+    /// By default, this method calls `next` directly.
+    fn call<I, O, F, Fut>(
+        &self,
+        _op: &S3Operation,
+        req: S3Request<I>,
+        next: F,
+    ) -> impl Future<Output = S3Result<S3Response<O>>> + Send
+    where
+        I: Send + 'static,
+        O: Send + 'static,
+        F: FnOnce(S3Request<I>) -> Fut + Send,
+        Fut: Future<Output = S3Result<S3Response<O>>> + Send,
+    {
+        next(req)
+    }
+}
+
+/// A decorator of [`S3`] implementations
+pub struct S3Layer<H> {
+    inner: Arc<dyn S3>,
+    hook: H,
+}
+
+impl<H: S3Hook> S3Layer<H> {
+    #[must_use]
+    pub fn new(inner: impl S3, hook: H) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            hook,
+        }
+    }
+
+    /// Returns the inner implementation.
+    #[must_use]
+    pub fn inner(&self) -> &Arc<dyn S3> {
+        &self.inner
+    }
+
+    /// Returns the hook.
+    #[must_use]
+    pub fn hook(&self) -> &H {
+        &self.hook
+    }
+}
+
+/// Returns a mutable reference to the input if it is of type `T`.
+///
+/// It is a shortcut for downcasting the typed input in a generic hook.
+pub fn downcast_input_mut<T: 'static, I: 'static>(req: &mut S3Request<I>) -> Option<&mut T> {
+    (&mut req.input as &mut dyn Any).downcast_mut::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dto::{GetObjectInput, GetObjectOutput, PutObjectInput, PutObjectOutput};
+
+    use std::sync::Mutex;
+
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl S3 for Echo {
+        async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+            let output = PutObjectOutput {
+                e_tag: Some(req.input.key),
+                ..Default::default()
+            };
+            Ok(S3Response::new(output))
+        }
+    }
+
+    #[derive(Default)]
+    struct Prefix {
+        ops: Mutex<Vec<String>>,
+    }
+
+    impl S3Hook for Prefix {
+        async fn call<I, O, F, Fut>(&self, op: &S3Operation, mut req: S3Request<I>, next: F) -> S3Result<S3Response<O>>
+        where
+            I: Send + 'static,
+            O: Send + 'static,
+            F: FnOnce(S3Request<I>) -> Fut + Send,
+            Fut: Future<Output = S3Result<S3Response<O>>> + Send,
+        {
+            self.ops.lock().unwrap().push(op.name().to_owned());
+            if let Some(input) = downcast_input_mut::<PutObjectInput, _>(&mut req) {
+                input.key.insert_str(0, "prefix/");
+            }
+            next(req).await
+        }
+    }
+
+    #[tokio::test]
+    async fn forward() {
+        let layer = S3Layer::new(Echo, Prefix::default());
+
+        let input = PutObjectInput::builder()
+            .bucket("b".into())
+            .key("a.txt".into())
+            .build()
+            .unwrap();
+        let resp = layer.put_object(S3Request::new(input)).await.unwrap();
+        assert_eq!(resp.output.e_tag.as_deref(), Some("prefix/a.txt"));
+
+        let input = GetObjectInput::builder()
+            .bucket("b".into())
+            .key("a.txt".into())
+            .build()
+            .unwrap();
+        let result: S3Result<S3Response<GetObjectOutput>> = layer.get_object(S3Request::new(input)).await;
+        assert!(result.is_err());
+
+        assert_eq!(*layer.hook().ops.lock().unwrap(), ["PutObject", "GetObject"]);
+    }
+}
@@ -18,6 +18,7 @@ mod error;
 
 mod http;
 mod ops;
+mod post_policy;
 mod request;
 mod response;
 mod s3_op;
@@ -26,20 +27,32 @@ mod sig_v2;
 mod sig_v4;
 
 pub mod access;
+pub mod access_log;
 pub mod auth;
 pub mod checksum;
+pub mod conditional;
+pub mod connection;
 pub mod crypto;
 pub mod dto;
 pub mod header;
 pub mod host;
+pub mod interceptor;
+pub mod layer;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod path;
+pub mod region;
+pub mod request_id;
 pub mod route;
 pub mod service;
 pub mod stream;
+pub mod sts;
+pub mod tls;
 pub mod xml;
 
 pub use self::error::*;
 pub use self::http::Body;
+pub use self::http::TrailingHeaders;
 pub use self::request::S3Request;
 pub use self::response::S3Response;
 pub use self::s3_op::S3Operation;
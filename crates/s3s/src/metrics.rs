@@ -0,0 +1,457 @@
+//! Prometheus metrics
+//!
+//! [`S3Service`](crate::service::S3Service) records the following metrics to the configured [`Metrics`]:
+//!
+//! | name | type | labels |
+//! | --- | --- | --- |
+//! | `s3s_requests_total` | counter | `operation` |
+//! | `s3s_errors_total` | counter | `operation`, `code` |
+//! | `s3s_request_duration_seconds` | histogram | `operation` |
+//! | `s3s_received_bytes_total` | counter | `operation` |
+//! | `s3s_sent_bytes_total` | counter | `operation` |
+//! | `s3s_requests_in_flight` | gauge | |
+//! | `s3s_signature_failures_total` | counter | |
+//!
+//! The request duration is the time from receiving the request to sending the response headers.
+//! The byte counters count the request and response bodies, which are recorded after the response body is finished.
+//! A request is in flight until its response body is finished.
+//! Requests to custom routes are labeled with [`S3Route::name`],
+//! and requests whose operation is not resolved are labeled as `Unknown`.
+//!
+//! The metrics can be rendered in the Prometheus text format by [`Metrics::render`],
+//! or served by [`MetricsRoute`], which can be added along with other routes by
+//! [`S3ServiceBuilder::add_route`](crate::service::S3ServiceBuilder::add_route).
+
+use crate::error::S3Result;
+use crate::http::{Body, FinishBody, Request};
+use crate::request::S3Request;
+use crate::response::S3Response;
+use crate::route::S3Route;
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use hyper::http::Extensions;
+use hyper::{HeaderMap, Method, StatusCode, Uri};
+
+/// The content type of the Prometheus text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The upper bounds of the request duration histogram, in seconds
+const DURATION_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+const UNKNOWN_OPERATION: &str = "Unknown";
+
+/// A registry of S3 service metrics
+///
+/// It is cheap to clone. The clones share the same metrics.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    in_flight: AtomicI64,
+    signature_failures: AtomicU64,
+    operations: Mutex<BTreeMap<&'static str, OperationMetrics>>,
+}
+
+#[derive(Debug, Default)]
+struct OperationMetrics {
+    requests: u64,
+    errors: BTreeMap<String, u64>,
+    duration: Histogram,
+    received_bytes: u64,
+    sent_bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, &le) in self.buckets.iter_mut().zip(DURATION_BUCKETS.iter()) {
+            if value <= le {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+impl Metrics {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_operation(&self, op: &'static str, f: impl FnOnce(&mut OperationMetrics)) {
+        let mut operations = self.inner.operations.lock().unwrap();
+        f(operations.entry(op).or_default());
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut ans = String::new();
+        // writing to a string never fails
+        let _ = self.encode(&mut ans);
+        ans
+    }
+
+    fn encode(&self, w: &mut String) -> fmt::Result {
+        let operations = self.inner.operations.lock().unwrap();
+
+        header(w, "s3s_requests_total", "counter", "The number of requests.")?;
+        for (op, m) in &*operations {
+            writeln!(w, "s3s_requests_total{{operation=\"{op}\"}} {}", m.requests)?;
+        }
+
+        header(w, "s3s_errors_total", "counter", "The number of error responses.")?;
+        for (op, m) in &*operations {
+            for (code, n) in &m.errors {
+                writeln!(w, "s3s_errors_total{{operation=\"{op}\",code=\"{}\"}} {n}", Escape(code))?;
+            }
+        }
+
+        let name = "s3s_request_duration_seconds";
+        header(
+            w,
+            name,
+            "histogram",
+            "The time from receiving the request to sending the response headers.",
+        )?;
+        for (op, m) in &*operations {
+            let h = &m.duration;
+            for (le, n) in DURATION_BUCKETS.iter().zip(h.buckets) {
+                writeln!(w, "{name}_bucket{{operation=\"{op}\",le=\"{le}\"}} {n}")?;
+            }
+            writeln!(w, "{name}_bucket{{operation=\"{op}\",le=\"+Inf\"}} {}", h.count)?;
+            writeln!(w, "{name}_sum{{operation=\"{op}\"}} {}", h.sum)?;
+            writeln!(w, "{name}_count{{operation=\"{op}\"}} {}", h.count)?;
+        }
+
+        header(w, "s3s_received_bytes_total", "counter", "The number of request body bytes received.")?;
+        for (op, m) in &*operations {
+            writeln!(w, "s3s_received_bytes_total{{operation=\"{op}\"}} {}", m.received_bytes)?;
+        }
+
+        header(w, "s3s_sent_bytes_total", "counter", "The number of response body bytes sent.")?;
+        for (op, m) in &*operations {
+            writeln!(w, "s3s_sent_bytes_total{{operation=\"{op}\"}} {}", m.sent_bytes)?;
+        }
+
+        drop(operations);
+
+        header(w, "s3s_requests_in_flight", "gauge", "The number of requests being processed.")?;
+        writeln!(w, "s3s_requests_in_flight {}", self.inner.in_flight.load(Ordering::Relaxed))?;
+
+        header(
+            w,
+            "s3s_signature_failures_total",
+            "counter",
+            "The number of requests failing signature checks.",
+        )?;
+        writeln!(
+            w,
+            "s3s_signature_failures_total {}",
+            self.inner.signature_failures.load(Ordering::Relaxed)
+        )?;
+
+        Ok(())
+    }
+
+    /// Starts measuring a request.
+    pub(crate) fn start(&self, req: hyper::Request<Body>) -> (hyper::Request<Body>, PendingMetrics) {
+        self.inner.in_flight.fetch_add(1, Ordering::Relaxed);
+        let in_flight = InFlight(self.clone());
+
+        let received = Arc::new(AtomicU64::new(0));
+        let req = req.map(|body| {
+            let received = received.clone();
+            FinishBody::wrap(body, move |n| received.store(n, Ordering::Relaxed))
+        });
+
+        let pending = PendingMetrics {
+            start: Instant::now(),
+            received,
+            in_flight,
+        };
+        (req, pending)
+    }
+}
+
+fn header(w: &mut String, name: &str, ty: &str, help: &str) -> fmt::Result {
+    writeln!(w, "# HELP {name} {help}")?;
+    writeln!(w, "# TYPE {name} {ty}")
+}
+
+/// Escapes a label value.
+struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decrements the in-flight gauge when it is dropped.
+struct InFlight(Metrics);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.inner.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The measurements of a request which is being processed
+pub(crate) struct PendingMetrics {
+    start: Instant,
+    received: Arc<AtomicU64>,
+    in_flight: InFlight,
+}
+
+impl PendingMetrics {
+    /// Records the result of the call,
+    /// and wraps the response body to record the body sizes when the body is finished.
+    pub fn finish(self, req: &Request, result: S3Result<hyper::Response<Body>>) -> S3Result<hyper::Response<Body>> {
+        let op = req.s3ext.op_name.unwrap_or(UNKNOWN_OPERATION);
+        let duration = self.start.elapsed().as_secs_f64();
+
+        let error_code = match &result {
+            Ok(_) => req.s3ext.error_code.as_ref(),
+            Err(err) => Some(err.code()),
+        };
+
+        let metrics = &self.in_flight.0;
+        metrics.with_operation(op, |m| {
+            m.requests += 1;
+            m.duration.observe(duration);
+            if let Some(code) = error_code {
+                *m.errors.entry(code.as_str().to_owned()).or_default() += 1;
+            }
+        });
+
+        if req.s3ext.signature_failed {
+            metrics.inner.signature_failures.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let Self { received, in_flight, .. } = self;
+        let res = result?;
+        Ok(res.map(|body| {
+            FinishBody::wrap(body, move |sent| {
+                let received = received.load(Ordering::Relaxed);
+                in_flight.0.with_operation(op, |m| {
+                    m.received_bytes = m.received_bytes.saturating_add(received);
+                    m.sent_bytes = m.sent_bytes.saturating_add(sent);
+                });
+                drop(in_flight);
+            })
+        }))
+    }
+}
+
+/// A custom route which serves the metrics in the Prometheus text format
+///
+/// It matches `GET /metrics` by default, which shadows `ListObjects` of a bucket named `metrics`.
+/// The route does not require a signature, so that it can be scraped by Prometheus.
+#[derive(Debug)]
+pub struct MetricsRoute {
+    metrics: Metrics,
+    path: String,
+}
+
+impl MetricsRoute {
+    #[must_use]
+    pub fn new(metrics: Metrics) -> Self {
+        Self {
+            metrics,
+            path: "/metrics".to_owned(),
+        }
+    }
+
+    /// Sets the path of the route.
+    pub fn set_path(&mut self, path: impl Into<String>) {
+        self.path = path.into();
+    }
+}
+
+#[async_trait::async_trait]
+impl S3Route for MetricsRoute {
+    fn is_match(&self, method: &Method, uri: &Uri, _: &HeaderMap, _: &mut Extensions, _: Option<IpAddr>) -> bool {
+        method == Method::GET && uri.path() == self.path
+    }
+
+    fn name(&self) -> &'static str {
+        "GetMetrics"
+    }
+
+    async fn check_access(&self, _: &mut S3Request<Body>) -> S3Result<()> {
+        Ok(())
+    }
+
+    async fn call(&self, _: S3Request<Body>) -> S3Result<S3Response<(StatusCode, Body)>> {
+        let mut resp = S3Response::new((StatusCode::OK, Body::from(self.metrics.render())));
+        resp.headers
+            .insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static(CONTENT_TYPE));
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dto::{PutObjectInput, PutObjectOutput};
+    use crate::s3_trait::S3;
+    use crate::service::S3ServiceBuilder;
+
+    use http_body_util::BodyExt;
+
+    #[test]
+    fn histogram() {
+        let mut h = Histogram::default();
+        h.observe(0.003);
+        h.observe(0.2);
+        h.observe(20.0);
+        assert_eq!(h.buckets[0], 1);
+        assert_eq!(h.buckets[4], 1);
+        assert_eq!(h.buckets[5], 2);
+        assert_eq!(h.buckets[10], 2);
+        assert_eq!(h.count, 3);
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(Escape("a\"b\\c\nd").to_string(), "a\\\"b\\\\c\\nd");
+    }
+
+    struct Sink;
+
+    #[async_trait::async_trait]
+    impl S3 for Sink {
+        async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+            let mut body = req.input.body.unwrap();
+            while futures::StreamExt::next(&mut body).await.is_some() {}
+            Ok(S3Response::new(PutObjectOutput::default()))
+        }
+    }
+
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl S3Route for Echo {
+        fn is_match(&self, method: &Method, uri: &Uri, _: &HeaderMap, _: &mut Extensions, _: Option<IpAddr>) -> bool {
+            method == Method::POST && uri.path() == "/echo"
+        }
+
+        fn name(&self) -> &'static str {
+            "Echo"
+        }
+
+        async fn check_access(&self, _: &mut S3Request<Body>) -> S3Result<()> {
+            Ok(())
+        }
+
+        async fn call(&self, req: S3Request<Body>) -> S3Result<S3Response<(StatusCode, Body)>> {
+            Ok(S3Response::new((StatusCode::OK, req.input)))
+        }
+    }
+
+    #[tokio::test]
+    async fn service() {
+        let metrics = Metrics::new();
+        let service = {
+            let mut builder = S3ServiceBuilder::new(Sink);
+            builder.set_metrics(metrics.clone());
+            builder.add_route(MetricsRoute::new(metrics.clone()));
+            builder.add_route(Echo);
+            builder.build()
+        };
+
+        let req = hyper::Request::builder()
+            .method(Method::PUT)
+            .uri("/bucket/key")
+            .header(hyper::header::CONTENT_LENGTH, "5")
+            .body(Body::from("hello".to_owned()))
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        drop(res);
+
+        let req = hyper::Request::builder().uri("/bucket?tagging").body(Body::empty()).unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_IMPLEMENTED);
+        let sent = BodyExt::collect(res.into_body()).await.unwrap().to_bytes().len();
+
+        let req = hyper::Request::builder()
+            .uri("/bucket")
+            .header(hyper::header::AUTHORIZATION, "AWS4-HMAC-SHA256 invalid")
+            .body(Body::empty())
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert!(res.status().is_client_error());
+        drop(res);
+
+        // The request is in flight until the response body is finished.
+        let req = hyper::Request::builder()
+            .method(Method::POST)
+            .uri("/echo")
+            .body(Body::from("echo".to_owned()))
+            .unwrap();
+        let streaming = service.call(req).await.unwrap();
+
+        let scrape = || async {
+            let req = hyper::Request::builder().uri("/metrics").body(Body::empty()).unwrap();
+            let res = service.call(req).await.unwrap();
+            assert_eq!(res.headers().get(hyper::header::CONTENT_TYPE).unwrap(), CONTENT_TYPE);
+            let body = BodyExt::collect(res.into_body()).await.unwrap().to_bytes();
+            String::from_utf8(body.to_vec()).unwrap()
+        };
+
+        let text = scrape().await;
+        assert!(text.lines().any(|l| l == "s3s_requests_in_flight 2"), "{text}");
+
+        let body = BodyExt::collect(streaming.into_body()).await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), b"echo");
+
+        let text = scrape().await;
+
+        let expected = [
+            "s3s_requests_total{operation=\"PutObject\"} 1",
+            "s3s_requests_total{operation=\"GetBucketTagging\"} 1",
+            "s3s_errors_total{operation=\"GetBucketTagging\",code=\"NotImplemented\"} 1",
+            "s3s_request_duration_seconds_count{operation=\"PutObject\"} 1",
+            "s3s_request_duration_seconds_bucket{operation=\"PutObject\",le=\"+Inf\"} 1",
+            "s3s_received_bytes_total{operation=\"PutObject\"} 5",
+            &format!("s3s_sent_bytes_total{{operation=\"GetBucketTagging\"}} {sent}"),
+            "s3s_requests_total{operation=\"Echo\"} 1",
+            "s3s_received_bytes_total{operation=\"Echo\"} 4",
+            "s3s_sent_bytes_total{operation=\"Echo\"} 4",
+            "s3s_requests_total{operation=\"GetMetrics\"} 1",
+            "s3s_requests_in_flight 1",
+            "s3s_signature_failures_total 1",
+        ];
+        for line in expected {
+            assert!(text.lines().any(|l| l == line), "missing {line:?} in\n{text}");
+        }
+    }
+}
@@ -107,6 +107,7 @@ use crate::dto::*;
 use crate::error::*;
 use crate::header::*;
 use crate::http;
+use crate::interceptor;
 use crate::ops::CallContext;
 use crate::path::S3Path;
 
@@ -517,11 +518,11 @@ impl super::Operation for AbortMultipartUpload {
         if let Some(access) = ccx.access {
             access.abort_multipart_upload(&mut s3_req).await?;
         }
-        let result = s3.abort_multipart_upload(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "AbortMultipartUpload", s3_req, |s3_req| {
+            s3.abort_multipart_upload(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -618,15 +619,20 @@ impl super::Operation for CompleteMultipartUpload {
             access.complete_multipart_upload(&mut s3_req).await?;
         }
         let s3 = s3.clone();
+        let interceptors = ccx.interceptors.clone();
+        let request_id = ccx.request_id.clone();
         let fut = async move {
-            let result = s3.complete_multipart_upload(s3_req).await;
+            let result = interceptor::intercept(&interceptors, "CompleteMultipartUpload", s3_req, |s3_req| {
+                s3.complete_multipart_upload(s3_req)
+            })
+            .await;
             match result {
                 Ok(s3_resp) => {
                     let mut resp = Self::serialize_http(s3_resp.output)?;
                     resp.headers.extend(s3_resp.headers);
                     Ok(resp)
                 }
-                Err(err) => super::serialize_error(err, true).map_err(Into::into),
+                Err(err) => super::serialize_error(super::with_request_id(err, &request_id), true).map_err(Into::into),
             }
         };
         let mut resp = http::Response::with_status(http::StatusCode::OK);
@@ -824,11 +830,8 @@ impl super::Operation for CopyObject {
         if let Some(access) = ccx.access {
             access.copy_object(&mut s3_req).await?;
         }
-        let result = s3.copy_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "CopyObject", s3_req, |s3_req| s3.copy_object(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -895,11 +898,8 @@ impl super::Operation for CreateBucket {
         if let Some(access) = ccx.access {
             access.create_bucket(&mut s3_req).await?;
         }
-        let result = s3.create_bucket(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "CreateBucket", s3_req, |s3_req| s3.create_bucket(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -948,11 +948,11 @@ impl super::Operation for CreateBucketMetadataTableConfiguration {
         if let Some(access) = ccx.access {
             access.create_bucket_metadata_table_configuration(&mut s3_req).await?;
         }
-        let result = s3.create_bucket_metadata_table_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "CreateBucketMetadataTableConfiguration", s3_req, |s3_req| {
+            s3.create_bucket_metadata_table_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1097,11 +1097,11 @@ impl super::Operation for CreateMultipartUpload {
         if let Some(access) = ccx.access {
             access.create_multipart_upload(&mut s3_req).await?;
         }
-        let result = s3.create_multipart_upload(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "CreateMultipartUpload", s3_req, |s3_req| {
+            s3.create_multipart_upload(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1141,11 +1141,8 @@ impl super::Operation for DeleteBucket {
         if let Some(access) = ccx.access {
             access.delete_bucket(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucket", s3_req, |s3_req| s3.delete_bucket(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1188,11 +1185,11 @@ impl super::Operation for DeleteBucketAnalyticsConfiguration {
         if let Some(access) = ccx.access {
             access.delete_bucket_analytics_configuration(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_analytics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketAnalyticsConfiguration", s3_req, |s3_req| {
+            s3.delete_bucket_analytics_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1232,11 +1229,9 @@ impl super::Operation for DeleteBucketCors {
         if let Some(access) = ccx.access {
             access.delete_bucket_cors(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_cors(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "DeleteBucketCors", s3_req, |s3_req| s3.delete_bucket_cors(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1276,11 +1271,11 @@ impl super::Operation for DeleteBucketEncryption {
         if let Some(access) = ccx.access {
             access.delete_bucket_encryption(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_encryption(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketEncryption", s3_req, |s3_req| {
+            s3.delete_bucket_encryption(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1317,11 +1312,11 @@ impl super::Operation for DeleteBucketIntelligentTieringConfiguration {
         if let Some(access) = ccx.access {
             access.delete_bucket_intelligent_tiering_configuration(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_intelligent_tiering_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketIntelligentTieringConfiguration", s3_req, |s3_req| {
+            s3.delete_bucket_intelligent_tiering_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1364,11 +1359,11 @@ impl super::Operation for DeleteBucketInventoryConfiguration {
         if let Some(access) = ccx.access {
             access.delete_bucket_inventory_configuration(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_inventory_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketInventoryConfiguration", s3_req, |s3_req| {
+            s3.delete_bucket_inventory_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1408,11 +1403,11 @@ impl super::Operation for DeleteBucketLifecycle {
         if let Some(access) = ccx.access {
             access.delete_bucket_lifecycle(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_lifecycle(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketLifecycle", s3_req, |s3_req| {
+            s3.delete_bucket_lifecycle(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1452,11 +1447,11 @@ impl super::Operation for DeleteBucketMetadataTableConfiguration {
         if let Some(access) = ccx.access {
             access.delete_bucket_metadata_table_configuration(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_metadata_table_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketMetadataTableConfiguration", s3_req, |s3_req| {
+            s3.delete_bucket_metadata_table_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1499,11 +1494,11 @@ impl super::Operation for DeleteBucketMetricsConfiguration {
         if let Some(access) = ccx.access {
             access.delete_bucket_metrics_configuration(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_metrics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketMetricsConfiguration", s3_req, |s3_req| {
+            s3.delete_bucket_metrics_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1543,11 +1538,11 @@ impl super::Operation for DeleteBucketOwnershipControls {
         if let Some(access) = ccx.access {
             access.delete_bucket_ownership_controls(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_ownership_controls(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketOwnershipControls", s3_req, |s3_req| {
+            s3.delete_bucket_ownership_controls(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1587,11 +1582,10 @@ impl super::Operation for DeleteBucketPolicy {
         if let Some(access) = ccx.access {
             access.delete_bucket_policy(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_policy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "DeleteBucketPolicy", s3_req, |s3_req| s3.delete_bucket_policy(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1631,11 +1625,11 @@ impl super::Operation for DeleteBucketReplication {
         if let Some(access) = ccx.access {
             access.delete_bucket_replication(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_replication(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteBucketReplication", s3_req, |s3_req| {
+            s3.delete_bucket_replication(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1675,11 +1669,10 @@ impl super::Operation for DeleteBucketTagging {
         if let Some(access) = ccx.access {
             access.delete_bucket_tagging(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "DeleteBucketTagging", s3_req, |s3_req| s3.delete_bucket_tagging(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1719,11 +1712,10 @@ impl super::Operation for DeleteBucketWebsite {
         if let Some(access) = ccx.access {
             access.delete_bucket_website(&mut s3_req).await?;
         }
-        let result = s3.delete_bucket_website(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "DeleteBucketWebsite", s3_req, |s3_req| s3.delete_bucket_website(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1791,11 +1783,8 @@ impl super::Operation for DeleteObject {
         if let Some(access) = ccx.access {
             access.delete_object(&mut s3_req).await?;
         }
-        let result = s3.delete_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteObject", s3_req, |s3_req| s3.delete_object(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1841,11 +1830,10 @@ impl super::Operation for DeleteObjectTagging {
         if let Some(access) = ccx.access {
             access.delete_object_tagging(&mut s3_req).await?;
         }
-        let result = s3.delete_object_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "DeleteObjectTagging", s3_req, |s3_req| s3.delete_object_tagging(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1904,11 +1892,8 @@ impl super::Operation for DeleteObjects {
         if let Some(access) = ccx.access {
             access.delete_objects(&mut s3_req).await?;
         }
-        let result = s3.delete_objects(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeleteObjects", s3_req, |s3_req| s3.delete_objects(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1948,11 +1933,11 @@ impl super::Operation for DeletePublicAccessBlock {
         if let Some(access) = ccx.access {
             access.delete_public_access_block(&mut s3_req).await?;
         }
-        let result = s3.delete_public_access_block(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "DeletePublicAccessBlock", s3_req, |s3_req| {
+            s3.delete_public_access_block(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -1998,11 +1983,11 @@ impl super::Operation for GetBucketAccelerateConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_accelerate_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_accelerate_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketAccelerateConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_accelerate_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2044,11 +2029,8 @@ impl super::Operation for GetBucketAcl {
         if let Some(access) = ccx.access {
             access.get_bucket_acl(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketAcl", s3_req, |s3_req| s3.get_bucket_acl(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2095,11 +2077,11 @@ impl super::Operation for GetBucketAnalyticsConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_analytics_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_analytics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketAnalyticsConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_analytics_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2141,11 +2123,8 @@ impl super::Operation for GetBucketCors {
         if let Some(access) = ccx.access {
             access.get_bucket_cors(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_cors(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketCors", s3_req, |s3_req| s3.get_bucket_cors(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2189,11 +2168,10 @@ impl super::Operation for GetBucketEncryption {
         if let Some(access) = ccx.access {
             access.get_bucket_encryption(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_encryption(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketEncryption", s3_req, |s3_req| s3.get_bucket_encryption(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2234,11 +2212,11 @@ impl super::Operation for GetBucketIntelligentTieringConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_intelligent_tiering_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_intelligent_tiering_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketIntelligentTieringConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_intelligent_tiering_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2285,11 +2263,11 @@ impl super::Operation for GetBucketInventoryConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_inventory_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_inventory_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketInventoryConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_inventory_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2336,11 +2314,11 @@ impl super::Operation for GetBucketLifecycleConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_lifecycle_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_lifecycle_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketLifecycleConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_lifecycle_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2382,11 +2360,9 @@ impl super::Operation for GetBucketLocation {
         if let Some(access) = ccx.access {
             access.get_bucket_location(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_location(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketLocation", s3_req, |s3_req| s3.get_bucket_location(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2428,11 +2404,9 @@ impl super::Operation for GetBucketLogging {
         if let Some(access) = ccx.access {
             access.get_bucket_logging(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_logging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketLogging", s3_req, |s3_req| s3.get_bucket_logging(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2476,11 +2450,11 @@ impl super::Operation for GetBucketMetadataTableConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_metadata_table_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_metadata_table_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketMetadataTableConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_metadata_table_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2527,11 +2501,11 @@ impl super::Operation for GetBucketMetricsConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_metrics_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_metrics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketMetricsConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_metrics_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2573,11 +2547,11 @@ impl super::Operation for GetBucketNotificationConfiguration {
         if let Some(access) = ccx.access {
             access.get_bucket_notification_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_notification_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketNotificationConfiguration", s3_req, |s3_req| {
+            s3.get_bucket_notification_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2621,11 +2595,11 @@ impl super::Operation for GetBucketOwnershipControls {
         if let Some(access) = ccx.access {
             access.get_bucket_ownership_controls(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_ownership_controls(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketOwnershipControls", s3_req, |s3_req| {
+            s3.get_bucket_ownership_controls(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2669,11 +2643,9 @@ impl super::Operation for GetBucketPolicy {
         if let Some(access) = ccx.access {
             access.get_bucket_policy(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_policy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketPolicy", s3_req, |s3_req| s3.get_bucket_policy(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2717,11 +2689,11 @@ impl super::Operation for GetBucketPolicyStatus {
         if let Some(access) = ccx.access {
             access.get_bucket_policy_status(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_policy_status(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketPolicyStatus", s3_req, |s3_req| {
+            s3.get_bucket_policy_status(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2765,11 +2737,11 @@ impl super::Operation for GetBucketReplication {
         if let Some(access) = ccx.access {
             access.get_bucket_replication(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_replication(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketReplication", s3_req, |s3_req| {
+            s3.get_bucket_replication(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2811,11 +2783,11 @@ impl super::Operation for GetBucketRequestPayment {
         if let Some(access) = ccx.access {
             access.get_bucket_request_payment(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_request_payment(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetBucketRequestPayment", s3_req, |s3_req| {
+            s3.get_bucket_request_payment(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2857,11 +2829,9 @@ impl super::Operation for GetBucketTagging {
         if let Some(access) = ccx.access {
             access.get_bucket_tagging(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketTagging", s3_req, |s3_req| s3.get_bucket_tagging(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2903,11 +2873,10 @@ impl super::Operation for GetBucketVersioning {
         if let Some(access) = ccx.access {
             access.get_bucket_versioning(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_versioning(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketVersioning", s3_req, |s3_req| s3.get_bucket_versioning(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2949,11 +2918,9 @@ impl super::Operation for GetBucketWebsite {
         if let Some(access) = ccx.access {
             access.get_bucket_website(&mut s3_req).await?;
         }
-        let result = s3.get_bucket_website(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetBucketWebsite", s3_req, |s3_req| s3.get_bucket_website(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3104,11 +3071,8 @@ impl super::Operation for GetObject {
             access.get_object(&mut s3_req).await?;
         }
         let overridden_headers = super::get_object::extract_overridden_response_headers(&s3_req)?;
-        let result = s3.get_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetObject", s3_req, |s3_req| s3.get_object(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(overridden_headers);
         super::get_object::merge_custom_headers(&mut resp, s3_resp.headers);
@@ -3159,11 +3123,8 @@ impl super::Operation for GetObjectAcl {
         if let Some(access) = ccx.access {
             access.get_object_acl(&mut s3_req).await?;
         }
-        let result = s3.get_object_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetObjectAcl", s3_req, |s3_req| s3.get_object_acl(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3236,11 +3197,10 @@ impl super::Operation for GetObjectAttributes {
         if let Some(access) = ccx.access {
             access.get_object_attributes(&mut s3_req).await?;
         }
-        let result = s3.get_object_attributes(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetObjectAttributes", s3_req, |s3_req| s3.get_object_attributes(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3291,11 +3251,10 @@ impl super::Operation for GetObjectLegalHold {
         if let Some(access) = ccx.access {
             access.get_object_legal_hold(&mut s3_req).await?;
         }
-        let result = s3.get_object_legal_hold(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetObjectLegalHold", s3_req, |s3_req| s3.get_object_legal_hold(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3339,11 +3298,11 @@ impl super::Operation for GetObjectLockConfiguration {
         if let Some(access) = ccx.access {
             access.get_object_lock_configuration(&mut s3_req).await?;
         }
-        let result = s3.get_object_lock_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetObjectLockConfiguration", s3_req, |s3_req| {
+            s3.get_object_lock_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3394,11 +3353,10 @@ impl super::Operation for GetObjectRetention {
         if let Some(access) = ccx.access {
             access.get_object_retention(&mut s3_req).await?;
         }
-        let result = s3.get_object_retention(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetObjectRetention", s3_req, |s3_req| s3.get_object_retention(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3448,11 +3406,9 @@ impl super::Operation for GetObjectTagging {
         if let Some(access) = ccx.access {
             access.get_object_tagging(&mut s3_req).await?;
         }
-        let result = s3.get_object_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetObjectTagging", s3_req, |s3_req| s3.get_object_tagging(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3501,11 +3457,9 @@ impl super::Operation for GetObjectTorrent {
         if let Some(access) = ccx.access {
             access.get_object_torrent(&mut s3_req).await?;
         }
-        let result = s3.get_object_torrent(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "GetObjectTorrent", s3_req, |s3_req| s3.get_object_torrent(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3549,11 +3503,11 @@ impl super::Operation for GetPublicAccessBlock {
         if let Some(access) = ccx.access {
             access.get_public_access_block(&mut s3_req).await?;
         }
-        let result = s3.get_public_access_block(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "GetPublicAccessBlock", s3_req, |s3_req| {
+            s3.get_public_access_block(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3598,11 +3552,8 @@ impl super::Operation for HeadBucket {
         if let Some(access) = ccx.access {
             access.head_bucket(&mut s3_req).await?;
         }
-        let result = s3.head_bucket(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "HeadBucket", s3_req, |s3_req| s3.head_bucket(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3746,11 +3697,8 @@ impl super::Operation for HeadObject {
         if let Some(access) = ccx.access {
             access.head_object(&mut s3_req).await?;
         }
-        let result = s3.head_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "HeadObject", s3_req, |s3_req| s3.head_object(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3795,11 +3743,11 @@ impl super::Operation for ListBucketAnalyticsConfigurations {
         if let Some(access) = ccx.access {
             access.list_bucket_analytics_configurations(&mut s3_req).await?;
         }
-        let result = s3.list_bucket_analytics_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListBucketAnalyticsConfigurations", s3_req, |s3_req| {
+            s3.list_bucket_analytics_configurations(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3841,11 +3789,11 @@ impl super::Operation for ListBucketIntelligentTieringConfigurations {
         if let Some(access) = ccx.access {
             access.list_bucket_intelligent_tiering_configurations(&mut s3_req).await?;
         }
-        let result = s3.list_bucket_intelligent_tiering_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListBucketIntelligentTieringConfigurations", s3_req, |s3_req| {
+            s3.list_bucket_intelligent_tiering_configurations(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3890,11 +3838,11 @@ impl super::Operation for ListBucketInventoryConfigurations {
         if let Some(access) = ccx.access {
             access.list_bucket_inventory_configurations(&mut s3_req).await?;
         }
-        let result = s3.list_bucket_inventory_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListBucketInventoryConfigurations", s3_req, |s3_req| {
+            s3.list_bucket_inventory_configurations(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3939,11 +3887,11 @@ impl super::Operation for ListBucketMetricsConfigurations {
         if let Some(access) = ccx.access {
             access.list_bucket_metrics_configurations(&mut s3_req).await?;
         }
-        let result = s3.list_bucket_metrics_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListBucketMetricsConfigurations", s3_req, |s3_req| {
+            s3.list_bucket_metrics_configurations(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -3991,11 +3939,8 @@ impl super::Operation for ListBuckets {
         if let Some(access) = ccx.access {
             access.list_buckets(&mut s3_req).await?;
         }
-        let result = s3.list_buckets(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListBuckets", s3_req, |s3_req| s3.list_buckets(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4059,11 +4004,11 @@ impl super::Operation for ListMultipartUploads {
         if let Some(access) = ccx.access {
             access.list_multipart_uploads(&mut s3_req).await?;
         }
-        let result = s3.list_multipart_uploads(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListMultipartUploads", s3_req, |s3_req| {
+            s3.list_multipart_uploads(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4131,11 +4076,10 @@ impl super::Operation for ListObjectVersions {
         if let Some(access) = ccx.access {
             access.list_object_versions(&mut s3_req).await?;
         }
-        let result = s3.list_object_versions(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "ListObjectVersions", s3_req, |s3_req| s3.list_object_versions(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4200,11 +4144,8 @@ impl super::Operation for ListObjects {
         if let Some(access) = ccx.access {
             access.list_objects(&mut s3_req).await?;
         }
-        let result = s3.list_objects(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListObjects", s3_req, |s3_req| s3.list_objects(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4275,11 +4216,8 @@ impl super::Operation for ListObjectsV2 {
         if let Some(access) = ccx.access {
             access.list_objects_v2(&mut s3_req).await?;
         }
-        let result = s3.list_objects_v2(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListObjectsV2", s3_req, |s3_req| s3.list_objects_v2(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4348,11 +4286,8 @@ impl super::Operation for ListParts {
         if let Some(access) = ccx.access {
             access.list_parts(&mut s3_req).await?;
         }
-        let result = s3.list_parts(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "ListParts", s3_req, |s3_req| s3.list_parts(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4398,11 +4333,11 @@ impl super::Operation for PutBucketAccelerateConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_accelerate_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_accelerate_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketAccelerateConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_accelerate_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4469,11 +4404,8 @@ impl super::Operation for PutBucketAcl {
         if let Some(access) = ccx.access {
             access.put_bucket_acl(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketAcl", s3_req, |s3_req| s3.put_bucket_acl(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4519,11 +4451,11 @@ impl super::Operation for PutBucketAnalyticsConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_analytics_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_analytics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketAnalyticsConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_analytics_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4572,11 +4504,8 @@ impl super::Operation for PutBucketCors {
         if let Some(access) = ccx.access {
             access.put_bucket_cors(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_cors(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketCors", s3_req, |s3_req| s3.put_bucket_cors(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4625,11 +4554,10 @@ impl super::Operation for PutBucketEncryption {
         if let Some(access) = ccx.access {
             access.put_bucket_encryption(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_encryption(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutBucketEncryption", s3_req, |s3_req| s3.put_bucket_encryption(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4672,11 +4600,11 @@ impl super::Operation for PutBucketIntelligentTieringConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_intelligent_tiering_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_intelligent_tiering_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketIntelligentTieringConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_intelligent_tiering_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4722,11 +4650,11 @@ impl super::Operation for PutBucketInventoryConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_inventory_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_inventory_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketInventoryConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_inventory_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4782,11 +4710,11 @@ impl super::Operation for PutBucketLifecycleConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_lifecycle_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_lifecycle_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketLifecycleConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_lifecycle_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4835,11 +4763,9 @@ impl super::Operation for PutBucketLogging {
         if let Some(access) = ccx.access {
             access.put_bucket_logging(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_logging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutBucketLogging", s3_req, |s3_req| s3.put_bucket_logging(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4885,11 +4811,11 @@ impl super::Operation for PutBucketMetricsConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_metrics_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_metrics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketMetricsConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_metrics_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4936,11 +4862,11 @@ impl super::Operation for PutBucketNotificationConfiguration {
         if let Some(access) = ccx.access {
             access.put_bucket_notification_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_notification_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketNotificationConfiguration", s3_req, |s3_req| {
+            s3.put_bucket_notification_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -4986,11 +4912,11 @@ impl super::Operation for PutBucketOwnershipControls {
         if let Some(access) = ccx.access {
             access.put_bucket_ownership_controls(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_ownership_controls(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketOwnershipControls", s3_req, |s3_req| {
+            s3.put_bucket_ownership_controls(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5043,11 +4969,9 @@ impl super::Operation for PutBucketPolicy {
         if let Some(access) = ccx.access {
             access.put_bucket_policy(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_policy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutBucketPolicy", s3_req, |s3_req| s3.put_bucket_policy(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5099,11 +5023,11 @@ impl super::Operation for PutBucketReplication {
         if let Some(access) = ccx.access {
             access.put_bucket_replication(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_replication(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketReplication", s3_req, |s3_req| {
+            s3.put_bucket_replication(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5152,11 +5076,11 @@ impl super::Operation for PutBucketRequestPayment {
         if let Some(access) = ccx.access {
             access.put_bucket_request_payment(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_request_payment(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutBucketRequestPayment", s3_req, |s3_req| {
+            s3.put_bucket_request_payment(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5205,11 +5129,9 @@ impl super::Operation for PutBucketTagging {
         if let Some(access) = ccx.access {
             access.put_bucket_tagging(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutBucketTagging", s3_req, |s3_req| s3.put_bucket_tagging(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5261,11 +5183,10 @@ impl super::Operation for PutBucketVersioning {
         if let Some(access) = ccx.access {
             access.put_bucket_versioning(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_versioning(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutBucketVersioning", s3_req, |s3_req| s3.put_bucket_versioning(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5314,11 +5235,9 @@ impl super::Operation for PutBucketWebsite {
         if let Some(access) = ccx.access {
             access.put_bucket_website(&mut s3_req).await?;
         }
-        let result = s3.put_bucket_website(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutBucketWebsite", s3_req, |s3_req| s3.put_bucket_website(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5468,15 +5387,12 @@ impl PutObject {
 
     pub fn deserialize_http_multipart(req: &mut http::Request, m: http::Multipart) -> S3Result<PutObjectInput> {
         let bucket = http::unwrap_bucket(req);
-        let key = http::parse_field_value(&m, "key")?.ok_or_else(|| invalid_request!("missing key"))?;
-
-        let vec_stream = req.s3ext.vec_stream.take().expect("missing vec stream");
+        let key = m.object_key().ok_or_else(|| invalid_request!("missing key"))?;
 
-        let content_length = i64::try_from(vec_stream.exact_remaining_length())
-            .map_err(|e| s3_error!(e, InvalidArgument, "content-length overflow"))?;
-        let content_length = (content_length != 0).then_some(content_length);
+        let body: Option<StreamingBlob> = req.s3ext.file_stream.take();
 
-        let body: Option<StreamingBlob> = Some(StreamingBlob::new(vec_stream));
+        // the length of the file is unknown until the stream ends, unless the whole file has been received
+        let content_length: Option<ContentLength> = body.as_ref().and_then(http::file_content_length);
 
         let acl: Option<ObjectCannedACL> = http::parse_field_value(&m, "x-amz-acl")?;
 
@@ -5651,11 +5567,8 @@ impl super::Operation for PutObject {
         if let Some(access) = ccx.access {
             access.put_object(&mut s3_req).await?;
         }
-        let result = s3.put_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutObject", s3_req, |s3_req| s3.put_object(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5731,11 +5644,8 @@ impl super::Operation for PutObjectAcl {
         if let Some(access) = ccx.access {
             access.put_object_acl(&mut s3_req).await?;
         }
-        let result = s3.put_object_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutObjectAcl", s3_req, |s3_req| s3.put_object_acl(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5793,11 +5703,10 @@ impl super::Operation for PutObjectLegalHold {
         if let Some(access) = ccx.access {
             access.put_object_legal_hold(&mut s3_req).await?;
         }
-        let result = s3.put_object_legal_hold(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutObjectLegalHold", s3_req, |s3_req| s3.put_object_legal_hold(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5854,11 +5763,11 @@ impl super::Operation for PutObjectLockConfiguration {
         if let Some(access) = ccx.access {
             access.put_object_lock_configuration(&mut s3_req).await?;
         }
-        let result = s3.put_object_lock_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutObjectLockConfiguration", s3_req, |s3_req| {
+            s3.put_object_lock_configuration(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5920,11 +5829,10 @@ impl super::Operation for PutObjectRetention {
         if let Some(access) = ccx.access {
             access.put_object_retention(&mut s3_req).await?;
         }
-        let result = s3.put_object_retention(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutObjectRetention", s3_req, |s3_req| s3.put_object_retention(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -5982,11 +5890,9 @@ impl super::Operation for PutObjectTagging {
         if let Some(access) = ccx.access {
             access.put_object_tagging(&mut s3_req).await?;
         }
-        let result = s3.put_object_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "PutObjectTagging", s3_req, |s3_req| s3.put_object_tagging(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6035,11 +5941,11 @@ impl super::Operation for PutPublicAccessBlock {
         if let Some(access) = ccx.access {
             access.put_public_access_block(&mut s3_req).await?;
         }
-        let result = s3.put_public_access_block(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "PutPublicAccessBlock", s3_req, |s3_req| {
+            s3.put_public_access_block(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6095,11 +6001,8 @@ impl super::Operation for RestoreObject {
         if let Some(access) = ccx.access {
             access.restore_object(&mut s3_req).await?;
         }
-        let result = s3.restore_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "RestoreObject", s3_req, |s3_req| s3.restore_object(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6158,11 +6061,10 @@ impl super::Operation for SelectObjectContent {
         if let Some(access) = ccx.access {
             access.select_object_content(&mut s3_req).await?;
         }
-        let result = s3.select_object_content(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "SelectObjectContent", s3_req, |s3_req| s3.select_object_content(s3_req))
+                .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6263,11 +6165,8 @@ impl super::Operation for UploadPart {
         if let Some(access) = ccx.access {
             access.upload_part(&mut s3_req).await?;
         }
-        let result = s3.upload_part(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "UploadPart", s3_req, |s3_req| s3.upload_part(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6375,11 +6274,9 @@ impl super::Operation for UploadPartCopy {
         if let Some(access) = ccx.access {
             access.upload_part_copy(&mut s3_req).await?;
         }
-        let result = s3.upload_part_copy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result =
+            interceptor::intercept(ccx.interceptors, "UploadPartCopy", s3_req, |s3_req| s3.upload_part_copy(s3_req)).await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6550,11 +6447,11 @@ impl super::Operation for WriteGetObjectResponse {
         if let Some(access) = ccx.access {
             access.write_get_object_response(&mut s3_req).await?;
         }
-        let result = s3.write_get_object_response(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err, false),
-        };
+        let result = interceptor::intercept(ccx.interceptors, "WriteGetObjectResponse", s3_req, |s3_req| {
+            s3.write_get_object_response(s3_req)
+        })
+        .await;
+        let s3_resp = result?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -6,11 +6,15 @@ use self::signature::SignatureContext;
 
 mod get_object;
 
+mod post_object;
+pub use self::post_object::PostObjectContext;
+
 #[cfg(test)]
 mod tests;
 
 use crate::access::{S3Access, S3AccessContext};
-use crate::auth::{Credentials, S3Auth};
+use crate::auth::{Credentials, S3Auth, SignaturePolicy};
+use crate::checksum::BodyChecksums;
 use crate::error::*;
 use crate::header;
 use crate::host::S3Host;
@@ -18,12 +22,13 @@ use crate::http;
 use crate::http::Body;
 use crate::http::{OrderedHeaders, OrderedQs};
 use crate::http::{Request, Response};
+use crate::interceptor::S3Interceptor;
 use crate::path::{ParseS3PathError, S3Path};
+use crate::region::Region;
 use crate::request::S3Request;
+use crate::request_id::RequestId;
 use crate::route::S3Route;
 use crate::s3_trait::S3;
-use crate::stream::VecByteStream;
-use crate::stream::aggregate_unlimited;
 
 use std::mem;
 use std::net::{IpAddr, SocketAddr};
@@ -51,7 +56,12 @@ pub struct CallContext<'a> {
     pub host: Option<&'a dyn S3Host>,
     pub auth: Option<&'a dyn S3Auth>,
     pub access: Option<&'a dyn S3Access>,
-    pub route: Option<&'a dyn S3Route>,
+    pub routes: &'a [Box<dyn S3Route>],
+    pub interceptors: &'a Arc<[Box<dyn S3Interceptor>]>,
+    pub request_id: &'a RequestId,
+    pub regions: &'a [Region],
+    pub signature_policy: &'a SignaturePolicy,
+    pub verify_checksums: bool,
 }
 
 fn build_s3_request<T>(input: T, req: &mut Request) -> S3Request<T> {
@@ -62,6 +72,10 @@ fn build_s3_request<T>(input: T, req: &mut Request) -> S3Request<T> {
     let credentials = req.s3ext.credentials.take();
     let region = req.s3ext.region.take();
     let service = req.s3ext.service.take();
+    let trailing_headers = req.s3ext.trailing_headers.take();
+    let body_checksums = req.s3ext.body_checksums.clone();
+    let connection = req.s3ext.connection.clone();
+    let client_ip = req.s3ext.client_ip;
 
     S3Request {
         method,
@@ -72,13 +86,19 @@ fn build_s3_request<T>(input: T, req: &mut Request) -> S3Request<T> {
         credentials,
         region,
         service,
+        trailing_headers,
+        body_checksums,
+        connection,
+        client_ip,
     }
 }
 
 pub(crate) fn serialize_error(mut e: S3Error, no_decl: bool) -> S3Result<Response> {
     let status = e.status_code().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
     let mut res = Response::with_status(status);
-    if no_decl {
+    if status == StatusCode::NOT_MODIFIED {
+        // a 304 response must not contain a body
+    } else if no_decl {
         http::set_xml_body_no_decl(&mut res, &e)?;
     } else {
         http::set_xml_body(&mut res, &e)?;
@@ -90,6 +110,31 @@ pub(crate) fn serialize_error(mut e: S3Error, no_decl: bool) -> S3Result<Respons
     Ok(res)
 }
 
+fn serialize_call_error(req: &mut Request, ccx: &CallContext<'_>, err: S3Error) -> S3Result<Response> {
+    req.s3ext.error_code = Some(err.code().clone());
+    serialize_error(with_request_id(err, ccx.request_id), false)
+}
+
+/// Attaches the request id to the error, unless it already has one.
+fn with_request_id(mut e: S3Error, request_id: &RequestId) -> S3Error {
+    if e.request_id().is_none() {
+        e.set_request_id(request_id.id());
+    }
+    e
+}
+
+fn set_request_id_headers(res: &mut Response, request_id: &RequestId) {
+    let pairs = [
+        (header::X_AMZ_REQUEST_ID, request_id.id()),
+        (header::X_AMZ_ID_2, request_id.host_id()),
+    ];
+    for (name, val) in pairs {
+        if let Ok(val) = HeaderValue::try_from(val) {
+            res.headers.entry(name).or_insert(val);
+        }
+    }
+}
+
 fn unknown_operation() -> S3Error {
     S3Error::with_message(S3ErrorCode::NotImplemented, "Unknown operation")
 }
@@ -185,30 +230,42 @@ fn fmt_content_length(len: usize) -> http::HeaderValue {
 }
 
 pub async fn call(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Response> {
+    req.extensions.insert(ccx.request_id.clone());
+    let mut res = call_inner(req, ccx).await?;
+    set_request_id_headers(&mut res, ccx.request_id);
+    Ok(res)
+}
+
+async fn call_inner(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Response> {
     let prep = match prepare(req, ccx).await {
         Ok(op) => op,
         Err(err) => {
             debug!(?err, "failed to prepare");
-            return serialize_error(err, false);
+            return serialize_call_error(req, ccx, err);
         }
     };
 
     match prep {
-        Prepare::S3(op) => {
-            match op.call(ccx, req).await {
-                Ok(resp) => {
-                    Ok(resp) //
-                }
-                Err(err) => {
-                    debug!(op = %op.name(), ?err, "op returns error");
-                    serialize_error(err, false)
-                }
+        Prepare::S3(op) => match op.call(ccx, req).await {
+            Ok(resp) => match req.s3ext.post_object.take() {
+                Some(post_object) => post_object.transform_response(resp),
+                None => Ok(resp),
+            },
+            Err(err) => {
+                debug!(op = %op.name(), ?err, "op returns error");
+                // the body stream has failed the verification
+                let err = req
+                    .s3ext
+                    .body_checksums
+                    .as_ref()
+                    .and_then(BodyChecksums::error)
+                    .unwrap_or(err);
+                serialize_call_error(req, ccx, err)
             }
-        }
-        Prepare::CustomRoute => {
+        },
+        Prepare::CustomRoute(route) => {
             let body = mem::take(&mut req.body);
             let mut s3_req = build_s3_request(body, req);
-            let route = ccx.route.unwrap();
 
             let result = async {
                 route.check_access(&mut s3_req).await?;
@@ -225,22 +282,23 @@ pub async fn call(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Response
                 }),
                 Err(err) => {
                     debug!(?err, "custom route returns error");
-                    serialize_error(err, false)
+                    serialize_call_error(req, ccx, err)
                 }
             }
         }
     }
 }
 
-enum Prepare {
+enum Prepare<'a> {
     S3(&'static dyn Operation),
-    CustomRoute,
+    CustomRoute(&'a dyn S3Route),
 }
 
 #[allow(clippy::too_many_lines)]
-async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare> {
+async fn prepare<'a>(req: &mut Request, ccx: &CallContext<'a>) -> S3Result<Prepare<'a>> {
     let s3_path;
     let mut content_length;
+    let post_policy;
     {
         let decoded_uri_path = urlencoding::decode(req.uri.path())
             .map_err(|_| S3ErrorCode::InvalidURI)?
@@ -249,6 +307,7 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
         let host_header = extract_host(req)?;
         let vh;
         let vh_bucket;
+        let vh_region;
         {
             let result = 'parse: {
                 if let (Some(host_header), Some(s3_host)) = (host_header.as_deref(), ccx.host) {
@@ -259,12 +318,15 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
                         debug!(?vh);
 
                         vh_bucket = vh.bucket();
+                        vh_region = vh.region();
+                        req.extensions.insert(vh.clone().into_owned());
                         break 'parse crate::path::parse_virtual_hosted_style(vh_bucket, &decoded_uri_path);
                     }
                 }
 
                 debug!(?decoded_uri_path, "parsing path-style request");
                 vh_bucket = None;
+                vh_region = None;
                 crate::path::parse_path_style(&decoded_uri_path)
             };
 
@@ -284,6 +346,7 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
         {
             let mut scx = SignatureContext {
                 auth: ccx.auth,
+                policy: ccx.signature_policy,
 
                 req_method: &req.method,
                 req_uri: &req.uri,
@@ -294,27 +357,45 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
 
                 decoded_uri_path,
                 vh_bucket,
+                vh_region,
+                regions: ccx.regions,
 
                 content_length,
                 decoded_content_length,
                 mime,
 
                 multipart: None,
+                post_policy: None,
                 transformed_body: None,
+                trailing_headers: None,
             };
 
-            let credentials = scx.check().await?;
+            let credentials = match scx.check().await {
+                Ok(credentials) => credentials,
+                Err(err) => {
+                    #[cfg(feature = "metrics")]
+                    {
+                        req.s3ext.signature_failed = true;
+                    }
+                    return Err(err);
+                }
+            };
 
             body_changed = scx.transformed_body.is_some() || scx.multipart.is_some();
             transformed_body = scx.transformed_body;
 
             req.s3ext.multipart = scx.multipart;
+            post_policy = scx.post_policy;
+            req.s3ext.trailing_headers = scx.trailing_headers;
 
             match credentials {
                 Some(cred) => {
+                    req.s3ext.requester = Some(cred.access_key.clone());
                     req.s3ext.credentials = Some(Credentials {
                         access_key: cred.access_key,
                         secret_key: cred.secret_key,
+                        session_token: cred.session_token,
+                        identity: cred.identity,
                     });
                     req.s3ext.region = cred.region;
                     req.s3ext.service = cred.service;
@@ -323,6 +404,11 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
                     req.s3ext.credentials = None;
                 }
             }
+
+            if req.s3ext.region.is_none() {
+                let vh_region = vh_region.and_then(|r| Region::new(r).ok());
+                req.s3ext.region = vh_region.or_else(|| ccx.regions.first().cloned());
+            }
         }
 
         if body_changed {
@@ -342,9 +428,10 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
         debug!(?body_changed, ?decoded_content_length, ?has_multipart);
     }
 
-    if let Some(route) = ccx.route {
-        if route.is_match(&req.method, &req.uri, &req.headers, &mut req.extensions) {
-            return Ok(Prepare::CustomRoute);
+    for route in ccx.routes {
+        if route.is_match(&req.method, &req.uri, &req.headers, &mut req.extensions, req.s3ext.client_ip) {
+            req.s3ext.op_name = Some(route.name());
+            return Ok(Prepare::CustomRoute(&**route));
         }
     }
 
@@ -353,13 +440,15 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
             if req.method == Method::POST {
                 match s3_path {
                     S3Path::Root => return Err(unknown_operation()),
-                    S3Path::Bucket { .. } => {
+                    S3Path::Bucket { bucket } => {
                         // POST object
                         debug!(?multipart);
+                        if let Some(post_policy) = &post_policy {
+                            post_policy.check_fields(bucket, multipart.fields())?;
+                        }
+                        req.s3ext.post_object = Some(PostObjectContext::extract(bucket, multipart)?);
                         let file_stream = multipart.take_file_stream().expect("missing file stream");
-                        let vec_bytes = aggregate_unlimited(file_stream).await.map_err(S3Error::internal_error)?;
-                        let vec_stream = VecByteStream::new(vec_bytes);
-                        req.s3ext.vec_stream = Some(vec_stream);
+                        req.s3ext.file_stream = Some(post_object::file_body(file_stream, post_policy.as_ref()));
                         break 'resolve (&PutObject as &'static dyn Operation, false);
                     }
                     // FIXME: POST /bucket/key hits this branch
@@ -380,12 +469,15 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
     }
 
     debug!(op = %op.name(), ?s3_path, "resolved route");
+    req.s3ext.op_name = Some(op.name());
 
     if ccx.auth.is_some() {
         let mut acx = S3AccessContext {
             credentials: req.s3ext.credentials.as_ref(),
             s3_path,
             s3_op: &crate::S3Operation { name: op.name() },
+            connection: req.s3ext.connection.as_ref(),
+            client_ip: req.s3ext.client_ip,
             method: &req.method,
             uri: &req.uri,
             headers: &req.headers,
@@ -399,6 +491,13 @@ async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Prepare>
 
     debug!(op = %op.name(), ?s3_path, "checked access");
 
+    if ccx.verify_checksums && matches!(op.name(), "PutObject" | "UploadPart") && req.s3ext.post_object.is_none() {
+        let body = mem::take(&mut req.body);
+        let (body, checksums) = crate::checksum::verify_body(body, &req.headers, req.s3ext.trailing_headers.clone())?;
+        req.body = body;
+        req.s3ext.body_checksums = Some(checksums);
+    }
+
     if needs_full_body {
         extract_full_body(content_length, &mut req.body).await?;
     }
@@ -0,0 +1,284 @@
+//! POST Object
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/API/RESTObjectPOST.html>
+
+use crate::dto::StreamingBlob;
+use crate::error::*;
+use crate::header;
+use crate::http::{FileStream, Multipart, Response};
+use crate::post_policy::{self, PostPolicy};
+use crate::stream::{ByteStream, RemainingLength};
+use crate::xml;
+
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use bytes::Bytes;
+use futures::Stream;
+use hyper::StatusCode;
+use hyper::Uri;
+use hyper::http::HeaderValue;
+
+/// The maximum size of an object uploaded in a single request (5 GiB)
+const MAX_OBJECT_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// The form fields which control the response of POST Object
+pub struct PostObjectContext {
+    bucket: String,
+    key: String,
+    success_action_redirect: Option<String>,
+    success_action_status: Option<String>,
+}
+
+impl PostObjectContext {
+    pub fn extract(bucket: &str, m: &Multipart) -> S3Result<Self> {
+        let key = m.object_key().ok_or_else(|| invalid_request!("missing key"))?;
+        let success_action_redirect = m
+            .find_field_value("success_action_redirect")
+            .or_else(|| m.find_field_value("redirect"));
+        let success_action_status = m.find_field_value("success_action_status");
+        Ok(Self {
+            bucket: bucket.to_owned(),
+            key,
+            success_action_redirect: success_action_redirect.map(ToOwned::to_owned),
+            success_action_status: success_action_status.map(ToOwned::to_owned),
+        })
+    }
+
+    /// Converts the response of `PutObject` to the response of POST Object.
+    pub fn transform_response(self, mut res: Response) -> S3Result<Response> {
+        let etag = res
+            .headers
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+
+        // an invalid redirect URL is ignored
+        let redirect = self
+            .success_action_redirect
+            .filter(|s| s.parse::<Uri>().is_ok_and(|uri| uri.scheme().is_some()));
+
+        if let Some(mut location) = redirect {
+            location.push(if location.contains('?') { '&' } else { '?' });
+            location.push_str("bucket=");
+            location.push_str(&urlencoding::encode(&self.bucket));
+            location.push_str("&key=");
+            location.push_str(&urlencoding::encode(&self.key));
+            location.push_str("&etag=");
+            location.push_str(&urlencoding::encode(etag));
+
+            let location = HeaderValue::try_from(location).map_err(|e| invalid_request!(e, "invalid success_action_redirect"))?;
+            res.status = StatusCode::SEE_OTHER;
+            res.headers.insert(header::LOCATION, location);
+            return Ok(res);
+        }
+
+        match self.success_action_status.as_deref() {
+            Some("200") => res.status = StatusCode::OK,
+            Some("201") => {
+                let location = format!("/{}/{}", self.bucket, urlencoding::encode(&self.key));
+                let etag = etag.to_owned();
+
+                let mut buf = Vec::with_capacity(256);
+                {
+                    let mut ser = xml::Serializer::new(&mut buf);
+                    ser.decl()
+                        .and_then(|()| {
+                            ser.element("PostResponse", |s| {
+                                s.content("Location", &location)?;
+                                s.content("Bucket", &self.bucket)?;
+                                s.content("Key", &self.key)?;
+                                s.content("ETag", &etag)
+                            })
+                        })
+                        .map_err(S3Error::internal_error)?;
+                }
+
+                res.status = StatusCode::CREATED;
+                res.body = buf.into();
+                res.headers
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/xml"));
+            }
+            _ => res.status = StatusCode::NO_CONTENT,
+        }
+
+        Ok(res)
+    }
+}
+
+/// Streams the file of POST Object and enforces the size limit.
+pub fn file_body(file_stream: FileStream, post_policy: Option<&PostPolicy>) -> StreamingBlob {
+    let (min, max) = match post_policy.and_then(PostPolicy::content_length_range) {
+        Some((min, max)) => (min, max.min(MAX_OBJECT_SIZE)),
+        None => (0, MAX_OBJECT_SIZE),
+    };
+    StreamingBlob::new(LimitedFileStream {
+        inner: file_stream,
+        len: 0,
+        min,
+        max,
+    })
+}
+
+struct LimitedFileStream {
+    inner: FileStream,
+    len: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Stream for LimitedFileStream {
+    type Item = Result<Bytes, StdError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let ans = match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+            Some(Ok(bytes)) => {
+                this.len = this.len.saturating_add(bytes.len() as u64);
+                if this.len > this.max {
+                    Some(Err(post_policy::entity_too_large().into()))
+                } else {
+                    Some(Ok(bytes))
+                }
+            }
+            Some(Err(err)) => Some(Err(err.into())),
+            None if this.len < this.min => Some(Err(post_policy::entity_too_small().into())),
+            None => None,
+        };
+        Poll::Ready(ans)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ByteStream for LimitedFileStream {
+    fn remaining_length(&self) -> RemainingLength {
+        self.inner.remaining_length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::http::Body;
+    use crate::http::transform_multipart;
+
+    use futures::StreamExt;
+    use http_body_util::BodyExt;
+
+    fn response() -> Response {
+        let mut res = Response::with_status(StatusCode::OK);
+        res.headers.insert(header::ETAG, HeaderValue::from_static("\"abc\""));
+        res
+    }
+
+    fn ctx(redirect: Option<&str>, status: Option<&str>) -> PostObjectContext {
+        PostObjectContext {
+            bucket: "bucket".into(),
+            key: "a b/c.txt".into(),
+            success_action_redirect: redirect.map(Into::into),
+            success_action_status: status.map(Into::into),
+        }
+    }
+
+    async fn body_string(body: Body) -> String {
+        let bytes = BodyExt::collect(body).await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn success_action() {
+        let res = ctx(None, None).transform_response(response()).unwrap();
+        assert_eq!(res.status, StatusCode::NO_CONTENT);
+
+        let res = ctx(None, Some("200")).transform_response(response()).unwrap();
+        assert_eq!(res.status, StatusCode::OK);
+
+        let res = ctx(None, Some("201")).transform_response(response()).unwrap();
+        assert_eq!(res.status, StatusCode::CREATED);
+        let body = body_string(res.body).await;
+        assert!(body.contains("<PostResponse><Location>/bucket/a%20b%2Fc.txt</Location><Bucket>bucket</Bucket>"));
+        assert!(body.contains("<Key>a b/c.txt</Key><ETag>&quot;abc&quot;</ETag></PostResponse>"));
+
+        let res = ctx(Some("https://example.com/done?x=1"), Some("201"))
+            .transform_response(response())
+            .unwrap();
+        assert_eq!(res.status, StatusCode::SEE_OTHER);
+        assert_eq!(
+            res.headers.get(header::LOCATION).unwrap(),
+            "https://example.com/done?x=1&bucket=bucket&key=a%20b%2Fc.txt&etag=%22abc%22"
+        );
+
+        let res = ctx(Some("not a url"), None).transform_response(response()).unwrap();
+        assert_eq!(res.status, StatusCode::NO_CONTENT);
+    }
+
+    async fn form(key: &str, content: &str, chunk_size: usize) -> Multipart {
+        let boundary = "9431149156168";
+        let body = format!(
+            concat!(
+                "--{b}\r\n",
+                "Content-Disposition: form-data; name=\"key\"\r\n\r\n{key}\r\n",
+                "--{b}\r\n",
+                "Content-Disposition: form-data; name=\"file\"; filename=\"file.txt\"\r\n",
+                "Content-Type: text/plain\r\n\r\n",
+                "{content}\r\n",
+                "--{b}--\r\n",
+            ),
+            b = boundary,
+            key = key,
+            content = content
+        );
+        let chunks: Vec<_> = Bytes::from(body)
+            .chunks(chunk_size)
+            .map(|c| Ok::<_, StdError>(Bytes::copy_from_slice(c)))
+            .collect();
+        transform_multipart(futures::stream::iter(chunks), boundary.as_bytes())
+            .await
+            .unwrap()
+    }
+
+    async fn upload(content: &str, policy: Option<&str>) -> Result<usize, StdError> {
+        let mut multipart = form("object", content, usize::MAX).await;
+
+        let policy = policy.map(|json| PostPolicy::parse(&base64_simd::STANDARD.encode_to_string(json)).unwrap());
+        let file_stream = multipart.take_file_stream().unwrap();
+        let mut body = file_body(file_stream, policy.as_ref());
+
+        let mut len = 0;
+        while let Some(bytes) = body.next().await {
+            len += bytes?.len();
+        }
+        Ok(len)
+    }
+
+    #[tokio::test]
+    async fn file_key_and_length() {
+        let mut multipart = form("user/${filename}", "hello", usize::MAX).await;
+        assert_eq!(multipart.object_key().as_deref(), Some("user/file.txt"));
+        let ctx = PostObjectContext::extract("bucket", &multipart).unwrap();
+        assert_eq!(ctx.key, "user/file.txt");
+
+        // The whole file has been received with the form fields.
+        let body = file_body(multipart.take_file_stream().unwrap(), None);
+        assert_eq!(crate::http::file_content_length(&body), Some(5));
+
+        // The file is still being received.
+        let mut multipart = form("object", "hello", 200).await;
+        let body = file_body(multipart.take_file_stream().unwrap(), None);
+        assert_eq!(crate::http::file_content_length(&body), None);
+    }
+
+    #[tokio::test]
+    async fn size_limit() {
+        assert_eq!(upload("hello", None).await.unwrap(), 5);
+
+        let policy = r#"{"expiration": "2100-01-01T00:00:00Z", "conditions": [["content-length-range", 2, 4]]}"#;
+        assert_eq!(upload("hey", Some(policy)).await.unwrap(), 3);
+        assert!(upload("hello", Some(policy)).await.is_err());
+        assert!(upload("h", Some(policy)).await.is_err());
+    }
+}
@@ -1,9 +1,13 @@
+use crate::auth::Identity;
 use crate::auth::S3Auth;
 use crate::auth::SecretKey;
+use crate::auth::SignaturePolicy;
 use crate::error::*;
 use crate::http;
-use crate::http::{AwsChunkedStream, Body, Multipart};
+use crate::http::{AwsChunkedStream, Body, Multipart, TrailingHeaders};
 use crate::http::{OrderedHeaders, OrderedQs};
+use crate::post_policy::PostPolicy;
+use crate::region::Region;
 use crate::sig_v2;
 use crate::sig_v2::{AuthorizationV2, PresignedUrlV2};
 use crate::sig_v4;
@@ -20,6 +24,10 @@ use bytestring::ByteString;
 use hyper::Method;
 use hyper::Uri;
 use mime::Mime;
+#[cfg(feature = "sigv4a")]
+use smallvec::SmallVec;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc2822;
 use tracing::debug;
 
 fn extract_amz_content_sha256<'a>(hs: &'_ OrderedHeaders<'a>) -> S3Result<Option<AmzContentSha256<'a>>> {
@@ -53,6 +61,7 @@ fn extract_amz_date(hs: &'_ OrderedHeaders<'_>) -> S3Result<Option<AmzDate>> {
 
 pub struct SignatureContext<'a> {
     pub auth: Option<&'a dyn S3Auth>,
+    pub policy: &'a SignaturePolicy,
 
     pub req_method: &'a Method,
     pub req_uri: &'a Uri,
@@ -63,6 +72,8 @@ pub struct SignatureContext<'a> {
 
     pub decoded_uri_path: String,
     pub vh_bucket: Option<&'a str>,
+    pub vh_region: Option<&'a str>,
+    pub regions: &'a [Region],
 
     pub content_length: Option<u64>,
     pub mime: Option<Mime>,
@@ -70,12 +81,16 @@ pub struct SignatureContext<'a> {
 
     pub transformed_body: Option<Body>,
     pub multipart: Option<Multipart>,
+    pub post_policy: Option<PostPolicy>,
+    pub trailing_headers: Option<TrailingHeaders>,
 }
 
 pub struct CredentialsExt {
     pub access_key: String,
     pub secret_key: SecretKey,
-    pub region: Option<String>,
+    pub session_token: Option<String>,
+    pub identity: Option<Identity>,
+    pub region: Option<Region>,
     pub service: Option<String>,
 }
 
@@ -83,21 +98,229 @@ fn require_auth(auth: Option<&dyn S3Auth>) -> S3Result<&dyn S3Auth> {
     auth.ok_or_else(|| s3_error!(NotImplemented, "This service has no authentication provider"))
 }
 
+fn sig_v2_not_allowed() -> S3Error {
+    invalid_request!("The authorization mechanism you have provided is not supported. Please use AWS4-HMAC-SHA256.")
+}
+
+/// The algorithm of signature version 4A
+const SIG_V4A_ALGORITHM: &str = "AWS4-ECDSA-P256-SHA256";
+
+#[cfg(not(feature = "sigv4a"))]
+fn sig_v4a_not_implemented() -> S3Error {
+    s3_error!(
+        NotImplemented,
+        "AWS4-ECDSA-P256-SHA256 is not supported. Please enable the `sigv4a` feature."
+    )
+}
+
+/// Parses `X-Amz-Region-Set`, a comma-separated list of region names and wildcards such as `*` or `us-*`.
+#[cfg(feature = "sigv4a")]
+fn parse_region_set(region_set: &str) -> S3Result<SmallVec<[&str; 4]>> {
+    let patterns: SmallVec<[&str; 4]> = region_set.split(',').map(str::trim).collect();
+    let is_valid = |pattern: &str| match pattern.strip_suffix('*') {
+        Some(prefix) => prefix
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-'),
+        None => Region::new(pattern).is_ok(),
+    };
+    if patterns.iter().all(|p| is_valid(p)).not() {
+        return Err(invalid_request!("invalid region set: {region_set:?}"));
+    }
+    Ok(patterns)
+}
+
+/// Checks whether a parsed region set contains the region.
+#[cfg(feature = "sigv4a")]
+fn region_set_contains(patterns: &[&str], region: &str) -> bool {
+    patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => region.starts_with(prefix),
+        None => *pattern == region,
+    })
+}
+
+/// Verifies a `SigV4a` signature and returns the key for verifying the subsequent chunk signatures.
+#[cfg(feature = "sigv4a")]
+fn v4a_verify_signature(
+    access_key: &str,
+    secret_key: &SecretKey,
+    string_to_sign: &str,
+    signature: &str,
+) -> S3Result<p256::ecdsa::VerifyingKey> {
+    let Some(key) = sig_v4::v4a::derive_verifying_key(access_key, secret_key) else {
+        return Err(s3_error!(InternalError, "failed to derive the SigV4a key"));
+    };
+    if sig_v4::v4a::verify_signature(&key, string_to_sign, signature).not() {
+        debug!(expected=?signature, "signature mismatch");
+        return Err(s3_error!(SignatureDoesNotMatch));
+    }
+    Ok(key)
+}
+
+/// Checks that the request time is within the allowed clock skew.
+fn check_request_time(policy: &SignaturePolicy, date: OffsetDateTime, now: OffsetDateTime) -> S3Result<()> {
+    if (now - date).unsigned_abs() > policy.max_skew_time {
+        return Err(s3_error!(
+            RequestTimeTooSkewed,
+            "The difference between the request time and the server's time is too large."
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the expiration of a presigned url.
+///
+/// Requests that are up to the allowed clock skew in the future are accepted.
+/// See also <https://github.com/minio/minio/blob/b5177993b371817699d3fa25685f54f88d8bfcce/cmd/signature-v4.go#L238-L242>
+fn check_presigned_url_time(
+    policy: &SignaturePolicy,
+    date: OffsetDateTime,
+    expires: time::Duration,
+    now: OffsetDateTime,
+) -> S3Result<()> {
+    if expires > policy.max_presigned_url_expires {
+        let max = policy.max_presigned_url_expires.as_secs();
+        return Err(s3_error!(
+            AuthorizationQueryParametersError,
+            "X-Amz-Expires must be less than or equal to {max} seconds"
+        ));
+    }
+
+    let duration = now - date;
+
+    if duration.is_negative() && duration.unsigned_abs() > policy.max_skew_time {
+        return Err(s3_error!(RequestTimeTooSkewed, "request date is later than server time too much"));
+    }
+
+    if duration > expires {
+        return Err(s3_error!(AccessDenied, "Request has expired"));
+    }
+
+    Ok(())
+}
+
+/// Checks the expiration of a presigned url v2.
+///
+/// The expiration time is absolute, so it is limited relative to the request time.
+fn check_presigned_url_v2_time(policy: &SignaturePolicy, expires_time: OffsetDateTime, now: OffsetDateTime) -> S3Result<()> {
+    let remaining = expires_time - now;
+
+    if remaining.is_negative() {
+        return Err(s3_error!(AccessDenied, "Request has expired"));
+    }
+
+    if remaining.unsigned_abs() > policy.max_presigned_url_expires {
+        let max = policy.max_presigned_url_expires.as_secs();
+        return Err(s3_error!(
+            AuthorizationQueryParametersError,
+            "Expires must be less than or equal to {max} seconds from the request time"
+        ));
+    }
+
+    Ok(())
+}
+
 impl SignatureContext<'_> {
+    /// Checks the region of the credential scope.
+    ///
+    /// The region must be the region of the virtual host if any,
+    /// or one of the regions served by the service.
+    fn check_region(&self, region: &str, code: S3ErrorCode, msg: &str) -> S3Result<Region> {
+        let Ok(ans) = Region::new(region) else {
+            return Err(S3Error::with_message(code, format!("{msg}; the region '{region}' is invalid")));
+        };
+
+        let expected = match self.vh_region {
+            Some(expected) => (region != expected).then_some(expected),
+            None => match self.regions {
+                [] => None,
+                regions => regions.iter().all(|r| r != region).then(|| regions[0].as_str()),
+            },
+        };
+
+        match expected {
+            Some(expected) => Err(S3Error::with_message(
+                code,
+                format!("{msg}; the region '{region}' is wrong; expecting '{expected}'"),
+            )),
+            None => Ok(ans),
+        }
+    }
+
+    /// Checks the region set of a `SigV4a` signature.
+    ///
+    /// The set must contain the region of the virtual host if any,
+    /// or one of the regions served by the service, which becomes the region of the request.
+    /// If the service does not declare its regions, the request has a region only if the set is a single region name.
+    #[cfg(feature = "sigv4a")]
+    fn check_region_set(&self, region_set: &str, code: S3ErrorCode, msg: &str) -> S3Result<Option<Region>> {
+        let patterns = parse_region_set(region_set)?;
+
+        let served: SmallVec<[&str; 4]> = match self.vh_region {
+            Some(region) => smallvec::smallvec![region],
+            None => self.regions.iter().map(Region::as_str).collect(),
+        };
+        let Some(&primary) = served.first() else {
+            return match patterns.as_slice() {
+                [region] => Ok(Region::new(*region).ok()),
+                _ => Ok(None),
+            };
+        };
+
+        match served.iter().find(|r| region_set_contains(&patterns, r)) {
+            Some(region) => Ok(Region::new(*region).ok()),
+            None => Err(S3Error::with_message(
+                code,
+                format!("{msg}; the region set '{region_set}' does not contain '{primary}'"),
+            )),
+        }
+    }
+
     pub async fn check(&mut self) -> S3Result<Option<CredentialsExt>> {
         if let Some(result) = self.v2_check().await {
             debug!("checked signature v2");
-            return Ok(Some(result?));
+            let mut cred = result?;
+            self.check_session_token(&mut cred).await?;
+            return Ok(Some(cred));
         }
 
         if let Some(result) = self.v4_check().await {
             debug!("checked signature v4");
-            return Ok(Some(result?));
+            let mut cred = result?;
+            self.check_session_token(&mut cred).await?;
+            return Ok(Some(cred));
+        }
+
+        if self.policy.allow_anonymous.not() {
+            return Err(s3_error!(AccessDenied, "Anonymous access is forbidden"));
         }
 
         Ok(None)
     }
 
+    async fn check_session_token(&self, cred: &mut CredentialsExt) -> S3Result<()> {
+        let auth = require_auth(self.auth)?;
+        let session_token = self.session_token();
+        cred.identity = auth.check_session_token(&cred.access_key, session_token).await?;
+        cred.session_token = session_token.map(ToOwned::to_owned);
+        Ok(())
+    }
+
+    /// `x-amz-security-token` in the headers, the query strings or the POST form fields
+    fn session_token(&self) -> Option<&str> {
+        if let Some(val) = self.hs.get_unique(crate::header::X_AMZ_SECURITY_TOKEN) {
+            return Some(val);
+        }
+        let qs = self.qs.and_then(|qs| {
+            qs.get_unique("X-Amz-Security-Token")
+                .or_else(|| qs.get_unique("x-amz-security-token"))
+        });
+        if qs.is_some() {
+            return qs;
+        }
+        let multipart = self.multipart.as_ref()?;
+        multipart.find_field_value("x-amz-security-token")
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn v4_check(&mut self) -> Option<S3Result<CredentialsExt>> {
         // POST auth
@@ -159,16 +382,17 @@ impl SignatureContext<'_> {
         let credential =
             CredentialV4::parse(info.x_amz_credential).map_err(|_| invalid_request!("invalid field: x-amz-credential"))?;
 
+        let region = self.check_region(credential.aws_region, S3ErrorCode::InvalidRequest, "Invalid field: x-amz-credential")?;
+
         let amz_date = AmzDate::parse(info.x_amz_date).map_err(|_| invalid_request!("invalid field: x-amz-date"))?;
 
         let access_key = credential.access_key_id.to_owned();
         let secret_key = auth.get_secret_key(&access_key).await?;
 
-        let region = credential.aws_region;
         let service = credential.aws_service;
 
         let string_to_sign = info.policy;
-        let signature = sig_v4::calculate_signature(string_to_sign, &secret_key, &amz_date, region, service);
+        let signature = sig_v4::calculate_signature(string_to_sign, &secret_key, &amz_date, &region, service);
 
         let expected_signature = info.x_amz_signature;
         if signature != expected_signature {
@@ -176,13 +400,18 @@ impl SignatureContext<'_> {
             return Err(s3_error!(SignatureDoesNotMatch));
         }
 
-        let region = region.to_owned();
+        let post_policy = PostPolicy::parse(info.policy).map_err(|e| s3_error!(e, InvalidPolicyDocument))?;
+        post_policy.check_expiration(OffsetDateTime::now_utc())?;
+
         let service = service.to_owned();
 
         self.multipart = Some(multipart);
+        self.post_policy = Some(post_policy);
         Ok(CredentialsExt {
             access_key,
             secret_key,
+            session_token: None,
+            identity: None,
             region: Some(region),
             service: Some(service),
         })
@@ -193,73 +422,86 @@ impl SignatureContext<'_> {
 
         let presigned_url = PresignedUrlV4::parse(qs).map_err(|err| invalid_request!(err, "missing presigned url v4 fields"))?;
 
-        if presigned_url.algorithm != "AWS4-HMAC-SHA256" {
+        let is_v4a = presigned_url.algorithm == SIG_V4A_ALGORITHM;
+
+        #[cfg(not(feature = "sigv4a"))]
+        if is_v4a {
+            return Err(sig_v4a_not_implemented());
+        }
+
+        if presigned_url.algorithm != "AWS4-HMAC-SHA256" && is_v4a.not() {
             return Err(s3_error!(
                 NotImplemented,
                 "X-Amz-Algorithm other than AWS4-HMAC-SHA256 is not implemented"
             ));
         }
 
+        #[allow(unused_mut)]
+        let mut region = if is_v4a {
+            None
+        } else {
+            let region = presigned_url.credential.aws_region;
+            let code = S3ErrorCode::AuthorizationQueryParametersError;
+            Some(self.check_region(region, code, "Error parsing the X-Amz-Credential parameter")?)
+        };
+
         // ASK: how to use it?
         let _content_sha256: Option<AmzContentSha256<'_>> = extract_amz_content_sha256(&self.hs)?;
 
         {
             // check expiration
-            let now = time::OffsetDateTime::now_utc();
-
             let date = presigned_url
                 .amz_date
                 .to_time()
                 .ok_or_else(|| invalid_request!("invalid amz date"))?;
 
-            let duration = now - date;
-
-            // Allow requests that are up to 15 minutes in the future.
-            // This is to account for clock skew between the client and server.
-            // See also https://github.com/minio/minio/blob/b5177993b371817699d3fa25685f54f88d8bfcce/cmd/signature-v4.go#L238-L242
-
-            // TODO: configurable max_skew_time
-
-            let max_skew_time = time::Duration::seconds(15 * 60);
-            if duration.is_negative() && duration.abs() > max_skew_time {
-                return Err(s3_error!(RequestTimeTooSkewed, "request date is later than server time too much"));
-            }
-
-            if duration > presigned_url.expires {
-                return Err(s3_error!(AccessDenied, "Request has expired"));
-            }
+            check_presigned_url_time(self.policy, date, presigned_url.expires, OffsetDateTime::now_utc())?;
         }
 
         let auth = require_auth(self.auth)?;
         let access_key = presigned_url.credential.access_key_id;
         let secret_key = auth.get_secret_key(access_key).await?;
 
-        let region = presigned_url.credential.aws_region;
         let service = presigned_url.credential.aws_service;
 
-        let signature = {
+        let canonical_request = {
             let headers = self.hs.find_multiple(&presigned_url.signed_headers);
             let method = &self.req_method;
             let uri_path = &self.decoded_uri_path;
 
-            let canonical_request = sig_v4::create_presigned_canonical_request(method, uri_path, qs.as_ref(), &headers);
+            sig_v4::create_presigned_canonical_request(method, uri_path, qs.as_ref(), &headers)
+        };
+        let amz_date = &presigned_url.amz_date;
+        let expected_signature = presigned_url.signature;
 
-            let amz_date = &presigned_url.amz_date;
-            let string_to_sign = sig_v4::create_string_to_sign(&canonical_request, amz_date, region, service);
+        #[cfg(feature = "sigv4a")]
+        if is_v4a {
+            let region_set = presigned_url
+                .region_set
+                .ok_or_else(|| invalid_request!("missing query: X-Amz-Region-Set"))?;
+            let code = S3ErrorCode::AuthorizationQueryParametersError;
+            region = self.check_region_set(region_set, code, "Error parsing the X-Amz-Region-Set parameter")?;
 
-            sig_v4::calculate_signature(&string_to_sign, &secret_key, amz_date, region, service)
-        };
+            let string_to_sign = sig_v4::v4a::create_string_to_sign(&canonical_request, amz_date, service);
+            v4a_verify_signature(access_key, &secret_key, &string_to_sign, expected_signature)?;
+        }
 
-        let expected_signature = presigned_url.signature;
-        if signature != expected_signature {
-            debug!(?signature, expected=?expected_signature, "signature mismatch");
-            return Err(s3_error!(SignatureDoesNotMatch));
+        if let (false, Some(region)) = (is_v4a, &region) {
+            let string_to_sign = sig_v4::create_string_to_sign(&canonical_request, amz_date, region, service);
+            let signature = sig_v4::calculate_signature(&string_to_sign, &secret_key, amz_date, region, service);
+
+            if signature != expected_signature {
+                debug!(?signature, expected=?expected_signature, "signature mismatch");
+                return Err(s3_error!(SignatureDoesNotMatch));
+            }
         }
 
         Ok(CredentialsExt {
             access_key: access_key.into(),
             secret_key,
-            region: Some(region.into()),
+            session_token: None,
+            identity: None,
+            region,
             service: Some(service.into()),
         })
     }
@@ -272,13 +514,42 @@ impl SignatureContext<'_> {
             a.signed_headers.sort_unstable();
             a
         };
-        let region = authorization.credential.aws_region;
         let service = authorization.credential.aws_service;
 
         if !matches!(service, "s3" | "sts") {
             return Err(s3_error!(NotImplemented, "unknown service"));
         }
 
+        let is_v4a = authorization.algorithm == SIG_V4A_ALGORITHM;
+
+        #[cfg(not(feature = "sigv4a"))]
+        if is_v4a {
+            return Err(sig_v4a_not_implemented());
+        }
+
+        let region = if is_v4a {
+            None
+        } else {
+            let region = authorization.credential.aws_region;
+            let code = S3ErrorCode::AuthorizationHeaderMalformed;
+            Some(self.check_region(region, code, "The authorization header is malformed")?)
+        };
+
+        #[cfg(feature = "sigv4a")]
+        let region = if is_v4a {
+            if authorization.signed_headers.contains(&"x-amz-region-set").not() {
+                return Err(invalid_request!("x-amz-region-set must be signed"));
+            }
+            let region_set = self
+                .hs
+                .get_unique(crate::header::X_AMZ_REGION_SET)
+                .ok_or_else(|| invalid_request!("missing header: x-amz-region-set"))?;
+            let code = S3ErrorCode::AuthorizationHeaderMalformed;
+            self.check_region_set(region_set, code, "The authorization header is malformed")?
+        } else {
+            region
+        };
+
         let auth = require_auth(self.auth)?;
 
         let amz_content_sha256 = extract_amz_content_sha256(&self.hs)?;
@@ -292,9 +563,29 @@ impl SignatureContext<'_> {
 
         let amz_date = extract_amz_date(&self.hs)?.ok_or_else(|| invalid_request!("missing header: x-amz-date"))?;
 
-        let is_stream = matches!(amz_content_sha256, Some(AmzContentSha256::MultipleChunks));
+        {
+            let date = amz_date
+                .to_time()
+                .ok_or_else(|| invalid_request!("invalid header: x-amz-date"))?;
+            check_request_time(self.policy, date, OffsetDateTime::now_utc())?;
+        }
+
+        let is_stream = amz_content_sha256.as_ref().is_some_and(AmzContentSha256::is_streaming);
+        let has_trailer = amz_content_sha256.as_ref().is_some_and(AmzContentSha256::has_trailer);
 
-        let signature = {
+        if has_trailer && self.hs.get_unique(crate::header::X_AMZ_TRAILER).is_none() {
+            return Err(invalid_request!("missing header: x-amz-trailer"));
+        }
+
+        let is_ecdsa_stream = amz_content_sha256.as_ref().is_some_and(AmzContentSha256::is_ecdsa);
+        if is_ecdsa_stream != is_v4a
+            && is_stream
+            && matches!(amz_content_sha256, Some(AmzContentSha256::UnsignedMultipleChunksWithTrailer)).not()
+        {
+            return Err(invalid_request!("x-amz-content-sha256 does not match the signing algorithm"));
+        }
+
+        let canonical_request = {
             let method = &self.req_method;
             let uri_path = &self.decoded_uri_path;
             let query_strings: &[(String, String)] = self.qs.as_ref().map_or(&[], AsRef::as_ref);
@@ -302,8 +593,16 @@ impl SignatureContext<'_> {
             // here requires that `auth.signed_headers` is sorted
             let headers = self.hs.find_multiple(&authorization.signed_headers);
 
-            let canonical_request = if is_stream {
-                let payload = sig_v4::Payload::MultipleChunks;
+            if is_stream {
+                let payload = match amz_content_sha256 {
+                    Some(AmzContentSha256::MultipleChunksWithTrailer) => sig_v4::Payload::MultipleChunksWithTrailer,
+                    Some(AmzContentSha256::UnsignedMultipleChunksWithTrailer) => {
+                        sig_v4::Payload::UnsignedMultipleChunksWithTrailer
+                    }
+                    Some(AmzContentSha256::EcdsaMultipleChunks) => sig_v4::Payload::EcdsaMultipleChunks,
+                    Some(AmzContentSha256::EcdsaMultipleChunksWithTrailer) => sig_v4::Payload::EcdsaMultipleChunksWithTrailer,
+                    _ => sig_v4::Payload::MultipleChunks,
+                };
                 sig_v4::create_canonical_request(method, uri_path, query_strings, &headers, payload)
             } else if matches!(*self.req_method, Method::GET | Method::HEAD) {
                 let payload = if matches!(amz_content_sha256, Some(AmzContentSha256::UnsignedPayload)) {
@@ -333,15 +632,27 @@ impl SignatureContext<'_> {
                         sig_v4::Payload::SingleChunk(&bytes),
                     )
                 }
-            };
-            let string_to_sign = sig_v4::create_string_to_sign(&canonical_request, &amz_date, region, service);
-            sig_v4::calculate_signature(&string_to_sign, &secret_key, &amz_date, region, service)
+            }
         };
 
         let expected_signature = authorization.signature;
-        if signature != expected_signature {
-            debug!(?signature, expected=?expected_signature, "signature mismatch");
-            return Err(s3_error!(SignatureDoesNotMatch));
+
+        #[cfg(feature = "sigv4a")]
+        let ecdsa_key = if is_v4a {
+            let string_to_sign = sig_v4::v4a::create_string_to_sign(&canonical_request, &amz_date, service);
+            Some(v4a_verify_signature(access_key, &secret_key, &string_to_sign, expected_signature)?)
+        } else {
+            None
+        };
+
+        if let (false, Some(region)) = (is_v4a, &region) {
+            let string_to_sign = sig_v4::create_string_to_sign(&canonical_request, &amz_date, region, service);
+            let signature = sig_v4::calculate_signature(&string_to_sign, &secret_key, &amz_date, region, service);
+
+            if signature != expected_signature {
+                debug!(?signature, expected=?expected_signature, "signature mismatch");
+                return Err(s3_error!(SignatureDoesNotMatch));
+            }
         }
 
         if is_stream {
@@ -349,25 +660,34 @@ impl SignatureContext<'_> {
                 .decoded_content_length
                 .ok_or_else(|| s3_error!(MissingContentLength, "missing header: x-amz-decoded-content-length"))?;
 
-            let stream = AwsChunkedStream::new(
-                mem::take(self.req_body),
-                signature.into(),
+            let is_signed = matches!(amz_content_sha256, Some(AmzContentSha256::UnsignedMultipleChunksWithTrailer)).not();
+            let signature_ctx = is_signed.then(|| http::SignatureCtx {
                 amz_date,
-                authorization.credential.aws_region.into(),
-                authorization.credential.aws_service.into(),
-                secret_key.clone(),
-                decoded_content_length,
-            );
+                region: region.clone(),
+                service: authorization.credential.aws_service.into(),
+                secret_key: secret_key.clone(),
+                prev_signature: expected_signature.into(),
+                #[cfg(feature = "sigv4a")]
+                ecdsa_key,
+            });
+
+            let trailing_headers = has_trailer.then(TrailingHeaders::new);
+
+            let stream =
+                AwsChunkedStream::new(mem::take(self.req_body), signature_ctx, decoded_content_length, trailing_headers.clone());
 
             debug!(len=?stream.exact_remaining_length(), "aws-chunked");
 
             self.transformed_body = Some(Body::from(stream.into_byte_stream()));
+            self.trailing_headers = trailing_headers;
         }
 
         Ok(CredentialsExt {
             access_key: access_key.into(),
             secret_key,
-            region: Some(region.into()),
+            session_token: None,
+            identity: None,
+            region,
             service: Some(service.into()),
         })
     }
@@ -376,6 +696,9 @@ impl SignatureContext<'_> {
     pub async fn v2_check(&mut self) -> Option<S3Result<CredentialsExt>> {
         if let Some(qs) = self.qs {
             if qs.has("Signature") {
+                if self.policy.allow_sig_v2.not() {
+                    return Some(Err(sig_v2_not_allowed()));
+                }
                 debug!("checking presigned url");
                 return Some(self.v2_check_presigned_url().await);
             }
@@ -383,6 +706,9 @@ impl SignatureContext<'_> {
 
         if let Some(auth) = self.hs.get_unique(crate::header::AUTHORIZATION) {
             if let Ok(auth) = AuthorizationV2::parse(auth) {
+                if self.policy.allow_sig_v2.not() {
+                    return Some(Err(sig_v2_not_allowed()));
+                }
                 debug!("checking header auth");
                 return Some(self.v2_check_header_auth(auth).await);
             }
@@ -394,10 +720,14 @@ impl SignatureContext<'_> {
     pub async fn v2_check_header_auth(&mut self, auth_v2: AuthorizationV2<'_>) -> S3Result<CredentialsExt> {
         let method = &self.req_method;
 
-        let date = self.hs.get_unique("date").or_else(|| self.hs.get_unique("x-amz-date"));
-        if date.is_none() {
+        let Some(date) = self.hs.get_unique("x-amz-date").or_else(|| self.hs.get_unique("date")) else {
             return Err(invalid_request!("missing date"));
-        }
+        };
+
+        let Ok(date) = OffsetDateTime::parse(date, &Rfc2822) else {
+            return Err(s3_error!(AccessDenied, "AWS authentication requires a valid Date or x-amz-date header"));
+        };
+        check_request_time(self.policy, date, OffsetDateTime::now_utc())?;
 
         let auth = require_auth(self.auth)?;
         let access_key = auth_v2.access_key;
@@ -424,6 +754,8 @@ impl SignatureContext<'_> {
         Ok(CredentialsExt {
             access_key: access_key.into(),
             secret_key,
+            session_token: None,
+            identity: None,
             region: None,
             service: Some("s3".into()),
         })
@@ -433,9 +765,7 @@ impl SignatureContext<'_> {
         let qs = self.qs.unwrap(); // assume: qs has "Signature"
         let presigned_url = PresignedUrlV2::parse(qs).map_err(|err| invalid_request!(err, "missing presigned url v2 fields"))?;
 
-        if time::OffsetDateTime::now_utc() > presigned_url.expires_time {
-            return Err(s3_error!(AccessDenied, "Request has expired"));
-        }
+        check_presigned_url_v2_time(self.policy, presigned_url.expires_time, OffsetDateTime::now_utc())?;
 
         let auth = require_auth(self.auth)?;
         let access_key = presigned_url.access_key;
@@ -460,8 +790,119 @@ impl SignatureContext<'_> {
         Ok(CredentialsExt {
             access_key: access_key.into(),
             secret_key,
+            session_token: None,
+            identity: None,
             region: None,
             service: Some("s3".into()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use time::macros::datetime;
+
+    #[test]
+    fn request_time() {
+        let policy = SignaturePolicy {
+            max_skew_time: Duration::from_mins(1),
+            ..SignaturePolicy::default()
+        };
+
+        let now = datetime!(2025-01-01 12:00:00 UTC);
+
+        let cases = [
+            (datetime!(2025-01-01 12:00:00 UTC), true),
+            (datetime!(2025-01-01 11:59:00 UTC), true),
+            (datetime!(2025-01-01 12:01:00 UTC), true),
+            (datetime!(2025-01-01 11:58:59 UTC), false),
+            (datetime!(2025-01-01 12:01:01 UTC), false),
+        ];
+
+        for (date, ok) in cases {
+            let result = check_request_time(&policy, date, now);
+            match result {
+                Ok(()) => assert!(ok, "{date}"),
+                Err(e) => {
+                    assert!(!ok, "{date}");
+                    assert_eq!(*e.code(), S3ErrorCode::RequestTimeTooSkewed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn presigned_url_time() {
+        let policy = SignaturePolicy {
+            max_skew_time: Duration::from_mins(1),
+            max_presigned_url_expires: Duration::from_hours(1),
+            ..SignaturePolicy::default()
+        };
+
+        let now = datetime!(2025-01-01 12:00:00 UTC);
+        let expires = |secs| time::Duration::seconds(secs);
+
+        let cases = [
+            (datetime!(2025-01-01 11:30:00 UTC), expires(3600), None),
+            (datetime!(2025-01-01 12:00:30 UTC), expires(3600), None),
+            (datetime!(2025-01-01 12:02:00 UTC), expires(3600), Some(S3ErrorCode::RequestTimeTooSkewed)),
+            (datetime!(2025-01-01 10:30:00 UTC), expires(3600), Some(S3ErrorCode::AccessDenied)),
+            (
+                datetime!(2025-01-01 12:00:00 UTC),
+                expires(7200),
+                Some(S3ErrorCode::AuthorizationQueryParametersError),
+            ),
+        ];
+
+        for (date, expires, expected) in cases {
+            let result = check_presigned_url_time(&policy, date, expires, now);
+            assert_eq!(result.err().map(|e| e.code().clone()), expected, "{date} {expires}");
+        }
+    }
+
+    #[test]
+    fn presigned_url_v2_time() {
+        let policy = SignaturePolicy {
+            max_presigned_url_expires: Duration::from_hours(1),
+            ..SignaturePolicy::default()
+        };
+
+        let now = datetime!(2025-01-01 12:00:00 UTC);
+
+        let cases = [
+            (datetime!(2025-01-01 12:30:00 UTC), None),
+            (datetime!(2025-01-01 13:00:00 UTC), None),
+            (datetime!(2025-01-01 11:59:59 UTC), Some(S3ErrorCode::AccessDenied)),
+            (datetime!(2025-01-01 13:00:01 UTC), Some(S3ErrorCode::AuthorizationQueryParametersError)),
+            (datetime!(2036-01-01 00:00:00 UTC), Some(S3ErrorCode::AuthorizationQueryParametersError)),
+        ];
+
+        for (expires_time, expected) in cases {
+            let result = check_presigned_url_v2_time(&policy, expires_time, now);
+            assert_eq!(result.err().map(|e| e.code().clone()), expected, "{expires_time}");
+        }
+    }
+
+    #[cfg(feature = "sigv4a")]
+    #[test]
+    fn region_set() {
+        assert_eq!(parse_region_set("us-east-1").unwrap().as_slice(), ["us-east-1"]);
+        assert_eq!(parse_region_set("us-east-1, us-west-2").unwrap().as_slice(), ["us-east-1", "us-west-2"]);
+        assert_eq!(parse_region_set("*").unwrap().as_slice(), ["*"]);
+        assert!(parse_region_set("").is_err());
+        assert!(parse_region_set("us-east-1,").is_err());
+        assert!(parse_region_set("US-EAST-1").is_err());
+        assert!(parse_region_set("us-*-1").is_err());
+
+        let contains = |set: &str, region: &str| region_set_contains(&parse_region_set(set).unwrap(), region);
+        assert!(contains("*", "eu-west-1"));
+        assert!(contains("us-*", "us-east-1"));
+        assert!(contains("us-*", "eu-west-1").not());
+        assert!(contains("eu-west-1,us-east-1", "us-east-1"));
+        assert!(contains("us-east-1", "us-east-2").not());
+    }
+}
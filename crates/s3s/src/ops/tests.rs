@@ -62,3 +62,99 @@ fn error_custom_headers() {
         )
     );
 }
+
+#[tokio::test]
+async fn request_id() {
+    use crate::dto::{ListBucketsInput, ListBucketsOutput};
+    use crate::request_id::RequestIdGenerator;
+    use crate::service::S3ServiceBuilder;
+    use crate::{S3Request, S3Response};
+
+    struct Buckets;
+
+    #[async_trait::async_trait]
+    impl S3 for Buckets {
+        async fn list_buckets(&self, req: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
+            assert_eq!(req.extensions.get::<RequestId>().unwrap().id(), "REQ1");
+            Ok(S3Response::new(ListBucketsOutput::default()))
+        }
+    }
+
+    struct Fixed;
+
+    impl RequestIdGenerator for Fixed {
+        fn generate(&self) -> RequestId {
+            RequestId::new("REQ1", "HOST1")
+        }
+    }
+
+    let service = {
+        let mut builder = S3ServiceBuilder::new(Buckets);
+        builder.set_request_id_generator(Fixed);
+        builder.build()
+    };
+
+    let req = |uri: &str| hyper::Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let res = service.call(req("/")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get("x-amz-request-id").unwrap(), "REQ1");
+    assert_eq!(res.headers().get("x-amz-id-2").unwrap(), "HOST1");
+
+    let res = service.call(req("/bucket")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_IMPLEMENTED);
+    assert_eq!(res.headers().get("x-amz-request-id").unwrap(), "REQ1");
+    let body = res.into_body().bytes().unwrap();
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.contains("<RequestId>REQ1</RequestId>"), "{body}");
+}
+
+#[tokio::test]
+async fn virtual_host_region() {
+    use crate::dto::{ListObjectsInput, ListObjectsOutput};
+    use crate::host::{PatternHost, VirtualHost};
+    use crate::service::S3ServiceBuilder;
+    use crate::{S3Request, S3Response};
+
+    struct Objects;
+
+    #[async_trait::async_trait]
+    impl S3 for Objects {
+        async fn list_objects(&self, req: S3Request<ListObjectsInput>) -> S3Result<S3Response<ListObjectsOutput>> {
+            let vh = req.extensions.get::<VirtualHost<'static>>().unwrap();
+            assert_eq!(vh.region(), Some("us-west-2"));
+            assert_eq!(req.input.bucket, "bucket");
+            Ok(S3Response::new(ListObjectsOutput::default()))
+        }
+    }
+
+    let service = {
+        let mut host = PatternHost::new();
+        host.add_pattern("{bucket}.s3.{region}.example.com").unwrap();
+        let mut builder = S3ServiceBuilder::new(Objects);
+        builder.set_host(host);
+        builder.build()
+    };
+
+    let req = hyper::Request::builder()
+        .uri("/")
+        .header(header::HOST, "bucket.s3.us-west-2.example.com")
+        .body(Body::empty())
+        .unwrap();
+    let res = service.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let auth = "AWS4-HMAC-SHA256 Credential=AKEXAMPLE/20250101/us-east-1/s3/aws4_request, SignedHeaders=host, Signature=abc";
+    let req = hyper::Request::builder()
+        .uri("/")
+        .header(header::HOST, "bucket.s3.us-west-2.example.com")
+        .header(header::AUTHORIZATION, auth)
+        .body(Body::empty())
+        .unwrap();
+    let res = service.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body = res.into_body().bytes().unwrap();
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.contains("<Code>AuthorizationHeaderMalformed</Code>"), "{body}");
+    assert!(body.contains("expecting &apos;us-west-2&apos;"), "{body}");
+}
@@ -0,0 +1,98 @@
+//! Regions
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A region name, such as `us-east-1`
+///
+/// A valid region name consists of 1 to 63 lowercase letters, digits and hyphens,
+/// and does not start or end with a hyphen.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region(Box<str>);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("The region is invalid")]
+pub struct InvalidRegion(());
+
+fn is_valid_region(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.is_empty() || bytes.len() > 63 {
+        return false;
+    }
+    if bytes.first() == Some(&b'-') || bytes.last() == Some(&b'-') {
+        return false;
+    }
+    bytes
+        .iter()
+        .all(|&b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+impl Region {
+    /// The default region of S3
+    pub const US_EAST_1: &str = "us-east-1";
+
+    /// Creates a region from a name.
+    ///
+    /// # Errors
+    /// Returns an error if the name is invalid.
+    pub fn new(name: impl Into<Box<str>>) -> Result<Self, InvalidRegion> {
+        let name = name.into();
+        if is_valid_region(&name) {
+            Ok(Self(name))
+        } else {
+            Err(InvalidRegion(()))
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Region {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Region {
+    type Err = InvalidRegion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl PartialEq<str> for Region {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl From<Region> for String {
+    fn from(region: Region) -> Self {
+        region.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        for name in ["us-east-1", "cn-north-1", "local", "a"] {
+            assert_eq!(Region::new(name).unwrap().as_str(), name);
+        }
+        for name in ["", "US-EAST-1", "us_east_1", "-us", "us-", "us.east", &"a".repeat(64)] {
+            assert!(Region::new(name).is_err(), "{name:?}");
+        }
+    }
+}
@@ -1,4 +1,10 @@
 use crate::auth::Credentials;
+use crate::checksum::BodyChecksums;
+use crate::connection::ConnectionInfo;
+use crate::http::TrailingHeaders;
+use crate::region::Region;
+
+use std::net::IpAddr;
 
 use hyper::http::{Extensions, HeaderValue};
 use hyper::{HeaderMap, Method, Uri};
@@ -30,10 +36,34 @@ pub struct S3Request<T> {
     pub credentials: Option<Credentials>,
 
     /// The requested region.
-    pub region: Option<String>,
+    ///
+    /// It is the region of the credential scope for signed requests.
+    /// Otherwise, it is the region of the virtual host or the primary region of the service, if any.
+    pub region: Option<Region>,
 
     /// The requested service.
     pub service: Option<String>,
+
+    /// Trailing headers of an aws-chunked request body.
+    ///
+    /// They are available after the body has been fully consumed.
+    pub trailing_headers: Option<TrailingHeaders>,
+
+    /// Checksums of the request body computed by the service.
+    ///
+    /// It is only set for `PutObject` and `UploadPart` requests when checksum verification is enabled.
+    /// The checksums are available after the body has been fully consumed.
+    pub body_checksums: Option<BodyChecksums>,
+
+    /// The connection of the request.
+    ///
+    /// `None` means the server does not provide the connection information.
+    pub connection: Option<ConnectionInfo>,
+
+    /// The IP address of the client.
+    ///
+    /// It is the peer address of the connection, or the forwarded address if the peer is a trusted proxy.
+    pub client_ip: Option<IpAddr>,
 }
 
 impl<T> S3Request<T> {
@@ -47,6 +77,10 @@ impl<T> S3Request<T> {
             credentials: default(),
             region: default(),
             service: default(),
+            trailing_headers: default(),
+            body_checksums: default(),
+            connection: default(),
+            client_ip: default(),
         }
     }
 
@@ -60,6 +94,10 @@ impl<T> S3Request<T> {
             credentials: self.credentials,
             region: self.region,
             service: self.service,
+            trailing_headers: self.trailing_headers,
+            body_checksums: self.body_checksums,
+            connection: self.connection,
+            client_ip: self.client_ip,
         }
     }
 }
@@ -0,0 +1,110 @@
+//! Request identifiers
+//!
+//! Every request handled by [`S3Service`](crate::service::S3Service) is assigned a [`RequestId`],
+//! which is sent back in the `x-amz-request-id` and `x-amz-id-2` response headers.
+//!
+//! The [`RequestId`] of the current request can be found in the request extensions.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use time::OffsetDateTime;
+
+/// The identifiers of a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId {
+    id: String,
+    host_id: String,
+}
+
+impl RequestId {
+    #[must_use]
+    pub fn new(id: impl Into<String>, host_id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            host_id: host_id.into(),
+        }
+    }
+
+    /// Returns the request id (`x-amz-request-id`).
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the extended request id (`x-amz-id-2`).
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+pub trait RequestIdGenerator: Send + Sync + 'static {
+    /// Generates the identifiers of a new request.
+    fn generate(&self) -> RequestId;
+}
+
+/// The default request id generator
+///
+/// The request id consists of the current timestamp and a counter, in uppercase hex.
+/// The host id is fixed for the generator.
+#[derive(Debug)]
+pub struct DefaultRequestIdGenerator {
+    host_id: String,
+    counter: AtomicU64,
+}
+
+impl DefaultRequestIdGenerator {
+    /// Creates a generator with a random host id.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut buf = [0; 32];
+        // the host id is not required to be unpredictable
+        let _ = getrandom::fill(&mut buf);
+        Self::with_host_id(hex_simd::encode_to_string(buf, hex_simd::AsciiCase::Lower))
+    }
+
+    #[must_use]
+    pub fn with_host_id(host_id: impl Into<String>) -> Self {
+        Self {
+            host_id: host_id.into(),
+            counter: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Default for DefaultRequestIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestIdGenerator for DefaultRequestIdGenerator {
+    fn generate(&self) -> RequestId {
+        let nanos = OffsetDateTime::now_utc().unix_timestamp_nanos();
+        let seq = self.counter.fetch_add(1, Ordering::Relaxed);
+        let id = format!("{:X}{:04X}", nanos, seq & 0xFFFF);
+        RequestId::new(id, self.host_id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_generator() {
+        let generator = DefaultRequestIdGenerator::with_host_id("host");
+        let a = generator.generate();
+        let b = generator.generate();
+        assert_ne!(a.id(), b.id());
+        assert_eq!(a.host_id(), "host");
+        assert!(a.id().chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+    }
+}
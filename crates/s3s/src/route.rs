@@ -9,11 +9,28 @@ use hyper::StatusCode;
 use hyper::Uri;
 use hyper::http::Extensions;
 
+use std::net::IpAddr;
+
 // TODO: Refactor S3Request and S3Response to support custom route better
 
 #[async_trait::async_trait]
 pub trait S3Route: Send + Sync + 'static {
-    fn is_match(&self, method: &Method, uri: &Uri, headers: &HeaderMap, extensions: &mut Extensions) -> bool;
+    /// Checks whether the route handles a request.
+    ///
+    /// `client_ip` is the resolved IP address of the client, if the connection information is known.
+    fn is_match(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        client_ip: Option<IpAddr>,
+    ) -> bool;
+
+    /// Returns the operation name of the route, which is used in metrics and access logs.
+    fn name(&self) -> &'static str {
+        "CustomRoute"
+    }
 
     async fn check_access(&self, req: &mut S3Request<Body>) -> S3Result<()> {
         match req.credentials {
@@ -35,7 +52,7 @@ mod tests {
 
     #[async_trait::async_trait]
     impl S3Route for AssumeRole {
-        fn is_match(&self, method: &Method, uri: &Uri, headers: &HeaderMap, _: &mut Extensions) -> bool {
+        fn is_match(&self, method: &Method, uri: &Uri, headers: &HeaderMap, _: &mut Extensions, _: Option<IpAddr>) -> bool {
             if method == Method::POST && uri.path() == "/" {
                 if let Some(val) = headers.get(header::CONTENT_TYPE) {
                     if val.as_bytes() == b"application/x-www-form-urlencoded" {
@@ -1,10 +1,18 @@
 use crate::access::S3Access;
-use crate::auth::S3Auth;
+use crate::access_log::{AccessLogSink, PendingRecord};
+use crate::auth::{S3Auth, SignaturePolicy};
+use crate::connection::{Connected, ConnectionInfo, IpCidr};
 use crate::error::{S3Error, S3Result};
 use crate::host::S3Host;
 use crate::http::{Body, Request};
+use crate::interceptor::S3Interceptor;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::region::Region;
+use crate::request_id::{DefaultRequestIdGenerator, RequestIdGenerator};
 use crate::route::S3Route;
 use crate::s3_trait::S3;
+use crate::tls::TlsInfo;
 
 use std::convert::Infallible;
 use std::fmt;
@@ -20,7 +28,16 @@ pub struct S3ServiceBuilder {
     host: Option<Box<dyn S3Host>>,
     auth: Option<Box<dyn S3Auth>>,
     access: Option<Box<dyn S3Access>>,
-    route: Option<Box<dyn S3Route>>,
+    routes: Vec<Box<dyn S3Route>>,
+    interceptors: Vec<Box<dyn S3Interceptor>>,
+    request_id_generator: Option<Box<dyn RequestIdGenerator>>,
+    access_log: Option<Arc<dyn AccessLogSink>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+    regions: Vec<Region>,
+    trusted_proxies: Vec<IpCidr>,
+    signature_policy: SignaturePolicy,
+    verify_checksums: bool,
 }
 
 impl S3ServiceBuilder {
@@ -31,7 +48,16 @@ impl S3ServiceBuilder {
             host: None,
             auth: None,
             access: None,
-            route: None,
+            routes: Vec::new(),
+            interceptors: Vec::new(),
+            request_id_generator: None,
+            access_log: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            regions: Vec::new(),
+            trusted_proxies: Vec::new(),
+            signature_policy: SignaturePolicy::default(),
+            verify_checksums: false,
         }
     }
 
@@ -47,8 +73,69 @@ impl S3ServiceBuilder {
         self.access = Some(Box::new(access));
     }
 
+    /// Sets the custom route, replacing the routes added before.
     pub fn set_route(&mut self, route: impl S3Route) {
-        self.route = Some(Box::new(route));
+        self.routes.clear();
+        self.add_route(route);
+    }
+
+    /// Adds a custom route.
+    ///
+    /// Routes are matched in the order they are added, before resolving S3 operations.
+    pub fn add_route(&mut self, route: impl S3Route) {
+        self.routes.push(Box::new(route));
+    }
+
+    /// Adds an interceptor.
+    ///
+    /// Interceptors are called in the order they are added.
+    pub fn add_interceptor(&mut self, interceptor: impl S3Interceptor) {
+        self.interceptors.push(Box::new(interceptor));
+    }
+
+    /// Sets the request id generator.
+    ///
+    /// [`DefaultRequestIdGenerator`] is used if not set.
+    pub fn set_request_id_generator(&mut self, generator: impl RequestIdGenerator) {
+        self.request_id_generator = Some(Box::new(generator));
+    }
+
+    /// Sets the sink of access log records.
+    pub fn set_access_log(&mut self, sink: impl AccessLogSink) {
+        self.access_log = Some(Arc::new(sink));
+    }
+
+    /// Sets the registry of metrics.
+    #[cfg(feature = "metrics")]
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Adds a region served by the service.
+    ///
+    /// The first region is the primary region.
+    /// If any region is added, `SigV4` requests scoped to other regions are rejected.
+    pub fn add_region(&mut self, region: Region) {
+        self.regions.push(region);
+    }
+
+    /// Adds a network of trusted proxies.
+    ///
+    /// The client IP of a request from a trusted proxy is taken from the `Forwarded` or `X-Forwarded-For` header.
+    pub fn add_trusted_proxy(&mut self, proxy: IpCidr) {
+        self.trusted_proxies.push(proxy);
+    }
+
+    pub fn set_signature_policy(&mut self, policy: SignaturePolicy) {
+        self.signature_policy = policy;
+    }
+
+    /// Enables verifying the bodies of `PutObject` and `UploadPart` requests
+    /// against `Content-MD5` and `x-amz-checksum-*` values.
+    ///
+    /// See [`crate::checksum`] for details.
+    pub fn set_verify_checksums(&mut self, enabled: bool) {
+        self.verify_checksums = enabled;
     }
 
     #[must_use]
@@ -58,7 +145,18 @@ impl S3ServiceBuilder {
             host: self.host,
             auth: self.auth,
             access: self.access,
-            route: self.route,
+            routes: self.routes,
+            interceptors: self.interceptors.into(),
+            request_id_generator: self
+                .request_id_generator
+                .unwrap_or_else(|| Box::new(DefaultRequestIdGenerator::new())),
+            access_log: self.access_log,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+            regions: self.regions,
+            trusted_proxies: self.trusted_proxies,
+            signature_policy: self.signature_policy,
+            verify_checksums: self.verify_checksums,
         }
     }
 }
@@ -68,28 +166,57 @@ pub struct S3Service {
     host: Option<Box<dyn S3Host>>,
     auth: Option<Box<dyn S3Auth>>,
     access: Option<Box<dyn S3Access>>,
-    route: Option<Box<dyn S3Route>>,
+    routes: Vec<Box<dyn S3Route>>,
+    interceptors: Arc<[Box<dyn S3Interceptor>]>,
+    request_id_generator: Box<dyn RequestIdGenerator>,
+    access_log: Option<Arc<dyn AccessLogSink>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+    regions: Vec<Region>,
+    trusted_proxies: Vec<IpCidr>,
+    signature_policy: SignaturePolicy,
+    verify_checksums: bool,
 }
 
 impl S3Service {
     #[tracing::instrument(
         level = "debug",
         skip(self, req),
-        fields(start_time=?time::OffsetDateTime::now_utc())
+        fields(start_time=?time::OffsetDateTime::now_utc(), request_id=tracing::field::Empty)
     )]
     pub async fn call(&self, req: hyper::Request<Body>) -> S3Result<hyper::Response<Body>> {
         debug!(?req);
 
         let t0 = std::time::Instant::now();
 
+        let request_id = self.request_id_generator.generate();
+        tracing::Span::current().record("request_id", request_id.id());
+
+        let pending_log = self.access_log.as_ref().map(|_| PendingRecord::new(&req));
+
+        #[cfg(feature = "metrics")]
+        let (req, pending_metrics) = match &self.metrics {
+            Some(metrics) => {
+                let (req, pending) = metrics.start(req);
+                (req, Some(pending))
+            }
+            None => (req, None),
+        };
+
         let mut req = Request::from(req);
+        self.resolve_connection(&mut req);
 
         let ccx = crate::ops::CallContext {
             s3: &self.s3,
             host: self.host.as_deref(),
             auth: self.auth.as_deref(),
             access: self.access.as_deref(),
-            route: self.route.as_deref(),
+            routes: &self.routes,
+            interceptors: &self.interceptors,
+            request_id: &request_id,
+            regions: &self.regions,
+            signature_policy: &self.signature_policy,
+            verify_checksums: self.verify_checksums,
         };
         let result = crate::ops::call(&mut req, &ccx).await.map(Into::into);
 
@@ -100,7 +227,28 @@ impl S3Service {
             Err(ref err) => error!(?duration, ?err),
         }
 
-        result
+        #[cfg(feature = "metrics")]
+        let result = match pending_metrics {
+            Some(pending) => pending.finish(&req, result),
+            None => result,
+        };
+
+        match (pending_log, &self.access_log) {
+            (Some(pending), Some(sink)) => pending.finish(&req, &request_id, result, sink.clone()),
+            _ => result,
+        }
+    }
+
+    /// Reads the connection information from the extensions, and resolves the client IP.
+    fn resolve_connection(&self, req: &mut Request) {
+        let mut connection = req.extensions.get::<ConnectionInfo>().cloned();
+        if let Some(tls) = req.extensions.get::<TlsInfo>() {
+            connection.get_or_insert_default().tls.get_or_insert_with(|| tls.clone());
+        }
+
+        let peer_ip = connection.as_ref().and_then(|c| c.peer_addr).map(|addr| addr.ip());
+        req.s3ext.client_ip = peer_ip.map(|ip| crate::connection::resolve_client_ip(ip, &req.headers, &self.trusted_proxies));
+        req.s3ext.connection = connection;
     }
 
     #[must_use]
@@ -127,6 +275,22 @@ impl SharedS3Service {
     pub fn into_make_service(self) -> MakeService<Self> {
         MakeService(self)
     }
+
+    /// Returns a service making a [`ConnectionS3Service`] for every accepted connection.
+    #[must_use]
+    pub fn into_make_connection_service(self) -> MakeConnectionService {
+        MakeConnectionService(self)
+    }
+
+    /// Returns a service serving a connection,
+    /// which attaches the connection information to every request.
+    #[must_use]
+    pub fn with_connection(&self, connection: ConnectionInfo) -> ConnectionS3Service {
+        ConnectionS3Service {
+            service: self.clone(),
+            connection: Arc::new(connection),
+        }
+    }
 }
 
 impl AsRef<S3Service> for SharedS3Service {
@@ -170,6 +334,56 @@ impl tower::Service<hyper::Request<hyper::body::Incoming>> for SharedS3Service {
     }
 }
 
+/// A service serving a connection
+///
+/// See [`SharedS3Service::with_connection`].
+#[derive(Debug, Clone)]
+pub struct ConnectionS3Service {
+    service: SharedS3Service,
+    connection: Arc<ConnectionInfo>,
+}
+
+impl ConnectionS3Service {
+    fn call_with_connection(
+        &self,
+        req: hyper::Request<hyper::body::Incoming>,
+    ) -> BoxFuture<'static, S3Result<hyper::Response<Body>>> {
+        let mut req = req.map(Body::from);
+        req.extensions_mut().insert(ConnectionInfo::clone(&self.connection));
+        let service = self.service.0.clone();
+        Box::pin(service.call_shared(req))
+    }
+}
+
+impl Service<hyper::Request<hyper::body::Incoming>> for ConnectionS3Service {
+    type Response = hyper::Response<Body>;
+
+    type Error = S3Error;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn call(&self, req: hyper::Request<hyper::body::Incoming>) -> Self::Future {
+        self.call_with_connection(req)
+    }
+}
+
+#[cfg(feature = "tower")]
+impl tower::Service<hyper::Request<hyper::body::Incoming>> for ConnectionS3Service {
+    type Response = hyper::Response<Body>;
+
+    type Error = S3Error;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: hyper::Request<hyper::body::Incoming>) -> Self::Future {
+        self.call_with_connection(req)
+    }
+}
+
 #[derive(Clone)]
 pub struct MakeService<S>(S);
 
@@ -184,3 +398,38 @@ impl<T, S: Clone> Service<T> for MakeService<S> {
         ready(Ok(self.0.clone()))
     }
 }
+
+/// A service making a [`ConnectionS3Service`] for every accepted connection
+///
+/// The connection information is read from the connection target.
+#[derive(Debug, Clone)]
+pub struct MakeConnectionService(SharedS3Service);
+
+impl<T: Connected> Service<T> for MakeConnectionService {
+    type Response = ConnectionS3Service;
+
+    type Error = Infallible;
+
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn call(&self, target: T) -> Self::Future {
+        ready(Ok(self.0.with_connection(target.connection_info())))
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<T: Connected> tower::Service<T> for MakeConnectionService {
+    type Response = ConnectionS3Service;
+
+    type Error = Infallible;
+
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, target: T) -> Self::Future {
+        ready(Ok(self.0.with_connection(target.connection_info())))
+    }
+}
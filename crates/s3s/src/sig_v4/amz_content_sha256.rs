@@ -17,6 +17,14 @@ pub enum AmzContentSha256<'a> {
     },
     /// `UNSIGNED-PAYLOAD`
     UnsignedPayload,
+    /// `STREAMING-AWS4-HMAC-SHA256-PAYLOAD-TRAILER`
+    MultipleChunksWithTrailer,
+    /// `STREAMING-UNSIGNED-PAYLOAD-TRAILER`
+    UnsignedMultipleChunksWithTrailer,
+    /// `STREAMING-AWS4-ECDSA-P256-SHA256-PAYLOAD`
+    EcdsaMultipleChunks,
+    /// `STREAMING-AWS4-ECDSA-P256-SHA256-PAYLOAD-TRAILER`
+    EcdsaMultipleChunksWithTrailer,
 }
 
 /// [`AmzContentSha256`]
@@ -36,6 +44,10 @@ impl<'a> AmzContentSha256<'a> {
         match header {
             "UNSIGNED-PAYLOAD" => Ok(Self::UnsignedPayload),
             "STREAMING-AWS4-HMAC-SHA256-PAYLOAD" => Ok(Self::MultipleChunks),
+            "STREAMING-AWS4-HMAC-SHA256-PAYLOAD-TRAILER" => Ok(Self::MultipleChunksWithTrailer),
+            "STREAMING-UNSIGNED-PAYLOAD-TRAILER" => Ok(Self::UnsignedMultipleChunksWithTrailer),
+            "STREAMING-AWS4-ECDSA-P256-SHA256-PAYLOAD" => Ok(Self::EcdsaMultipleChunks),
+            "STREAMING-AWS4-ECDSA-P256-SHA256-PAYLOAD-TRAILER" => Ok(Self::EcdsaMultipleChunksWithTrailer),
             payload_checksum => {
                 if !is_sha256_checksum(payload_checksum) {
                     return Err(ParseAmzContentSha256Error::InvalidChecksum);
@@ -44,4 +56,32 @@ impl<'a> AmzContentSha256<'a> {
             }
         }
     }
+
+    /// Returns `true` if the payload is sent in aws-chunked encoding
+    #[must_use]
+    pub fn is_streaming(&self) -> bool {
+        matches!(
+            self,
+            Self::MultipleChunks
+                | Self::MultipleChunksWithTrailer
+                | Self::UnsignedMultipleChunksWithTrailer
+                | Self::EcdsaMultipleChunks
+                | Self::EcdsaMultipleChunksWithTrailer
+        )
+    }
+
+    /// Returns `true` if the payload has a trailer section
+    #[must_use]
+    pub fn has_trailer(&self) -> bool {
+        matches!(
+            self,
+            Self::MultipleChunksWithTrailer | Self::UnsignedMultipleChunksWithTrailer | Self::EcdsaMultipleChunksWithTrailer
+        )
+    }
+
+    /// Returns `true` if the chunks are signed with `SigV4a`
+    #[must_use]
+    pub fn is_ecdsa(&self) -> bool {
+        matches!(self, Self::EcdsaMultipleChunks | Self::EcdsaMultipleChunksWithTrailer)
+    }
 }
@@ -26,6 +26,9 @@ pub struct AuthorizationV4<'a> {
 /// This string has the following form:
 /// `<your-access-key-id>/<date>/<aws-region>/<aws-service>/aws4_request`
 ///
+/// The credential scope of `SigV4a` has no region:
+/// `<your-access-key-id>/<date>/<aws-service>/aws4_request`
+///
 /// See [sigv4-auth-using-authorization-header](https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-auth-using-authorization-header.html)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CredentialV4<'a> {
@@ -33,7 +36,7 @@ pub struct CredentialV4<'a> {
     pub access_key_id: &'a str,
     /// \<date\> value is specified using YYYYMMDD format.
     pub date: &'a str,
-    /// region
+    /// region (empty for `SigV4a`)
     pub aws_region: &'a str,
     /// \<aws-service\> value is `s3` when sending request to Amazon S3.
     pub aws_service: &'a str,
@@ -82,11 +85,12 @@ mod parser {
     use crate::utils::parser::{Error, consume, digit2, digit4};
 
     use nom::IResult;
+    use nom::branch::alt;
     use nom::bytes::complete::{tag, take, take_till, take_till1};
     use nom::character::complete::{multispace0, multispace1};
-    use nom::combinator::verify;
+    use nom::combinator::{map, verify};
     use nom::multi::separated_list1;
-    use nom::sequence::{delimited, preceded, terminated};
+    use nom::sequence::{delimited, pair, preceded, terminated};
 
     pub fn parse_authorization(mut input: &str) -> IResult<&str, AuthorizationV4<'_>> {
         let s = &mut input;
@@ -128,8 +132,9 @@ mod parser {
 
         let access_key_id = consume(s, until_slash0)?;
         let date = consume(s, verify(until_slash1, |s| verify_date(s).is_ok()))?;
-        let aws_region = consume(s, until_slash0)?;
-        let aws_service = consume(s, until_slash1)?;
+        let region_and_service = pair(until_slash0, until_slash1);
+        let service_only = map(until_slash1, |service| ("", service));
+        let (aws_region, aws_service) = consume(s, alt((region_and_service, service_only)))?;
         consume(s, tag("aws4_request"))?;
 
         let ans = CredentialV4 {
@@ -241,4 +246,22 @@ mod tests {
         assert_eq!(ans.signed_headers, &["host", "x-amz-content-sha256", "x-amz-date", "x-amz-user-agent"]);
         assert_eq!(ans.signature, "d2ff90c5a29855fd7c56251aa4c02c49a1bc258a8cc9c191ba3cfc037c5dab80");
     }
+
+    #[test]
+    fn sig_v4a() {
+        let auth = concat!(
+            "AWS4-ECDSA-P256-SHA256 ",
+            "Credential=AKIDEXAMPLE/20150830/service/aws4_request, ",
+            "SignedHeaders=host;x-amz-date;x-amz-region-set, ",
+            "Signature=3046022100fddaaf816a31e30d04973875b13bc27b98da10907a3a1872fd5819a221334abc022100de2ee81d83b54f31efc82834de07e6cd4d5c6f25bfa08e0f29a15bb0977c343e"
+        );
+        let ans = AuthorizationV4::parse(auth).unwrap();
+
+        assert_eq!(ans.algorithm, "AWS4-ECDSA-P256-SHA256");
+        assert_eq!(ans.credential.access_key_id, "AKIDEXAMPLE");
+        assert_eq!(ans.credential.date, "20150830");
+        assert_eq!(ans.credential.aws_region, "");
+        assert_eq!(ans.credential.aws_service, "service");
+        assert_eq!(ans.signed_headers, &["host", "x-amz-date", "x-amz-region-set"]);
+    }
 }
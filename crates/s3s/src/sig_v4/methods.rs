@@ -4,6 +4,7 @@ use super::AmzDate;
 
 use crate::auth::SecretKey;
 use crate::http::OrderedHeaders;
+use crate::region::Region;
 use crate::utils::crypto::{hex, hex_sha256, hex_sha256_chunk, hmac_sha256};
 use crate::utils::stable_sort_by_first;
 
@@ -79,6 +80,14 @@ pub enum Payload<'a> {
     SingleChunk(&'a [u8]),
     /// multiple chunks
     MultipleChunks,
+    /// multiple chunks with trailing headers
+    MultipleChunksWithTrailer,
+    /// unsigned multiple chunks with trailing headers
+    UnsignedMultipleChunksWithTrailer,
+    /// multiple chunks signed with `SigV4a`
+    EcdsaMultipleChunks,
+    /// multiple chunks with trailing headers signed with `SigV4a`
+    EcdsaMultipleChunksWithTrailer,
 }
 
 /// create canonical request
@@ -177,6 +186,10 @@ pub fn create_canonical_request(
             Payload::Empty => ans.push_str(EMPTY_STRING_SHA256_HASH),
             Payload::SingleChunk(data) => hex_sha256(data, |s| ans.push_str(s)),
             Payload::MultipleChunks => ans.push_str("STREAMING-AWS4-HMAC-SHA256-PAYLOAD"),
+            Payload::MultipleChunksWithTrailer => ans.push_str("STREAMING-AWS4-HMAC-SHA256-PAYLOAD-TRAILER"),
+            Payload::UnsignedMultipleChunksWithTrailer => ans.push_str("STREAMING-UNSIGNED-PAYLOAD-TRAILER"),
+            Payload::EcdsaMultipleChunks => ans.push_str("STREAMING-AWS4-ECDSA-P256-SHA256-PAYLOAD"),
+            Payload::EcdsaMultipleChunksWithTrailer => ans.push_str("STREAMING-AWS4-ECDSA-P256-SHA256-PAYLOAD-TRAILER"),
         }
     }
 
@@ -185,7 +198,7 @@ pub fn create_canonical_request(
 
 /// create string to sign
 #[must_use]
-pub fn create_string_to_sign(canonical_request: &str, amz_date: &AmzDate, region: &str, service: &str) -> String {
+pub fn create_string_to_sign(canonical_request: &str, amz_date: &AmzDate, region: &Region, service: &str) -> String {
     let mut ans = String::with_capacity(256);
 
     {
@@ -203,7 +216,7 @@ pub fn create_string_to_sign(canonical_request: &str, amz_date: &AmzDate, region
         // <CredentialScope>\n
         ans.push_str(&amz_date.fmt_date());
         ans.push('/');
-        ans.push_str(region); // TODO: use a `Region` type
+        ans.push_str(region.as_str());
         ans.push('/');
         ans.push_str(service);
         ans.push_str("/aws4_request\n");
@@ -220,7 +233,7 @@ pub fn create_string_to_sign(canonical_request: &str, amz_date: &AmzDate, region
 /// create `string_to_sign` of a chunk
 pub fn create_chunk_string_to_sign(
     amz_date: &AmzDate,
-    region: &str,
+    region: &Region,
     service: &str,
     prev_signature: &str,
     chunk_data: &[Bytes],
@@ -237,7 +250,7 @@ pub fn create_chunk_string_to_sign(
     {
         ans.push_str(&amz_date.fmt_date());
         ans.push('/');
-        ans.push_str(region); // TODO: use a `Region` type
+        ans.push_str(region.as_str());
         ans.push('/');
         ans.push_str(service);
         ans.push_str("/aws4_request\n");
@@ -261,13 +274,56 @@ pub fn create_chunk_string_to_sign(
     ans
 }
 
+/// create `string_to_sign` of the trailing headers
+pub fn create_trailer_string_to_sign(
+    amz_date: &AmzDate,
+    region: &Region,
+    service: &str,
+    prev_signature: &str,
+    trailing_headers: &[(impl AsRef<str>, impl AsRef<str>)],
+) -> String {
+    let mut ans = String::with_capacity(256);
+
+    {
+        ans.push_str("AWS4-HMAC-SHA256-TRAILER\n");
+    }
+    {
+        ans.push_str(&amz_date.fmt_iso8601());
+        ans.push('\n');
+    }
+    {
+        ans.push_str(&amz_date.fmt_date());
+        ans.push('/');
+        ans.push_str(region.as_str());
+        ans.push('/');
+        ans.push_str(service);
+        ans.push_str("/aws4_request\n");
+    }
+    {
+        ans.push_str(prev_signature);
+        ans.push('\n');
+    }
+    {
+        let mut canonical_headers = String::with_capacity(64);
+        for (name, value) in trailing_headers {
+            canonical_headers.push_str(name.as_ref());
+            canonical_headers.push(':');
+            canonical_headers.push_str(value.as_ref());
+            canonical_headers.push('\n');
+        }
+        hex_sha256(canonical_headers.as_bytes(), |s| ans.push_str(s));
+    }
+
+    ans
+}
+
 /// calculate signature
 #[must_use]
 pub fn calculate_signature(
     string_to_sign: &str,
     secret_key: &SecretKey,
     amz_date: &AmzDate,
-    region: &str,
+    region: &Region,
     service: &str,
 ) -> String {
     let mut secret = {
@@ -286,7 +342,7 @@ pub fn calculate_signature(
     drop(secret);
 
     // DateRegionKey
-    let date_region_key = hmac_sha256(date_key, region); // TODO: use a `Region` type
+    let date_region_key = hmac_sha256(date_key, region.as_str());
 
     // DateRegionServiceKey
     let date_region_service_key = hmac_sha256(date_region_key, service);
@@ -400,7 +456,7 @@ mod tests {
         let secret_access_key = SecretKey::from("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY");
         let timestamp = "20130524T000000Z";
         // let bucket = "examplebucket";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let path = "/test.txt";
 
@@ -454,7 +510,7 @@ mod tests {
         let secret_access_key = SecretKey::from("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY");
         let timestamp = "20130524T000000Z";
         // let bucket = "examplebucket";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let path = "/test$file.text";
 
@@ -511,7 +567,7 @@ mod tests {
         let secret_access_key = SecretKey::from("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY");
         let timestamp = "20130524T000000Z";
         // let bucket = "examplebucket";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let path = "/examplebucket/chunkObject.txt";
 
@@ -569,7 +625,7 @@ mod tests {
     fn example_put_object_multiple_chunks_chunk_signature() {
         let secret_access_key = SecretKey::from("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY");
         let timestamp = "20130524T000000Z";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let date = AmzDate::parse(timestamp).unwrap();
 
@@ -632,7 +688,7 @@ mod tests {
         let secret_access_key = SecretKey::from("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY");
         let timestamp = "20130524T000000Z";
         // let bucket = "examplebucket";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let path = "/";
 
@@ -684,7 +740,7 @@ mod tests {
         let secret_access_key = SecretKey::from("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY");
         let timestamp = "20130524T000000Z";
         // let bucket = "examplebucket";
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
         let path = "/";
 
@@ -780,12 +836,8 @@ mod tests {
             )
         );
 
-        let string_to_sign = create_string_to_sign(
-            &canonical_request,
-            &info.amz_date,
-            info.credential.aws_region,
-            info.credential.aws_service,
-        );
+        let region = Region::new(info.credential.aws_region).unwrap();
+        let string_to_sign = create_string_to_sign(&canonical_request, &info.amz_date, &region, info.credential.aws_service);
         assert_eq!(
             string_to_sign,
             concat!(
@@ -796,13 +848,8 @@ mod tests {
             )
         );
 
-        let signature = calculate_signature(
-            &string_to_sign,
-            &secret_access_key,
-            &info.amz_date,
-            info.credential.aws_region,
-            info.credential.aws_service,
-        );
+        let signature =
+            calculate_signature(&string_to_sign, &secret_access_key, &info.amz_date, &region, info.credential.aws_service);
         assert_eq!(signature, "aeeed9bbccd4d02ee5c0109b86d86835f995330da4c265957d157751f604d404");
         assert_eq!(signature, info.signature);
     }
@@ -835,7 +882,7 @@ mod tests {
 
         let payload = Payload::Empty;
         let date = AmzDate::parse(x_amz_date).unwrap();
-        let region = "us-east-1";
+        let region = &Region::new("us-east-1").unwrap();
         let service = "s3";
 
         let secret_access_key = SecretKey::from("minioadmin");
@@ -22,3 +22,6 @@ pub use self::post_signature::*;
 
 mod methods;
 pub use self::methods::*;
+
+#[cfg(feature = "sigv4a")]
+pub mod v4a;
@@ -23,6 +23,9 @@ pub struct PresignedUrlV4<'a> {
     pub signed_headers: SmallVec<[&'a str; 16]>,
     /// signature
     pub signature: &'a str,
+    /// region set of `SigV4a`
+    #[cfg_attr(not(feature = "sigv4a"), allow(dead_code))]
+    pub region_set: Option<&'a str>,
 }
 
 /// [`PresignedUrlV4`]
@@ -86,7 +89,13 @@ impl<'a> PresignedUrlV4<'a> {
         }
         let signed_headers = info.signed_headers.split(';').collect();
 
-        if !is_sha256_checksum(info.signature) {
+        // SigV4a signatures are hex-encoded DER instead of hex-encoded HMAC-SHA256
+        let is_valid_signature = if algorithm == "AWS4-ECDSA-P256-SHA256" {
+            is_hex_signature(info.signature)
+        } else {
+            is_sha256_checksum(info.signature)
+        };
+        if !is_valid_signature {
             return Err(err());
         }
         let signature = info.signature;
@@ -98,10 +107,16 @@ impl<'a> PresignedUrlV4<'a> {
             expires,
             signed_headers,
             signature,
+            region_set: qs.get_unique("X-Amz-Region-Set"),
         })
     }
 }
 
+fn is_hex_signature(s: &str) -> bool {
+    let is_lowercase_hex = |c: u8| matches!(c, b'0'..=b'9' | b'a'..=b'f');
+    !s.is_empty() && s.len().is_multiple_of(2) && s.as_bytes().iter().copied().all(is_lowercase_hex)
+}
+
 fn parse_expires(s: &str) -> Option<time::Duration> {
     let x = s.parse::<u32>().ok().filter(|&x| x > 0)?;
     Some(time::Duration::new(i64::from(x), 0))
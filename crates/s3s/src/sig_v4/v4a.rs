@@ -0,0 +1,301 @@
+//! AWS Signature Version 4A (ECDSA P-256)
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-authenticating-requests.html>
+//!
+
+use super::AmzDate;
+
+use crate::auth::SecretKey;
+use crate::utils::crypto::{hex_sha256, hex_sha256_chunk, hmac_sha256};
+
+use hyper::body::Bytes;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::elliptic_curve::PrimeField;
+use p256::{NonZeroScalar, PublicKey, Scalar};
+use smallvec::SmallVec;
+use zeroize::Zeroize;
+
+/// `X-Amz-Algorithm` of `SigV4a`
+pub const ALGORITHM: &str = "AWS4-ECDSA-P256-SHA256";
+
+/// sha256 hash of an empty string
+const EMPTY_STRING_SHA256_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Derives the public key of the ECDSA key pair from the secret key.
+///
+/// The private key is derived with the counter mode KDF of NIST SP 800-108,
+/// retrying with an incremented counter until the candidate is a valid P-256 scalar.
+#[must_use]
+pub fn derive_verifying_key(access_key_id: &str, secret_key: &SecretKey) -> Option<VerifyingKey> {
+    let mut input_key = {
+        let secret_key = secret_key.expose();
+        let mut buf = <SmallVec<[u8; 128]>>::with_capacity(secret_key.len().saturating_add(5));
+        buf.extend_from_slice(b"AWS4A");
+        buf.extend_from_slice(secret_key.as_bytes());
+        buf
+    };
+
+    let mut fixed_input = Vec::with_capacity(access_key_id.len().saturating_add(32));
+    let mut ans = None;
+
+    for counter in 1..=u8::MAX {
+        fixed_input.clear();
+        fixed_input.extend_from_slice(&1_u32.to_be_bytes());
+        fixed_input.extend_from_slice(ALGORITHM.as_bytes());
+        fixed_input.push(0);
+        fixed_input.extend_from_slice(access_key_id.as_bytes());
+        fixed_input.push(counter);
+        fixed_input.extend_from_slice(&256_u32.to_be_bytes());
+
+        let mut k0 = hmac_sha256(input_key.as_slice(), &fixed_input);
+
+        // the private key is `k0 + 1`, which requires `k0 <= n - 2`
+        let scalar: Option<Scalar> = Scalar::from_repr(k0.into()).into();
+        k0.zeroize();
+
+        let private_key: Option<NonZeroScalar> = scalar.and_then(|k0| NonZeroScalar::new(k0 + Scalar::ONE).into());
+        if let Some(private_key) = private_key {
+            ans = Some(VerifyingKey::from(PublicKey::from_secret_scalar(&private_key)));
+            break;
+        }
+    }
+
+    input_key.zeroize();
+    ans
+}
+
+/// Verifies a hex-encoded DER signature.
+#[must_use]
+pub fn verify_signature(key: &VerifyingKey, string_to_sign: &str, signature: &str) -> bool {
+    let Ok(der) = hex_simd::decode_to_vec(signature) else { return false };
+    let Ok(signature) = Signature::from_der(&der) else { return false };
+    key.verify(string_to_sign.as_bytes(), &signature).is_ok()
+}
+
+/// Removes the `*` padding of a chunk signature.
+#[must_use]
+pub fn trim_padded_signature(signature: &[u8]) -> &[u8] {
+    let len = signature.iter().rposition(|&b| b != b'*').map_or(0, |i| i.wrapping_add(1));
+    &signature[..len]
+}
+
+/// create `string_to_sign`
+#[must_use]
+pub fn create_string_to_sign(canonical_request: &str, amz_date: &AmzDate, service: &str) -> String {
+    let mut ans = String::with_capacity(256);
+
+    {
+        ans.push_str(ALGORITHM);
+        ans.push('\n');
+    }
+    push_scope(&mut ans, amz_date, service);
+    {
+        hex_sha256(canonical_request.as_bytes(), |s| ans.push_str(s));
+    }
+
+    ans
+}
+
+/// create `string_to_sign` of a chunk
+#[must_use]
+pub fn create_chunk_string_to_sign(amz_date: &AmzDate, service: &str, prev_signature: &str, chunk_data: &[Bytes]) -> String {
+    let mut ans = String::with_capacity(256);
+
+    {
+        ans.push_str(ALGORITHM);
+        ans.push_str("-PAYLOAD\n");
+    }
+    push_scope(&mut ans, amz_date, service);
+    {
+        ans.push_str(prev_signature);
+        ans.push('\n');
+    }
+    {
+        ans.push_str(EMPTY_STRING_SHA256_HASH);
+        ans.push('\n');
+    }
+    {
+        if chunk_data.is_empty() {
+            ans.push_str(EMPTY_STRING_SHA256_HASH);
+        } else {
+            hex_sha256_chunk(chunk_data, |s| ans.push_str(s));
+        }
+    }
+
+    ans
+}
+
+/// create `string_to_sign` of the trailing headers
+#[must_use]
+pub fn create_trailer_string_to_sign(
+    amz_date: &AmzDate,
+    service: &str,
+    prev_signature: &str,
+    trailing_headers: &[(impl AsRef<str>, impl AsRef<str>)],
+) -> String {
+    let mut ans = String::with_capacity(256);
+
+    {
+        ans.push_str(ALGORITHM);
+        ans.push_str("-TRAILER\n");
+    }
+    push_scope(&mut ans, amz_date, service);
+    {
+        ans.push_str(prev_signature);
+        ans.push('\n');
+    }
+    {
+        let mut canonical_headers = String::with_capacity(64);
+        for (name, value) in trailing_headers {
+            canonical_headers.push_str(name.as_ref());
+            canonical_headers.push(':');
+            canonical_headers.push_str(value.as_ref());
+            canonical_headers.push('\n');
+        }
+        hex_sha256(canonical_headers.as_bytes(), |s| ans.push_str(s));
+    }
+
+    ans
+}
+
+/// `<timestamp>\n<date>/<service>/aws4_request\n`
+///
+/// The credential scope of `SigV4a` does not contain a region.
+fn push_scope(ans: &mut String, amz_date: &AmzDate, service: &str) {
+    ans.push_str(&amz_date.fmt_iso8601());
+    ans.push('\n');
+
+    ans.push_str(&amz_date.fmt_date());
+    ans.push('/');
+    ans.push_str(service);
+    ans.push_str("/aws4_request\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::http::OrderedHeaders;
+    use crate::sig_v4::{Payload, create_canonical_request};
+
+    use std::ops::Not;
+
+    use hyper::Method;
+
+    // Test vectors from the AWS SigV4a test suite (`get-vanilla`)
+
+    const ACCESS_KEY_ID: &str = "AKIDEXAMPLE";
+    const SECRET_ACCESS_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+    fn verifying_key() -> VerifyingKey {
+        derive_verifying_key(ACCESS_KEY_ID, &SecretKey::from(SECRET_ACCESS_KEY)).unwrap()
+    }
+
+    #[test]
+    fn derive_key() {
+        let key = verifying_key();
+        let point = key.to_encoded_point(false);
+        assert_eq!(
+            hex_simd::encode_to_string(point.x().unwrap(), hex_simd::AsciiCase::Lower),
+            "b6618f6a65740a99e650b33b6b4b5bd0d43b176d721a3edfea7e7d2d56d936b1"
+        );
+        assert_eq!(
+            hex_simd::encode_to_string(point.y().unwrap(), hex_simd::AsciiCase::Lower),
+            "865ed22a7eadc9c5cb9d2cbaca1b3699139fedc5043dc6661864218330c8e518"
+        );
+    }
+
+    #[test]
+    fn get_vanilla_header() {
+        let method = Method::GET;
+        let headers = OrderedHeaders::from_slice_unchecked(&[
+            ("host", "example.amazonaws.com"),
+            ("x-amz-date", "20150830T123600Z"),
+            ("x-amz-region-set", "us-east-1"),
+        ]);
+        let qs: &[(String, String)] = &[];
+
+        let canonical_request = create_canonical_request(&method, "/", qs, &headers, Payload::Empty);
+        assert_eq!(
+            canonical_request,
+            concat!(
+                "GET\n",
+                "/\n",
+                "\n",
+                "host:example.amazonaws.com\n",
+                "x-amz-date:20150830T123600Z\n",
+                "x-amz-region-set:us-east-1\n",
+                "\n",
+                "host;x-amz-date;x-amz-region-set\n",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            )
+        );
+
+        let amz_date = AmzDate::parse("20150830T123600Z").unwrap();
+        let string_to_sign = create_string_to_sign(&canonical_request, &amz_date, "service");
+        assert_eq!(
+            string_to_sign,
+            concat!(
+                "AWS4-ECDSA-P256-SHA256\n",
+                "20150830T123600Z\n",
+                "20150830/service/aws4_request\n",
+                "cf59db423e841c8b7e3444158185aa261b724a5c27cbe762676f3eed19f4dc02",
+            )
+        );
+
+        let key = verifying_key();
+        let signatures = [
+            "3045022018b4e277d0281864beb51d3600e23f88510ea5031d68ddfbb68614b82a5eb7d2022100effb9c5f22ed9ef3ae0ab243d21f06bce82365bbb79529a07b6888c343ae5f8c",
+            "3046022100fddaaf816a31e30d04973875b13bc27b98da10907a3a1872fd5819a221334abc022100de2ee81d83b54f31efc82834de07e6cd4d5c6f25bfa08e0f29a15bb0977c343e",
+        ];
+        for signature in signatures {
+            assert!(verify_signature(&key, &string_to_sign, signature));
+        }
+
+        let other_string_to_sign = string_to_sign.replace("20150830T123600Z", "20150830T123601Z");
+        assert!(verify_signature(&key, &other_string_to_sign, signatures[0]).not());
+        assert!(verify_signature(&key, &string_to_sign, "3045").not());
+    }
+
+    #[test]
+    fn get_vanilla_query() {
+        let canonical_request = concat!(
+            "GET\n",
+            "/\n",
+            "X-Amz-Algorithm=AWS4-ECDSA-P256-SHA256&X-Amz-Credential=AKIDEXAMPLE%2F20150830%2Fservice%2Faws4_request&X-Amz-Date=20150830T123600Z&X-Amz-Expires=3600&X-Amz-Region-Set=us-east-1&X-Amz-SignedHeaders=host\n",
+            "host:example.amazonaws.com\n",
+            "\n",
+            "host\n",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+
+        let amz_date = AmzDate::parse("20150830T123600Z").unwrap();
+        let string_to_sign = create_string_to_sign(canonical_request, &amz_date, "service");
+        assert_eq!(
+            string_to_sign,
+            concat!(
+                "AWS4-ECDSA-P256-SHA256\n",
+                "20150830T123600Z\n",
+                "20150830/service/aws4_request\n",
+                "890c4ed28c1a1ac10b5862719b537afbe392e987dc1aab1efa16fe7de41d3c81",
+            )
+        );
+
+        let key = verifying_key();
+        let signatures = [
+            "3045022100b3246f8e1442dd58e73292ae1294720c3256ee58f6cea50f3a3f83b4a4b0331a0220329693f7745c9008a5887c0529ceccb0fae4e16d707b712ea66acdbaf963769a",
+            "3045022100a28466db6c76223d5aa6ed436a8451b30e21ba0a076e7f2f943dd1f3d2816249022049e4b3746929cd2a2cb7b7984dbe0f5d40a300e7d9a7a7f1307c196b6f96b61a",
+        ];
+        for signature in signatures {
+            assert!(verify_signature(&key, &string_to_sign, signature));
+        }
+    }
+
+    #[test]
+    fn trim_padding() {
+        assert_eq!(trim_padded_signature(b"3045****"), b"3045");
+        assert_eq!(trim_padded_signature(b"3045"), b"3045");
+        assert_eq!(trim_padded_signature(b"****"), b"");
+    }
+}
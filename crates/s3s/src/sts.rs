@@ -0,0 +1,587 @@
+//! STS `AssumeRole` endpoint
+//!
+//! [`AssumeRoleRoute`] issues temporary credentials of the configured roles to authenticated callers.
+//! [`StsAuth`] accepts the temporary credentials until they expire,
+//! and falls back to the inner authentication provider for other access keys.
+//!
+//! Both of them share a [`SessionStore`].
+//!
+//! See <https://docs.aws.amazon.com/STS/latest/APIReference/API_AssumeRole.html>
+
+use crate::Body;
+use crate::S3Request;
+use crate::S3Response;
+use crate::auth::Identity;
+use crate::auth::S3Auth;
+use crate::auth::SecretKey;
+use crate::dto;
+use crate::error::*;
+use crate::header;
+use crate::route::S3Route;
+use crate::sig_v4::AuthorizationV4;
+use crate::xml;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::ops::Not;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use hyper::HeaderMap;
+use hyper::Method;
+use hyper::StatusCode;
+use hyper::Uri;
+use hyper::http::Extensions;
+use hyper::http::HeaderValue;
+use serde::Deserialize;
+use subtle::ConstantTimeEq;
+use time::OffsetDateTime;
+
+/// The minimum value of `DurationSeconds`
+const MIN_DURATION: Duration = Duration::from_mins(15);
+
+/// The default value of `DurationSeconds`
+const DEFAULT_DURATION: Duration = Duration::from_hours(1);
+
+/// The maximum value of `DurationSeconds` accepted by AWS
+const MAX_DURATION: Duration = Duration::from_hours(12);
+
+/// The maximum size of a form-encoded request body
+const MAX_FORM_SIZE: usize = 64 * 1024;
+
+struct Session {
+    secret_key: SecretKey,
+    token: String,
+    expiration: OffsetDateTime,
+    user: dto::AssumedRoleUser,
+    source_access_key: String,
+}
+
+/// Temporary credentials issued by [`AssumeRoleRoute`]
+#[derive(Clone, Default)]
+pub struct SessionStore(Arc<Mutex<HashMap<String, Session>>>);
+
+impl std::fmt::Debug for SessionStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionStore")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Session>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the number of sessions, including expired ones which have not been removed yet.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the expired sessions.
+    pub fn remove_expired(&self) {
+        let now = OffsetDateTime::now_utc();
+        self.lock().retain(|_, s| s.expiration > now);
+    }
+
+    fn insert(&self, access_key: String, session: Session) {
+        let now = OffsetDateTime::now_utc();
+        let mut map = self.lock();
+        map.retain(|_, s| s.expiration > now);
+        map.insert(access_key, session);
+    }
+
+    /// Returns the access key of the caller who requested the temporary credentials.
+    ///
+    /// Returns `None` if the access key is not a temporary one.
+    /// Access control providers can use it to grant the permissions of the caller to the temporary credentials.
+    #[must_use]
+    pub fn source_access_key(&self, access_key: &str) -> Option<String> {
+        let now = OffsetDateTime::now_utc();
+        let map = self.lock();
+        let session = map.get(access_key).filter(|s| s.expiration > now)?;
+        Some(session.source_access_key.clone())
+    }
+
+    /// Returns `None` if the access key is not a temporary one.
+    fn get_secret_key(&self, access_key: &str, now: OffsetDateTime) -> Option<S3Result<SecretKey>> {
+        let mut map = self.lock();
+        let session = map.get(access_key)?;
+        if session.expiration <= now {
+            map.remove(access_key);
+            return Some(Err(s3_error!(ExpiredToken, "The provided token has expired.")));
+        }
+        Some(Ok(session.secret_key.clone()))
+    }
+
+    /// Returns `None` if the access key is not a temporary one.
+    fn check_session_token(&self, access_key: &str, session_token: Option<&str>) -> Option<S3Result<Identity>> {
+        let map = self.lock();
+        let session = map.get(access_key)?;
+        let result = match session_token {
+            None => Err(s3_error!(MissingSecurityHeader, "Missing header: x-amz-security-token")),
+            Some(token) if bool::from(token.as_bytes().ct_eq(session.token.as_bytes())) => {
+                Ok(Identity::new(session.user.clone()))
+            }
+            Some(_) => Err(s3_error!(InvalidToken, "The provided token is malformed or otherwise invalid.")),
+        };
+        Some(result)
+    }
+}
+
+/// An authentication provider which accepts the temporary credentials in a [`SessionStore`]
+///
+/// The [`dto::AssumedRoleUser`] of a session is attached to the credentials as [`Identity`].
+#[derive(Debug)]
+pub struct StsAuth<A> {
+    inner: A,
+    store: SessionStore,
+}
+
+impl<A: S3Auth> StsAuth<A> {
+    /// Constructs a new `StsAuth`.
+    ///
+    /// `inner` is used for the access keys which are not issued by [`AssumeRoleRoute`].
+    #[must_use]
+    pub fn new(inner: A, store: SessionStore) -> Self {
+        Self { inner, store }
+    }
+}
+
+#[async_trait::async_trait]
+impl<A: S3Auth> S3Auth for StsAuth<A> {
+    async fn get_secret_key(&self, access_key: &str) -> S3Result<SecretKey> {
+        match self.store.get_secret_key(access_key, OffsetDateTime::now_utc()) {
+            Some(result) => result,
+            None => self.inner.get_secret_key(access_key).await,
+        }
+    }
+
+    async fn check_session_token(&self, access_key: &str, session_token: Option<&str>) -> S3Result<Option<Identity>> {
+        match self.store.check_session_token(access_key, session_token) {
+            Some(result) => result.map(Some),
+            None => self.inner.check_session_token(access_key, session_token).await,
+        }
+    }
+}
+
+/// A custom route which handles `Action=AssumeRole`
+///
+/// It matches `POST /` requests with `Action=AssumeRole` in the query string,
+/// or form-encoded requests signed for the `sts` service.
+///
+/// The caller must be authenticated, and can only assume the roles added by [`AssumeRoleRoute::add_role`].
+/// The issued credentials are stored in the [`SessionStore`].
+#[derive(Debug)]
+pub struct AssumeRoleRoute {
+    store: SessionStore,
+    roles: HashSet<String>,
+    max_duration: Duration,
+}
+
+impl AssumeRoleRoute {
+    #[must_use]
+    pub fn new(store: SessionStore) -> Self {
+        Self {
+            store,
+            roles: HashSet::new(),
+            max_duration: MAX_DURATION,
+        }
+    }
+
+    /// Adds a role which can be assumed, such as `arn:aws:iam::123456789012:role/uploader`.
+    pub fn add_role(&mut self, role_arn: impl Into<String>) {
+        self.roles.insert(role_arn.into());
+    }
+
+    /// Sets the maximum lifetime of temporary credentials.
+    ///
+    /// The default value is 12 hours.
+    pub fn set_max_duration(&mut self, max_duration: Duration) {
+        self.max_duration = max_duration;
+    }
+
+    fn assume_role(&self, form: &AssumeRoleForm, caller: &str) -> S3Result<dto::AssumeRoleOutput> {
+        if form.action.as_deref() != Some("AssumeRole") {
+            return Err(s3_error!(NotImplemented, "Action other than AssumeRole is not implemented"));
+        }
+
+        let role_arn = form
+            .role_arn
+            .as_deref()
+            .ok_or_else(|| invalid_request!("missing parameter: RoleArn"))?;
+        let session_name = form
+            .role_session_name
+            .as_deref()
+            .ok_or_else(|| invalid_request!("missing parameter: RoleSessionName"))?;
+
+        if role_arn.is_empty() {
+            return Err(invalid_request!("invalid parameter: RoleArn"));
+        }
+        if self.roles.contains(role_arn).not() {
+            return Err(s3_error!(
+                AccessDenied,
+                "Not authorized to perform sts:AssumeRole on resource: {role_arn}"
+            ));
+        }
+        let role = RoleArn::parse(role_arn).unwrap_or(RoleArn {
+            partition: "aws",
+            account: "",
+            name: role_arn,
+        });
+        if is_valid_session_name(session_name).not() {
+            return Err(invalid_request!("invalid parameter: RoleSessionName"));
+        }
+
+        let duration = match form.duration_seconds {
+            Some(secs) => Duration::from_secs(secs),
+            None => DEFAULT_DURATION.min(self.max_duration),
+        };
+        if duration < MIN_DURATION || duration > self.max_duration {
+            let (min, max) = (MIN_DURATION.as_secs(), self.max_duration.as_secs());
+            return Err(invalid_request!("DurationSeconds must be between {min} and {max}"));
+        }
+
+        let access_key = random_access_key("ASIA")?;
+        let secret_key = random_base64(30)?;
+        let session_token = random_base64(96)?;
+        let role_id = random_access_key("AROA")?;
+        let expiration = OffsetDateTime::now_utc() + duration;
+
+        let user = dto::AssumedRoleUser {
+            arn: format!("arn:{}:sts::{}:assumed-role/{}/{session_name}", role.partition, role.account, role.name),
+            assumed_role_id: format!("{role_id}:{session_name}"),
+        };
+
+        // Temporary credentials requested with temporary credentials belong to the same source.
+        let source_access_key = self.store.source_access_key(caller).unwrap_or_else(|| caller.to_owned());
+
+        let session = Session {
+            secret_key: SecretKey::from(secret_key.as_str()),
+            token: session_token.clone(),
+            expiration,
+            user: user.clone(),
+            source_access_key,
+        };
+        self.store.insert(access_key.clone(), session);
+
+        Ok(dto::AssumeRoleOutput {
+            assumed_role_user: Some(user),
+            credentials: Some(dto::Credentials {
+                access_key_id: access_key,
+                expiration: expiration.into(),
+                secret_access_key: secret_key,
+                session_token,
+            }),
+            packed_policy_size: None,
+            source_identity: None,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl S3Route for AssumeRoleRoute {
+    fn is_match(&self, method: &Method, uri: &Uri, headers: &HeaderMap, _: &mut Extensions, _: Option<IpAddr>) -> bool {
+        if method != Method::POST || uri.path() != "/" {
+            return false;
+        }
+        if uri.query().is_some_and(|qs| qs.split('&').any(|p| p == "Action=AssumeRole")) {
+            return true;
+        }
+        // The action of a form-encoded request is in the body, which is not available here.
+        // S3 requests are never signed for `sts`.
+        is_form_encoded(headers) && is_signed_for_sts(headers)
+    }
+
+    fn name(&self) -> &'static str {
+        "AssumeRole"
+    }
+
+    async fn call(&self, mut req: S3Request<Body>) -> S3Result<S3Response<(StatusCode, Body)>> {
+        let body = req.input.store_all_limited(MAX_FORM_SIZE).await.map_err(|e| {
+            if e.is::<http_body_util::LengthLimitError>() {
+                s3_error!(MaxMessageLengthExceeded, "The request body is too large.")
+            } else {
+                S3Error::with_source(S3ErrorCode::InternalError, e)
+            }
+        })?;
+        // The parameters are in the form-encoded body, or in the query string.
+        let params = if body.is_empty() {
+            req.uri.query().unwrap_or_default().as_bytes()
+        } else {
+            &body
+        };
+        let form: AssumeRoleForm =
+            serde_urlencoded::from_bytes(params).map_err(|e| invalid_request!(e, "invalid form-encoded body"))?;
+
+        let caller = req.credentials.as_ref().map_or("", |c| c.access_key.as_str());
+        let output = self.assume_role(&form, caller)?;
+
+        let mut buf = Vec::with_capacity(1024);
+        {
+            let mut ser = xml::Serializer::new(&mut buf);
+            ser.decl()
+                .and_then(|()| xml::Serialize::serialize(&output, &mut ser))
+                .map_err(S3Error::internal_error)?;
+        }
+
+        let mut resp = S3Response::new((StatusCode::OK, Body::from(buf)));
+        resp.headers
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/xml"));
+        Ok(resp)
+    }
+}
+
+fn is_form_encoded(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(header::CONTENT_TYPE) else { return false };
+    let Some(mime) = content_type.to_str().ok().and_then(|s| s.parse::<mime::Mime>().ok()) else {
+        return false;
+    };
+    mime.essence_str() == mime::APPLICATION_WWW_FORM_URLENCODED.essence_str()
+}
+
+fn is_signed_for_sts(headers: &HeaderMap) -> bool {
+    let Some(authorization) = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    AuthorizationV4::parse(authorization).is_ok_and(|auth| auth.credential.aws_service == "sts")
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AssumeRoleForm {
+    action: Option<String>,
+    role_arn: Option<String>,
+    role_session_name: Option<String>,
+    duration_seconds: Option<u64>,
+}
+
+/// `arn:<partition>:iam::<account>:role/<path>/<name>`
+struct RoleArn<'a> {
+    partition: &'a str,
+    account: &'a str,
+    name: &'a str,
+}
+
+impl<'a> RoleArn<'a> {
+    fn parse(arn: &'a str) -> Option<Self> {
+        let mut parts = arn.splitn(6, ':');
+        let ("arn", partition, "iam", "", account, resource) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?, parts.next()?, parts.next()?)
+        else {
+            return None;
+        };
+        let name = resource.strip_prefix("role/")?.rsplit('/').next()?;
+        if partition.is_empty() || account.is_empty() || name.is_empty() {
+            return None;
+        }
+        Some(Self {
+            partition,
+            account,
+            name,
+        })
+    }
+}
+
+/// `[\w+=,.@-]{2,64}`
+fn is_valid_session_name(s: &str) -> bool {
+    (2..=64).contains(&s.len())
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'+' | b'=' | b',' | b'.' | b'@' | b'-'))
+}
+
+fn random_bytes<const N: usize>() -> S3Result<[u8; N]> {
+    let mut buf = [0; N];
+    getrandom::fill(&mut buf).map_err(S3Error::internal_error)?;
+    Ok(buf)
+}
+
+/// `<prefix>` followed by 16 random base32 characters
+fn random_access_key(prefix: &str) -> S3Result<String> {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let bytes = random_bytes::<16>()?;
+    let mut ans = String::with_capacity(prefix.len() + bytes.len());
+    ans.push_str(prefix);
+    ans.extend(bytes.iter().map(|&b| char::from(ALPHABET[usize::from(b % 32)])));
+    Ok(ans)
+}
+
+fn random_base64(len: usize) -> S3Result<String> {
+    let mut buf = vec![0; len];
+    getrandom::fill(&mut buf).map_err(S3Error::internal_error)?;
+    Ok(base64_simd::STANDARD.encode_to_string(&buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::auth::SimpleAuth;
+
+    fn form(duration_seconds: Option<u64>) -> AssumeRoleForm {
+        AssumeRoleForm {
+            action: Some("AssumeRole".into()),
+            role_arn: Some("arn:aws:iam::123456789012:role/ci/uploader".into()),
+            role_session_name: Some("ci-job".into()),
+            duration_seconds,
+        }
+    }
+
+    #[test]
+    fn role_arn() {
+        let role = RoleArn::parse("arn:aws:iam::123456789012:role/ci/uploader").unwrap();
+        assert_eq!(role.partition, "aws");
+        assert_eq!(role.account, "123456789012");
+        assert_eq!(role.name, "uploader");
+
+        assert!(RoleArn::parse("arn:aws:iam::123456789012:user/alice").is_none());
+        assert!(RoleArn::parse("arn:aws:s3:::bucket").is_none());
+        assert!(RoleArn::parse("uploader").is_none());
+    }
+
+    #[test]
+    fn session_name() {
+        assert!(is_valid_session_name("ci-job@example.com"));
+        assert!(is_valid_session_name("a").not());
+        assert!(is_valid_session_name("ci job").not());
+    }
+
+    #[test]
+    fn parse_form() {
+        let body = "Action=AssumeRole&Version=2011-06-15&RoleArn=arn%3Aaws%3Aiam%3A%3A123456789012%3Arole%2Fci&RoleSessionName=ci-job&DurationSeconds=900";
+        let form: AssumeRoleForm = serde_urlencoded::from_str(body).unwrap();
+        assert_eq!(form.action.as_deref(), Some("AssumeRole"));
+        assert_eq!(form.role_arn.as_deref(), Some("arn:aws:iam::123456789012:role/ci"));
+        assert_eq!(form.role_session_name.as_deref(), Some("ci-job"));
+        assert_eq!(form.duration_seconds, Some(900));
+    }
+
+    #[test]
+    fn route_match() {
+        let route = AssumeRoleRoute::new(SessionStore::new());
+        let is_match = |uri: &str, headers: &[(&str, &str)]| {
+            let uri = uri.parse::<Uri>().unwrap();
+            let mut map = HeaderMap::new();
+            for &(name, value) in headers {
+                map.insert(name.parse::<hyper::header::HeaderName>().unwrap(), HeaderValue::from_str(value).unwrap());
+            }
+            route.is_match(&Method::POST, &uri, &map, &mut Extensions::new(), None)
+        };
+
+        let form = ("content-type", "application/x-www-form-urlencoded; charset=utf-8");
+        let sts = (
+            "authorization",
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20250101/us-east-1/sts/aws4_request, SignedHeaders=host, Signature=abcd",
+        );
+        let s3 = (
+            "authorization",
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20250101/us-east-1/s3/aws4_request, SignedHeaders=host, Signature=abcd",
+        );
+
+        assert!(is_match("/", &[form, sts]));
+        assert!(is_match("/?Action=AssumeRole&Version=2011-06-15", &[]));
+        assert!(is_match("/", &[form, s3]).not());
+        assert!(is_match("/", &[form]).not());
+        assert!(is_match("/", &[sts]).not());
+        assert!(is_match("/?Action=GetCallerIdentity", &[]).not());
+        assert!(is_match("/bucket", &[form, sts]).not());
+    }
+
+    #[tokio::test]
+    async fn assume_role() {
+        let store = SessionStore::new();
+        let mut route = AssumeRoleRoute::new(store.clone());
+        let auth = StsAuth::new(SimpleAuth::from_single("AKIDEXAMPLE", "secret"), store.clone());
+
+        let err = route.assume_role(&form(None), "AKIDEXAMPLE").unwrap_err();
+        assert_eq!(*err.code(), S3ErrorCode::AccessDenied);
+        route.add_role("arn:aws:iam::123456789012:role/ci/uploader");
+
+        assert!(route.assume_role(&form(Some(60)), "AKIDEXAMPLE").is_err());
+        assert!(route.assume_role(&form(Some(86400)), "AKIDEXAMPLE").is_err());
+
+        let output = route.assume_role(&form(None), "AKIDEXAMPLE").unwrap();
+        let user = output.assumed_role_user.unwrap();
+        assert_eq!(user.arn, "arn:aws:sts::123456789012:assumed-role/uploader/ci-job");
+        assert!(user.assumed_role_id.ends_with(":ci-job"));
+
+        let cred = output.credentials.unwrap();
+        assert!(cred.access_key_id.starts_with("ASIA"));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.source_access_key(&cred.access_key_id).as_deref(), Some("AKIDEXAMPLE"));
+        assert!(store.source_access_key("AKIDEXAMPLE").is_none());
+
+        let chained = route.assume_role(&form(None), &cred.access_key_id).unwrap();
+        let chained = chained.credentials.unwrap().access_key_id;
+        assert_eq!(store.source_access_key(&chained).as_deref(), Some("AKIDEXAMPLE"));
+
+        let secret_key = auth.get_secret_key(&cred.access_key_id).await.unwrap();
+        assert_eq!(secret_key.expose(), cred.secret_access_key);
+
+        let token = cred.session_token.as_str();
+        let identity = auth
+            .check_session_token(&cred.access_key_id, Some(token))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.downcast_ref::<dto::AssumedRoleUser>().unwrap().arn, user.arn);
+        assert!(auth.check_session_token(&cred.access_key_id, Some("invalid")).await.is_err());
+        assert!(auth.check_session_token(&cred.access_key_id, None).await.is_err());
+
+        assert!(auth.get_secret_key("AKIDEXAMPLE").await.is_ok());
+        assert!(auth.check_session_token("AKIDEXAMPLE", None).await.unwrap().is_none());
+        assert!(auth.get_secret_key("unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn form_size_limit() {
+        let mut route = AssumeRoleRoute::new(SessionStore::new());
+        route.add_role("arn:aws:iam::123456789012:role/ci/uploader");
+
+        let body = format!(
+            "Action=AssumeRole&RoleArn=arn%3Aaws%3Aiam%3A%3A123456789012%3Arole%2Fci%2Fuploader&RoleSessionName=ci-job&Padding={}",
+            "x".repeat(MAX_FORM_SIZE)
+        );
+        let req = S3Request::new(Body::from(body));
+        let err = route.call(req).await.err().unwrap();
+        assert_eq!(*err.code(), S3ErrorCode::MaxMessageLengthExceeded);
+    }
+
+    #[tokio::test]
+    async fn expired_session() {
+        let store = SessionStore::new();
+        let auth = StsAuth::new(SimpleAuth::new(), store.clone());
+
+        let session = Session {
+            secret_key: SecretKey::from("secret"),
+            token: "token".into(),
+            expiration: OffsetDateTime::now_utc() - Duration::from_secs(1),
+            user: dto::AssumedRoleUser {
+                arn: "arn:aws:sts::123456789012:assumed-role/uploader/ci-job".into(),
+                assumed_role_id: "AROAEXAMPLE:ci-job".into(),
+            },
+            source_access_key: "AKIDEXAMPLE".into(),
+        };
+        store.lock().insert("ASIAEXAMPLE".into(), session);
+        assert!(store.source_access_key("ASIAEXAMPLE").is_none());
+
+        let err = auth.get_secret_key("ASIAEXAMPLE").await.unwrap_err();
+        assert_eq!(*err.code(), S3ErrorCode::ExpiredToken);
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,507 @@
+//! TLS connections
+//!
+//! A server terminating TLS provides a [`TlsInfo`] in the [`ConnectionInfo`](crate::connection::ConnectionInfo)
+//! of every request received over a secure connection, or inserts it into the request extensions directly.
+//! Requests without a [`TlsInfo`] are considered as plain HTTP requests.
+//!
+//! With the `tls` feature, [`TlsAcceptor`] accepts TLS connections with certificates
+//! loaded from PEM files, which can be reloaded without restarting the server, such as on SIGHUP.
+//! [`TlsListener`] accepts TCP connections and performs the TLS handshakes in the background.
+
+/// Information about the TLS session of a connection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsInfo {
+    /// The negotiated protocol version, such as `TLSv1.3`
+    pub version: Option<String>,
+    /// The negotiated cipher suite, such as `TLS_AES_128_GCM_SHA256`
+    pub cipher_suite: Option<String>,
+    /// The negotiated ALPN protocol, such as `h2`
+    pub alpn_protocol: Option<String>,
+    /// The server name sent by the client (SNI)
+    pub server_name: Option<String>,
+}
+
+#[cfg(feature = "tls")]
+pub use self::acceptor::TlsAcceptor;
+
+#[cfg(feature = "tls")]
+pub use self::listener::{MaybeTlsStream, TlsListener};
+
+#[cfg(feature = "tls")]
+mod acceptor {
+    use super::TlsInfo;
+
+    use crate::connection::{Connected, ConnectionInfo};
+
+    use std::fmt;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, RwLock};
+
+    use rustls::ServerConfig;
+    use rustls::pki_types::pem::{self, PemObject};
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    use tokio::io::{AsyncRead, AsyncWrite};
+    use tokio_rustls::server::TlsStream;
+    use tracing::{error, info};
+
+    /// A TLS acceptor with reloadable certificates
+    ///
+    /// Cloned acceptors share the same certificates.
+    #[derive(Clone)]
+    pub struct TlsAcceptor {
+        inner: Arc<Inner>,
+    }
+
+    struct Inner {
+        cert_path: PathBuf,
+        key_path: PathBuf,
+        config: RwLock<Arc<ServerConfig>>,
+    }
+
+    fn invalid_data(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+
+    fn pem_error(err: pem::Error) -> io::Error {
+        match err {
+            pem::Error::Io(err) => err,
+            _ => invalid_data(err),
+        }
+    }
+
+    fn load_config(cert_path: &Path, key_path: &Path) -> io::Result<Arc<ServerConfig>> {
+        let certs = CertificateDer::pem_file_iter(cert_path)
+            .map_err(pem_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(pem_error)?;
+        if certs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "no certificates found"));
+        }
+        let key = PrivateKeyDer::from_pem_file(key_path).map_err(pem_error)?;
+
+        let mut config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(invalid_data)?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(Arc::new(config))
+    }
+
+    impl TlsAcceptor {
+        /// Loads a certificate chain and a private key from PEM files.
+        ///
+        /// # Errors
+        /// Returns an error if the files can not be read or parsed.
+        pub fn from_pem_files(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> io::Result<Self> {
+            let cert_path = cert_path.into();
+            let key_path = key_path.into();
+            let config = load_config(&cert_path, &key_path)?;
+            Ok(Self {
+                inner: Arc::new(Inner {
+                    cert_path,
+                    key_path,
+                    config: RwLock::new(config),
+                }),
+            })
+        }
+
+        /// Reloads the certificate chain and the private key from the same files.
+        ///
+        /// Connections accepted later use the new certificates.
+        /// The previous certificates are kept if the files are invalid.
+        ///
+        /// # Errors
+        /// Returns an error if the files can not be read or parsed.
+        pub fn reload(&self) -> io::Result<()> {
+            let config = load_config(&self.inner.cert_path, &self.inner.key_path)?;
+            *self.inner.config.write().unwrap_or_else(std::sync::PoisonError::into_inner) = config;
+            Ok(())
+        }
+
+        /// Reloads the certificates every time the process receives SIGHUP.
+        ///
+        /// Does nothing on non-unix platforms.
+        ///
+        /// # Errors
+        /// Returns an error if the signal handler can not be registered.
+        pub fn reload_on_sighup(&self) -> io::Result<()> {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{SignalKind, signal};
+
+                let mut hangup = signal(SignalKind::hangup())?;
+                let acceptor = self.clone();
+                tokio::spawn(async move {
+                    while hangup.recv().await.is_some() {
+                        match acceptor.reload() {
+                            Ok(()) => info!("TLS certificates are reloaded"),
+                            Err(err) => error!("error reloading TLS certificates: {err}"),
+                        }
+                    }
+                });
+            }
+            Ok(())
+        }
+
+        fn config(&self) -> Arc<ServerConfig> {
+            let guard = self.inner.config.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+            Arc::clone(&guard)
+        }
+
+        /// Performs the TLS handshake on an accepted connection.
+        ///
+        /// # Errors
+        /// Returns an error if the handshake fails.
+        pub async fn accept<IO>(&self, io: IO) -> io::Result<(TlsStream<IO>, TlsInfo)>
+        where
+            IO: AsyncRead + AsyncWrite + Unpin,
+        {
+            let acceptor = tokio_rustls::TlsAcceptor::from(self.config());
+            let stream = acceptor.accept(io).await?;
+            let info = tls_info(stream.get_ref().1);
+            Ok((stream, info))
+        }
+    }
+
+    impl fmt::Debug for TlsAcceptor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("TlsAcceptor")
+                .field("cert_path", &self.inner.cert_path)
+                .field("key_path", &self.inner.key_path)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl<IO: Connected> Connected for TlsStream<IO> {
+        fn connection_info(&self) -> ConnectionInfo {
+            let (io, conn) = self.get_ref();
+            ConnectionInfo {
+                tls: Some(tls_info(conn)),
+                ..io.connection_info()
+            }
+        }
+    }
+
+    fn tls_info(conn: &rustls::ServerConnection) -> TlsInfo {
+        let version = conn.protocol_version().map(|v| match v {
+            rustls::ProtocolVersion::TLSv1_2 => "TLSv1.2".to_owned(),
+            rustls::ProtocolVersion::TLSv1_3 => "TLSv1.3".to_owned(),
+            _ => format!("{v:?}"),
+        });
+
+        // rustls names TLS 1.3 suites as `TLS13_*`, instead of the standard `TLS_*`.
+        let cipher_suite = conn.negotiated_cipher_suite().map(|s| {
+            let name = format!("{:?}", s.suite());
+            match name.strip_prefix("TLS13_") {
+                Some(rest) => format!("TLS_{rest}"),
+                None => name,
+            }
+        });
+
+        TlsInfo {
+            version,
+            cipher_suite,
+            alpn_protocol: conn.alpn_protocol().map(|p| String::from_utf8_lossy(p).into_owned()),
+            server_name: conn.server_name().map(ToOwned::to_owned),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use rustls::pki_types::ServerName;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        fn fixture(name: &str) -> PathBuf {
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+        }
+
+        #[tokio::test]
+        async fn handshake() {
+            let acceptor = TlsAcceptor::from_pem_files(fixture("localhost.crt"), fixture("localhost.key")).unwrap();
+            acceptor.reload().unwrap();
+
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(fixture("localhost.crt")).unwrap() {
+                roots.add(cert.unwrap()).unwrap();
+            }
+            let mut client_config = rustls::ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            client_config.alpn_protocols = vec![b"h2".to_vec()];
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+            let (client_io, server_io) = tokio::io::duplex(4096);
+
+            let server = tokio::spawn(async move {
+                let (mut stream, info) = acceptor.accept(server_io).await.unwrap();
+                let mut buf = [0; 4];
+                stream.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf, b"ping");
+                info
+            });
+
+            let name = ServerName::try_from("localhost").unwrap();
+            let mut stream = connector.connect(name, client_io).await.unwrap();
+            stream.write_all(b"ping").await.unwrap();
+            stream.flush().await.unwrap();
+
+            let info = server.await.unwrap();
+            assert_eq!(info.version.as_deref(), Some("TLSv1.3"));
+            assert!(info.cipher_suite.as_deref().is_some_and(|s| s.starts_with("TLS_")));
+            assert_eq!(info.alpn_protocol.as_deref(), Some("h2"));
+            assert_eq!(info.server_name.as_deref(), Some("localhost"));
+        }
+
+        #[test]
+        fn invalid_files() {
+            let err = TlsAcceptor::from_pem_files(fixture("localhost.key"), fixture("localhost.key")).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+            let err = TlsAcceptor::from_pem_files(fixture("missing.crt"), fixture("localhost.key")).unwrap_err();
+            assert_ne!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+mod listener {
+    use super::TlsAcceptor;
+
+    use crate::connection::ConnectionInfo;
+
+    use std::io;
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinSet;
+    use tokio_rustls::server::TlsStream;
+    use tracing::{debug, error};
+
+    const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    type Handshake = io::Result<(TlsStream<TcpStream>, ConnectionInfo)>;
+
+    /// A TCP listener which optionally terminates TLS
+    ///
+    /// The TLS handshakes run in background tasks,
+    /// so that a slow client does not block accepting other connections.
+    /// Failed handshakes are logged and skipped.
+    pub struct TlsListener {
+        listener: TcpListener,
+        local_addr: SocketAddr,
+        acceptor: Option<TlsAcceptor>,
+        handshake_timeout: Duration,
+        handshakes: JoinSet<Handshake>,
+    }
+
+    impl TlsListener {
+        /// Creates a listener. Connections are plain TCP if `acceptor` is `None`.
+        ///
+        /// # Errors
+        /// Returns an error if the local address of the listener is unavailable.
+        pub fn new(listener: TcpListener, acceptor: Option<TlsAcceptor>) -> io::Result<Self> {
+            let local_addr = listener.local_addr()?;
+            Ok(Self {
+                listener,
+                local_addr,
+                acceptor,
+                handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+                handshakes: JoinSet::new(),
+            })
+        }
+
+        /// Sets the timeout of TLS handshakes. The default is 10 seconds.
+        pub fn set_handshake_timeout(&mut self, timeout: Duration) {
+            self.handshake_timeout = timeout;
+        }
+
+        #[must_use]
+        pub fn local_addr(&self) -> SocketAddr {
+            self.local_addr
+        }
+
+        #[must_use]
+        pub fn is_tls(&self) -> bool {
+            self.acceptor.is_some()
+        }
+
+        /// Accepts the next connection, after its TLS handshake if TLS is enabled.
+        ///
+        /// This method is cancel safe.
+        ///
+        /// # Errors
+        /// Returns an error if the TCP listener fails to accept a connection.
+        pub async fn accept(&mut self) -> io::Result<(MaybeTlsStream, ConnectionInfo)> {
+            loop {
+                tokio::select! {
+                    res = self.listener.accept() => {
+                        let (socket, peer_addr) = res?;
+                        let connection = ConnectionInfo {
+                            peer_addr: Some(peer_addr),
+                            local_addr: Some(self.local_addr),
+                            tls: None,
+                        };
+
+                        let Some(acceptor) = self.acceptor.clone() else {
+                            return Ok((MaybeTlsStream::Plain(socket), connection));
+                        };
+                        let timeout = self.handshake_timeout;
+                        self.handshakes.spawn(async move {
+                            let (stream, tls) = tokio::time::timeout(timeout, acceptor.accept(socket)).await??;
+                            let connection = ConnectionInfo { tls: Some(tls), ..connection };
+                            Ok((stream, connection))
+                        });
+                    }
+                    Some(res) = self.handshakes.join_next() => {
+                        match res {
+                            Ok(Ok((stream, connection))) => {
+                                return Ok((MaybeTlsStream::Tls(Box::new(stream)), connection));
+                            }
+                            Ok(Err(err)) => debug!("error in TLS handshake: {err}"),
+                            Err(err) => error!("error in TLS handshake task: {err}"),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A connection accepted by [`TlsListener`]
+    pub enum MaybeTlsStream {
+        Plain(TcpStream),
+        Tls(Box<TlsStream<TcpStream>>),
+    }
+
+    impl AsyncRead for MaybeTlsStream {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Plain(s) => Pin::new(s).poll_read(cx, buf),
+                Self::Tls(s) => Pin::new(s).poll_read(cx, buf),
+            }
+        }
+    }
+
+    impl AsyncWrite for MaybeTlsStream {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            match self.get_mut() {
+                Self::Plain(s) => Pin::new(s).poll_write(cx, buf),
+                Self::Tls(s) => Pin::new(s).poll_write(cx, buf),
+            }
+        }
+
+        fn poll_write_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+            match self.get_mut() {
+                Self::Plain(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+                Self::Tls(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+            }
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            match self {
+                Self::Plain(s) => s.is_write_vectored(),
+                Self::Tls(s) => s.is_write_vectored(),
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Plain(s) => Pin::new(s).poll_flush(cx),
+                Self::Tls(s) => Pin::new(s).poll_flush(cx),
+            }
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Plain(s) => Pin::new(s).poll_shutdown(cx),
+                Self::Tls(s) => Pin::new(s).poll_shutdown(cx),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use std::ops::Not;
+        use std::path::Path;
+        use std::sync::Arc;
+
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::{CertificateDer, ServerName};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        fn fixture(name: &str) -> std::path::PathBuf {
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+        }
+
+        #[tokio::test]
+        async fn accept() {
+            let acceptor = TlsAcceptor::from_pem_files(fixture("localhost.crt"), fixture("localhost.key")).unwrap();
+            let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let mut listener = TlsListener::new(tcp, Some(acceptor)).unwrap();
+            listener.set_handshake_timeout(Duration::from_secs(1));
+            let addr = listener.local_addr();
+            assert!(listener.is_tls());
+
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(fixture("localhost.crt")).unwrap() {
+                roots.add(cert.unwrap()).unwrap();
+            }
+            let client_config = rustls::ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+            // A failed handshake does not block the next connection.
+            let mut bad = TcpStream::connect(addr).await.unwrap();
+            bad.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+
+            let client = tokio::spawn(async move {
+                let socket = TcpStream::connect(addr).await.unwrap();
+                let name = ServerName::try_from("localhost").unwrap();
+                let mut stream = connector.connect(name, socket).await.unwrap();
+                stream.write_all(b"ping").await.unwrap();
+                stream.flush().await.unwrap();
+                stream
+            });
+
+            let (mut stream, connection) = listener.accept().await.unwrap();
+            assert!(matches!(stream, MaybeTlsStream::Tls(_)));
+            assert_eq!(connection.local_addr, Some(addr));
+            assert_eq!(connection.tls.unwrap().server_name.as_deref(), Some("localhost"));
+
+            let mut buf = [0; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+            drop(client.await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn accept_plain() {
+            let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let mut listener = TlsListener::new(tcp, None).unwrap();
+            let addr = listener.local_addr();
+            assert!(listener.is_tls().not());
+
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            client.write_all(b"ping").await.unwrap();
+
+            let (mut stream, connection) = listener.accept().await.unwrap();
+            assert!(matches!(stream, MaybeTlsStream::Plain(_)));
+            assert_eq!(connection.peer_addr, Some(client.local_addr().unwrap()));
+            assert_eq!(connection.tls, None);
+
+            let mut buf = [0; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+        }
+    }
+}
@@ -16,6 +16,8 @@ s3s-fs \
     --port          8014            \
     --domain        localhost:8014  \
     --domain        localhost       \
+    --sts                           \
+    --sts-role      example         \
     "$DATA_DIR" | tee target/s3s-fs.log &
 
 sleep 1s